@@ -0,0 +1,784 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use walkdir::WalkDir;
+
+use crate::config::{self, CacheConfig};
+use crate::similarity::fnv1a;
+
+/// File in each central cache directory naming the PDF directory it belongs to
+const SOURCE_FILE: &str = "source.txt";
+
+/// Subdirectory of a cache directory holding page thumbnails
+pub const THUMBNAIL_DIR: &str = "thumbnails";
+
+/// Extension of cache records, after the PDF's file stem
+pub const RECORD_EXTENSION: &str = "record.zst";
+
+/// Extension of the plain text cache files of earlier versions
+const LEGACY_EXTENSION: &str = "txt";
+
+/// Format version written into records; records of other versions read as missing
+pub const RECORD_VERSION: u32 = 2;
+
+/// Start of every uncompressed record
+const RECORD_MAGIC: &[u8; 8] = b"PDFSCANR";
+
+/// zstd compression level of records
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Extraction backend named in records
+pub const EXTRACTION_BACKEND: &str = "pdf-extract";
+pub const EXTRACTION_BACKEND_VERSION: &str = "0.7";
+
+/// Backend named in records migrated from plain text cache files
+pub const LEGACY_BACKEND: &str = "plain-text-cache";
+
+/// Document information entries kept in records
+pub const INFO_KEYS: [&str; 8] = [
+    "Title",
+    "Author",
+    "Subject",
+    "Keywords",
+    "Creator",
+    "Producer",
+    "CreationDate",
+    "ModDate",
+];
+
+/// Custom error type for reading cache records
+#[derive(Debug)]
+pub enum CacheError {
+    IoError(io::Error),
+    /// Not a record, a truncated one, or one of another format version
+    FormatError(String),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::IoError(e) => write!(f, "IO error: {}", e),
+            CacheError::FormatError(e) => write!(f, "Invalid cache record: {}", e),
+        }
+    }
+}
+
+impl Error for CacheError {}
+
+impl From<io::Error> for CacheError {
+    fn from(error: io::Error) -> Self {
+        CacheError::IoError(error)
+    }
+}
+
+/// Extracted text of a PDF and what is known about its extraction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheRecord {
+    pub pages: Vec<String>,
+    /// Document information entries present in the PDF, as `(key, value)`
+    pub metadata: Vec<(String, String)>,
+    pub backend: String,
+    pub backend_version: String,
+    /// Size of the PDF the text was extracted from
+    pub source_size: u64,
+    /// Modification time of that PDF in nanoseconds since the Unix epoch
+    pub source_modified: u64,
+    /// Problems met during extraction that didn't stop it
+    pub warnings: Vec<String>,
+}
+
+impl CacheRecord {
+    /// A record of text extracted without page boundaries, as a single page
+    pub fn from_text(text: &str, backend: &str, backend_version: &str) -> Self {
+        Self {
+            pages: vec![text.to_string()],
+            backend: backend.to_string(),
+            backend_version: backend_version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Text of the whole document, with a line break between pages so that words at
+    /// page boundaries stay apart
+    pub fn text(&self) -> String {
+        self.pages.join("\n")
+    }
+
+    /// Take the size and modification time of the PDF the record is extracted from
+    pub fn with_source(mut self, stamp: (u64, u64)) -> Self {
+        (self.source_size, self.source_modified) = stamp;
+        self
+    }
+
+    /// Whether the PDF is unchanged since the record was extracted from it
+    pub fn is_current(&self, pdf_path: &Path) -> bool {
+        source_stamp(pdf_path) == Some((self.source_size, self.source_modified))
+    }
+
+    /// Value of a document information entry
+    pub fn metadata_value(&self, key: &str) -> Option<&str> {
+        self.metadata.iter().find(|(k, _)| k == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Serialize and compress the record
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut raw = RECORD_MAGIC.to_vec();
+        raw.extend_from_slice(&RECORD_VERSION.to_le_bytes());
+        put_string(&mut raw, &self.backend);
+        put_string(&mut raw, &self.backend_version);
+        raw.extend_from_slice(&self.source_size.to_le_bytes());
+        raw.extend_from_slice(&self.source_modified.to_le_bytes());
+        put_count(&mut raw, self.metadata.len());
+        for (key, value) in &self.metadata {
+            put_string(&mut raw, key);
+            put_string(&mut raw, value);
+        }
+        put_strings(&mut raw, &self.warnings);
+        put_strings(&mut raw, &self.pages);
+
+        zstd::encode_all(raw.as_slice(), COMPRESSION_LEVEL)
+    }
+
+    /// Decompress and read a record written by `encode`
+    pub fn decode(bytes: &[u8]) -> Result<Self, CacheError> {
+        let raw = zstd::decode_all(bytes)?;
+        let mut reader = RecordReader { raw: &raw, position: 0 };
+
+        if reader.take(RECORD_MAGIC.len())? != RECORD_MAGIC {
+            return Err(CacheError::FormatError("not a pdfscan cache record".to_string()));
+        }
+        let version = reader.u32()?;
+        if version != RECORD_VERSION {
+            return Err(CacheError::FormatError(format!("format version {} (expected {})", version, RECORD_VERSION)));
+        }
+
+        let backend = reader.string()?;
+        let backend_version = reader.string()?;
+        let source_size = reader.u64()?;
+        let source_modified = reader.u64()?;
+        let metadata = (0..reader.u32()?)
+            .map(|_| Ok((reader.string()?, reader.string()?)))
+            .collect::<Result<_, CacheError>>()?;
+        let warnings = reader.strings()?;
+        let pages = reader.strings()?;
+
+        Ok(CacheRecord { pages, metadata, backend, backend_version, source_size, source_modified, warnings })
+    }
+}
+
+fn put_count(raw: &mut Vec<u8>, count: usize) {
+    raw.extend_from_slice(&(count as u32).to_le_bytes());
+}
+
+fn put_string(raw: &mut Vec<u8>, value: &str) {
+    put_count(raw, value.len());
+    raw.extend_from_slice(value.as_bytes());
+}
+
+fn put_strings(raw: &mut Vec<u8>, values: &[String]) {
+    put_count(raw, values.len());
+    for value in values {
+        put_string(raw, value);
+    }
+}
+
+/// Reads the fields of an uncompressed record in order
+struct RecordReader<'a> {
+    raw: &'a [u8],
+    position: usize,
+}
+
+impl<'a> RecordReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CacheError> {
+        let end = self.position.checked_add(len)
+            .filter(|&end| end <= self.raw.len())
+            .ok_or_else(|| CacheError::FormatError("record is truncated".to_string()))?;
+        let bytes = &self.raw[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, CacheError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, CacheError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+    }
+
+    fn string(&mut self) -> Result<String, CacheError> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| CacheError::FormatError("text is not UTF-8".to_string()))
+    }
+
+    fn strings(&mut self) -> Result<Vec<String>, CacheError> {
+        (0..self.u32()?).map(|_| self.string()).collect()
+    }
+}
+
+/// Where extracted text and thumbnails are cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheLocation {
+    /// A `.pdfscan` directory next to the PDFs
+    #[default]
+    InTree,
+    /// The user's cache directory, one subdirectory per PDF directory
+    Central,
+    /// Nothing is cached
+    Disabled,
+}
+
+impl CacheLocation {
+    pub const ALL: [CacheLocation; 3] = [CacheLocation::InTree, CacheLocation::Central, CacheLocation::Disabled];
+
+    /// Name used on the command line and in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            CacheLocation::InTree => "in-tree",
+            CacheLocation::Central => "central",
+            CacheLocation::Disabled => "disabled",
+        }
+    }
+}
+
+impl fmt::Display for CacheLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for CacheLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "in-tree" | "intree" | "local" => Ok(CacheLocation::InTree),
+            "central" | "xdg" => Ok(CacheLocation::Central),
+            "disabled" | "none" | "off" => Ok(CacheLocation::Disabled),
+            _ => Err(format!(
+                "unknown cache location '{}' (expected one of: {})",
+                s,
+                CacheLocation::ALL.iter().map(|location| location.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Get the path of the cache record of a PDF
+pub fn get_cache_path(pdf_path: &Path, cache_dir: &Path) -> PathBuf {
+    let pdf_name = pdf_path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    cache_dir.join(format!("{}.{}", pdf_name, RECORD_EXTENSION))
+}
+
+/// Get the path of a PDF's cache record in the cache directory of the PDF's own
+/// directory, where `pdfscan cache` checks it against the PDF
+pub fn record_cache_path(pdf_path: &Path) -> PathBuf {
+    let pdf_dir = pdf_path.parent().unwrap_or_else(|| Path::new("."));
+    get_cache_path(pdf_path, &get_cache_directory(pdf_dir))
+}
+
+/// Path of the plain text cache file an earlier version wrote where a record now goes
+fn legacy_cache_path(cache_path: &Path) -> Option<PathBuf> {
+    let file_name = cache_path.file_name()?.to_str()?;
+    let stem = file_name.strip_suffix(RECORD_EXTENSION)?.strip_suffix('.')?;
+    Some(cache_path.with_file_name(format!("{}.{}", stem, LEGACY_EXTENSION)))
+}
+
+/// Get the cache directory for a directory of PDFs, as configured. A central cache
+/// directory is created with a note of the PDF directory, for `pdfscan cache` to
+/// report and prune.
+pub fn get_cache_directory(pdf_dir: &Path) -> PathBuf {
+    let cache = &config::get().cache;
+    let cache_dir = cache_directory_for(pdf_dir, cache);
+    if cache.location == CacheLocation::Central {
+        let note = cache_dir.join(SOURCE_FILE);
+        if !note.exists() {
+            // A failure shows up when the cache is written
+            let _ = fs::create_dir_all(&cache_dir).and_then(|_| fs::write(&note, absolute(pdf_dir).to_string_lossy().as_bytes()));
+        }
+    }
+    cache_dir
+}
+
+/// The cache directory of a directory of PDFs under a cache configuration. When caching
+/// is disabled this is the in-tree directory, which is then never read or written.
+pub fn cache_directory_for(pdf_dir: &Path, cache: &CacheConfig) -> PathBuf {
+    match (cache.location, cache.central_root()) {
+        (CacheLocation::Central, Some(root)) => root.join(directory_key(pdf_dir)),
+        _ => pdf_dir.join(&cache.directory_name),
+    }
+}
+
+/// Name of a directory's central cache: a hash of its absolute path, so that two
+/// directories with the same name don't share a cache
+pub fn directory_key(pdf_dir: &Path) -> String {
+    // Unlike std's hasher, FNV-1a is the same across builds
+    format!("{:016x}", fnv1a(absolute(pdf_dir).to_string_lossy().as_bytes()))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| {
+        std::env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+/// Whether cached files are read and written
+pub fn caching_enabled() -> bool {
+    config::get().cache.location != CacheLocation::Disabled
+}
+
+/// Size and modification time of a file, as kept in records
+pub fn source_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64);
+    Some((metadata.len(), modified))
+}
+
+/// Load the cache record of a PDF unless the PDF changed after it was written. A plain
+/// text cache file of an earlier version is read instead and replaced by a record.
+pub fn load_record_from_cache(pdf_path: &Path, cache_path: &Path) -> Option<CacheRecord> {
+    if !caching_enabled() {
+        return None;
+    }
+
+    match fs::read(cache_path) {
+        Ok(bytes) => CacheRecord::decode(&bytes).ok().filter(|record| record.is_current(pdf_path)),
+        Err(_) => migrate_legacy_cache(pdf_path, cache_path),
+    }
+}
+
+/// Turn the plain text cache file of a record into the record, unless the PDF is newer
+fn migrate_legacy_cache(pdf_path: &Path, cache_path: &Path) -> Option<CacheRecord> {
+    let legacy_path = legacy_cache_path(cache_path)?;
+    let stamp = source_stamp(pdf_path)?;
+    let pdf_modified = fs::metadata(pdf_path).and_then(|m| m.modified()).ok()?;
+    let text_modified = fs::metadata(&legacy_path).and_then(|m| m.modified()).ok()?;
+    if text_modified < pdf_modified {
+        return None;
+    }
+    let text = fs::read_to_string(&legacy_path).ok()?;
+
+    let mut record = CacheRecord::from_text(&text, LEGACY_BACKEND, "").with_source(stamp);
+    record.warnings.push("Migrated from a plain text cache; page boundaries and metadata are unknown".to_string());
+    if save_record_to_cache(cache_path, &record).is_ok() {
+        let _ = fs::remove_file(&legacy_path);
+    }
+    Some(record)
+}
+
+/// Load the text of a PDF from its cache record if it is current
+pub fn load_text_from_cache(pdf_path: &Path, cache_path: &Path) -> Option<String> {
+    load_record_from_cache(pdf_path, cache_path).map(|record| record.text())
+}
+
+/// Save a cache record
+pub fn save_record_to_cache(cache_path: &Path, record: &CacheRecord) -> Result<(), Box<dyn std::error::Error>> {
+    if !caching_enabled() {
+        return Ok(());
+    }
+    // Create parent directory if it doesn't exist
+    if let Some(parent) = cache_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(cache_path, record.encode()?)?;
+    Ok(())
+}
+
+/// Save text extracted without page boundaries or metadata to the cache
+pub fn save_text_to_cache(cache_path: &Path, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    save_record_to_cache(cache_path, &CacheRecord::from_text(text, EXTRACTION_BACKEND, EXTRACTION_BACKEND_VERSION))
+}
+
+/// Extract the text of each page and the document information of a PDF, in a worker
+/// process if extraction is isolated
+pub fn extract_record(pdf_path: &Path) -> Result<CacheRecord, String> {
+    let isolation = &config::get().isolation;
+    if isolation.enabled {
+        return crate::isolation::extract_isolated(pdf_path, isolation).map_err(|e| e.to_string());
+    }
+    extract_record_in_process(pdf_path)
+}
+
+/// Extract the text of each page and the document information of a PDF in this
+/// process, turning a panic in the extractor into an error
+pub fn extract_record_in_process(pdf_path: &Path) -> Result<CacheRecord, String> {
+    // Taken before reading, so that a change during extraction makes the record stale
+    let stamp = source_stamp(pdf_path).ok_or_else(|| format!("Cannot read {}", pdf_path.display()))?;
+    let bytes = fs::read(pdf_path).map_err(|e| e.to_string())?;
+    let pages = match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(&bytes)) {
+        Ok(Ok(pages)) => pages,
+        Ok(Err(e)) => return Err(format!("Cannot extract text: {}", e)),
+        Err(_) => return Err("Text extraction crashed (malformed PDF?)".to_string()),
+    };
+
+    let mut record = CacheRecord {
+        pages,
+        backend: EXTRACTION_BACKEND.to_string(),
+        backend_version: EXTRACTION_BACKEND_VERSION.to_string(),
+        ..Default::default()
+    }
+    .with_source(stamp);
+    match lopdf::Document::load_mem(&bytes) {
+        Ok(document) => record.metadata = document_metadata(&document),
+        Err(e) => record.warnings.push(format!("Cannot read document information: {}", e)),
+    }
+    if record.pages.iter().all(|page| page.trim().is_empty()) {
+        record.warnings.push("No text found; the pages may be scanned images".to_string());
+    }
+
+    Ok(record)
+}
+
+/// The `INFO_KEYS` entries of a PDF's document information that are present
+pub fn document_metadata(document: &lopdf::Document) -> Vec<(String, String)> {
+    let Ok(info) = document.trailer.get_deref(b"Info", document).and_then(|info| info.as_dict()) else {
+        return Vec::new();
+    };
+
+    INFO_KEYS.iter()
+        .filter_map(|key| {
+            let value = info.get_deref(key.as_bytes(), document).ok()?.as_str().ok()?;
+            Some((key.to_string(), crate::trends::pdf_text_string(value)))
+        })
+        .collect()
+}
+
+/// A PDF's cache record, extracted and saved first if there is none
+pub fn load_or_extract_record(pdf_path: &Path, cache_path: &Path) -> Result<CacheRecord, String> {
+    if let Some(record) = load_record_from_cache(pdf_path, cache_path) {
+        return Ok(record);
+    }
+
+    let record = extract_record(pdf_path)?;
+    // A cache that can't be written only costs the next extraction
+    let _ = save_record_to_cache(cache_path, &record);
+    Ok(record)
+}
+
+/// A cache directory and the directory of PDFs it caches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub cache_dir: PathBuf,
+    pub pdf_dir: PathBuf,
+}
+
+/// Totals over cache directories, or what was removed from them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub directories: usize,
+    pub texts: usize,
+    pub thumbnails: usize,
+    pub bytes: u64,
+    /// Cached files of PDFs that were removed or changed since
+    pub stale: usize,
+    /// Cache directories whose directory of PDFs is gone
+    pub orphaned: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} directories, {} texts, {} thumbnails, {:.2} MB",
+            self.directories,
+            self.texts,
+            self.thumbnails,
+            self.bytes as f64 / (1024.0 * 1024.0)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CachedKind {
+    Text,
+    Thumbnail,
+    Other,
+}
+
+/// A file in a cache directory
+struct CachedFile {
+    path: PathBuf,
+    kind: CachedKind,
+    bytes: u64,
+    stale: bool,
+}
+
+/// Whether a file name is a `directory_key`
+fn is_directory_key(name: &str) -> bool {
+    name.len() == 16 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// The central cache directories, optionally only those caching the given directories
+/// or their subdirectories. Only directories named by `directory_key` that note their
+/// PDF directory count; anything else under the root is left alone.
+pub fn central_entries(root: &Path, directories: &[PathBuf]) -> io::Result<Vec<CacheEntry>> {
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let directories: Vec<PathBuf> = directories.iter().map(|dir| absolute(dir)).collect();
+    let mut entries = Vec::new();
+    for item in fs::read_dir(root)? {
+        let cache_dir = item?.path();
+        let keyed = cache_dir.file_name().and_then(|name| name.to_str()).is_some_and(is_directory_key);
+        if !keyed || !cache_dir.is_dir() {
+            continue;
+        }
+
+        let Ok(note) = fs::read_to_string(cache_dir.join(SOURCE_FILE)) else {
+            continue;
+        };
+        let pdf_dir = PathBuf::from(note.trim_end());
+        let wanted = directories.is_empty() || directories.iter().any(|dir| pdf_dir.starts_with(dir));
+        if wanted {
+            entries.push(CacheEntry { cache_dir, pdf_dir });
+        }
+    }
+
+    entries.sort_by(|a, b| a.cache_dir.cmp(&b.cache_dir));
+    Ok(entries)
+}
+
+/// Whether a directory holds nothing but cache records, legacy text files and
+/// thumbnails, and holds something
+fn has_cache_contents(dir: &Path) -> bool {
+    let Ok(items) = fs::read_dir(dir) else {
+        return false;
+    };
+
+    let mut any = false;
+    for item in items {
+        let Ok(item) = item else {
+            return false;
+        };
+        let path = item.path();
+        let name = item.file_name().to_string_lossy().into_owned();
+        let recognised = if path.is_dir() {
+            name == THUMBNAIL_DIR && fs::read_dir(&path).is_ok_and(|thumbnails| thumbnails
+                .filter_map(|thumbnail| thumbnail.ok())
+                .all(|thumbnail| thumbnail.path().is_file() && thumbnail.file_name().to_string_lossy().ends_with(".png")))
+        } else {
+            [RECORD_EXTENSION, LEGACY_EXTENSION].iter().any(|extension| name.ends_with(&format!(".{}", extension)))
+        };
+        if !recognised {
+            return false;
+        }
+        any = true;
+    }
+    any
+}
+
+/// The in-tree cache directories named `name` in the given directories and their
+/// subdirectories. A directory of that name holding anything but cached files is not
+/// taken for a cache.
+pub fn in_tree_entries(directories: &[PathBuf], name: &str) -> Vec<CacheEntry> {
+    let mut entries: Vec<CacheEntry> = directories
+        .iter()
+        .flat_map(|dir| WalkDir::new(dir).into_iter().filter_map(|e| e.ok()))
+        .filter(|entry| entry.path().is_dir() && entry.path().file_name().is_some_and(|file_name| file_name == name))
+        .filter(|entry| has_cache_contents(entry.path()))
+        .filter_map(|entry| Some(CacheEntry {
+            cache_dir: entry.path().to_path_buf(),
+            pdf_dir: entry.path().parent()?.to_path_buf(),
+        }))
+        .collect();
+
+    entries.sort_by(|a, b| a.cache_dir.cmp(&b.cache_dir));
+    entries.dedup();
+    entries
+}
+
+/// Modification times of the PDFs in a directory, by file stem
+fn pdf_modification_times(pdf_dir: &Path) -> HashMap<String, SystemTime> {
+    let Ok(items) = fs::read_dir(pdf_dir) else {
+        return HashMap::new();
+    };
+
+    items
+        .filter_map(|item| item.ok())
+        .map(|item| item.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("pdf")))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+            Some((path.file_stem()?.to_string_lossy().into_owned(), modified))
+        })
+        .collect()
+}
+
+/// The files of a cache directory, with those of removed or changed PDFs marked stale
+fn cached_files(entry: &CacheEntry) -> Vec<CachedFile> {
+    let orphaned = !entry.pdf_dir.is_dir();
+    let pdfs = pdf_modification_times(&entry.pdf_dir);
+
+    WalkDir::new(&entry.cache_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|item| item.path().is_file())
+        .map(|item| {
+            let path = item.path().to_path_buf();
+            let metadata = fs::metadata(&path).ok();
+            let in_thumbnails = path.parent().and_then(Path::file_name).is_some_and(|dir| dir == THUMBNAIL_DIR);
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+
+            // Texts are `<stem>.record.zst` or `<stem>.txt`, thumbnails `<stem>_p<page>_<dpi>dpi.png`
+            let text_stem = [RECORD_EXTENSION, LEGACY_EXTENSION]
+                .iter()
+                .filter(|_| path.parent() == Some(entry.cache_dir.as_path()) && file_name != SOURCE_FILE)
+                .find_map(|extension| file_name.strip_suffix(extension)?.strip_suffix('.'));
+            let (kind, stem) = if let Some(stem) = text_stem {
+                (CachedKind::Text, Some(stem.to_string()))
+            } else if in_thumbnails && file_name.ends_with(".png") {
+                (CachedKind::Thumbnail, file_name.rsplitn(3, '_').nth(2).map(String::from))
+            } else {
+                (CachedKind::Other, None)
+            };
+
+            let cached_at = metadata.as_ref().and_then(|metadata| metadata.modified().ok());
+            let stale = match (kind, stem) {
+                (CachedKind::Other, _) => false,
+                _ if orphaned => true,
+                (_, Some(stem)) => match (pdfs.get(&stem), cached_at) {
+                    (Some(pdf_modified), Some(cached_at)) => *pdf_modified > cached_at,
+                    _ => true,
+                },
+                (_, None) => true,
+            };
+
+            CachedFile { path, kind, bytes: metadata.map(|metadata| metadata.len()).unwrap_or(0), stale }
+        })
+        .collect()
+}
+
+fn add_file(stats: &mut CacheStats, file: &CachedFile) {
+    match file.kind {
+        CachedKind::Text => stats.texts += 1,
+        CachedKind::Thumbnail => stats.thumbnails += 1,
+        CachedKind::Other => {},
+    }
+    stats.bytes += file.bytes;
+    if file.stale {
+        stats.stale += 1;
+    }
+}
+
+/// Count the files in cache directories
+pub fn cache_stats(entries: &[CacheEntry]) -> CacheStats {
+    let mut stats = CacheStats::default();
+    for entry in entries {
+        stats.directories += 1;
+        if !entry.pdf_dir.is_dir() {
+            stats.orphaned += 1;
+        }
+        for file in cached_files(entry) {
+            add_file(&mut stats, &file);
+        }
+    }
+    stats
+}
+
+/// Remove the cached files of removed and changed PDFs, and cache directories left
+/// without any; returns what was removed
+pub fn prune(entries: &[CacheEntry]) -> io::Result<CacheStats> {
+    let mut removed = CacheStats::default();
+    for entry in entries {
+        let files = cached_files(entry);
+        for file in files.iter().filter(|file| file.stale) {
+            fs::remove_file(&file.path)?;
+            add_file(&mut removed, file);
+        }
+
+        let kept = files.iter().any(|file| !file.stale && file.kind != CachedKind::Other);
+        if !kept {
+            fs::remove_dir_all(&entry.cache_dir)?;
+            removed.directories += 1;
+            if !entry.pdf_dir.is_dir() {
+                removed.orphaned += 1;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Remove cache directories entirely; returns what was removed
+pub fn clear(entries: &[CacheEntry]) -> io::Result<CacheStats> {
+    let removed = cache_stats(entries);
+    for entry in entries {
+        fs::remove_dir_all(&entry.cache_dir)?;
+    }
+    Ok(removed)
+}
+
+/// The cache directories `pdfscan cache` works on: the central ones, and the in-tree
+/// ones under the given directories
+fn managed_entries(directories: &[PathBuf]) -> Result<(Vec<CacheEntry>, Vec<CacheEntry>), Box<dyn Error>> {
+    for dir in directories {
+        if !dir.is_dir() {
+            return Err(format!("'{}' is not a directory", dir.display()).into());
+        }
+    }
+
+    let cache = &config::get().cache;
+    let central = match cache.central_root() {
+        Some(root) => central_entries(&root, directories)?,
+        None => Vec::new(),
+    };
+    Ok((central, in_tree_entries(directories, &cache.directory_name)))
+}
+
+/// Print the size of the caches
+pub fn run_stats(directories: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    let cache = &config::get().cache;
+    let (central, in_tree) = managed_entries(directories)?;
+
+    println!("Cache location: {}", cache.location);
+    match cache.central_root() {
+        Some(root) => println!("Central cache ({}): {}", root.display(), cache_stats(&central)),
+        None => println!("Central cache: no cache directory on this system"),
+    }
+    if directories.is_empty() {
+        println!("In-tree caches: give directories to look for '{}' directories in", cache.directory_name);
+    } else {
+        println!("In-tree caches ('{}'): {}", cache.directory_name, cache_stats(&in_tree));
+    }
+
+    let all: Vec<CacheEntry> = central.into_iter().chain(in_tree).collect();
+    let stats = cache_stats(&all);
+    if stats.stale > 0 || stats.orphaned > 0 {
+        println!(
+            "{} cached files of removed or changed PDFs and {} directories whose PDFs are gone; `pdfscan cache prune` removes them",
+            stats.stale, stats.orphaned
+        );
+    }
+    Ok(())
+}
+
+/// Remove cached files that are out of date
+pub fn run_prune(directories: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    let (central, in_tree) = managed_entries(directories)?;
+    let all: Vec<CacheEntry> = central.into_iter().chain(in_tree).collect();
+    let removed = prune(&all)?;
+    println!("Removed {} stale files and {} cache directories ({:.2} MB)", removed.stale, removed.directories, removed.bytes as f64 / (1024.0 * 1024.0));
+    Ok(())
+}
+
+/// Remove whole caches: the central one, or those of the given directories
+pub fn run_clear(directories: &[PathBuf]) -> Result<(), Box<dyn Error>> {
+    let (central, in_tree) = managed_entries(directories)?;
+    let all: Vec<CacheEntry> = central.into_iter().chain(in_tree).collect();
+    let removed = clear(&all)?;
+    println!("Removed {}", removed);
+    Ok(())
+}
@@ -0,0 +1,336 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::cache::CacheLocation;
+use crate::isolation::IsolationConfig;
+use crate::stats::{AnalysisOptions, CooccurrenceWindow, CorrelationMeasure, RankingMethod};
+use crate::stopwords::Language;
+
+/// Name of the per-project configuration file, looked up in the current directory
+/// and its parents
+pub const PROJECT_CONFIG_FILE: &str = ".pdfscan.toml";
+
+/// The configuration in effect, set once at startup
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Custom error type for configuration files
+#[derive(Debug)]
+pub enum ConfigError {
+    IoError(PathBuf, std::io::Error),
+    ParseError(PathBuf, String),
+    /// A known key with a value of the wrong type or out of range
+    InvalidValue(PathBuf, String, String),
+    UnknownKey(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::IoError(path, e) => write!(f, "Cannot read {}: {}", path.display(), e),
+            ConfigError::ParseError(path, e) => write!(f, "Invalid TOML in {}: {}", path.display(), e),
+            ConfigError::InvalidValue(path, key, e) => write!(f, "{}: {} {}", path.display(), key, e),
+            ConfigError::UnknownKey(path, key) => write!(f, "{}: unknown setting '{}'", path.display(), key),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Defaults for searches
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchConfig {
+    /// Directories `pdfscan search` looks in without `--directories`; the home
+    /// directory if empty
+    pub roots: Vec<PathBuf>,
+    /// Characters of context shown before and after a match in the GUI
+    pub context_chars: usize,
+}
+
+/// Defaults for the GUI's PDF viewer
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewerConfig {
+    /// Resolution pages are rendered at in the main view
+    pub dpi: u32,
+    /// Resolution of the sidebar thumbnails
+    pub thumbnail_dpi: u32,
+}
+
+/// Defaults for `pdfscan analyze`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisConfig {
+    pub measure: CorrelationMeasure,
+    pub threshold: f64,
+    pub ranking: RankingMethod,
+    pub window: CooccurrenceWindow,
+    pub language: Language,
+}
+
+/// Where extracted text is cached
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheConfig {
+    pub location: CacheLocation,
+    /// Name of the cache directory created next to the PDFs
+    pub directory_name: String,
+    /// Root of the central cache; `pdfscan` in the user's cache directory if unset
+    pub central_directory: Option<PathBuf>,
+}
+
+impl CacheConfig {
+    /// Root of the central cache, if there is one on this system
+    pub fn central_root(&self) -> Option<PathBuf> {
+        self.central_directory.clone().or_else(|| dirs::cache_dir().map(|dir| dir.join("pdfscan")))
+    }
+}
+
+/// Settings shared by the command line and the GUI
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub search: SearchConfig,
+    pub viewer: ViewerConfig,
+    pub analysis: AnalysisConfig,
+    pub cache: CacheConfig,
+    pub isolation: IsolationConfig,
+    /// Worker threads for parallel extraction; all cores if unset
+    pub threads: Option<usize>,
+    /// Files the settings were read from, in the order they were applied
+    pub sources: Vec<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            search: SearchConfig { roots: Vec::new(), context_chars: 40 },
+            viewer: ViewerConfig { dpi: 120, thumbnail_dpi: 18 },
+            analysis: AnalysisConfig {
+                measure: CorrelationMeasure::default(),
+                threshold: 0.1,
+                ranking: RankingMethod::default(),
+                window: CooccurrenceWindow::default(),
+                language: Language::default(),
+            },
+            cache: CacheConfig {
+                location: CacheLocation::default(),
+                directory_name: ".pdfscan".to_string(),
+                central_directory: None,
+            },
+            isolation: IsolationConfig::default(),
+            threads: None,
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Options for a keyword analysis with the configured defaults
+    pub fn analysis_options(&self) -> AnalysisOptions {
+        AnalysisOptions {
+            measure: self.analysis.measure,
+            threshold: self.analysis.threshold,
+            ranking: self.analysis.ranking,
+            window: self.analysis.window,
+            ..Default::default()
+        }
+    }
+
+    /// Apply the settings of a TOML document on top of these; relative paths are
+    /// resolved against `path`'s directory
+    pub fn merge_toml(&mut self, content: &str, path: &Path) -> Result<(), ConfigError> {
+        let table: toml::Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| ConfigError::ParseError(path.to_path_buf(), e.to_string()))?;
+        let base = path.parent().unwrap_or_else(|| Path::new("."));
+
+        for (section, values) in &table {
+            let toml::Value::Table(values) = values else {
+                return Err(ConfigError::InvalidValue(path.to_path_buf(), section.clone(), "must be a [section]".to_string()));
+            };
+
+            for (key, value) in values {
+                let name = format!("{}.{}", section, key);
+                let invalid = |message: &str| ConfigError::InvalidValue(path.to_path_buf(), name.clone(), message.to_string());
+
+                match name.as_str() {
+                    "search.roots" => {
+                        let toml::Value::Array(roots) = value else {
+                            return Err(invalid("must be a list of directories"));
+                        };
+                        self.search.roots = roots
+                            .iter()
+                            .map(|root| root.as_str().map(|root| base.join(root)).ok_or_else(|| invalid("must be a list of directories")))
+                            .collect::<Result<_, _>>()?;
+                    },
+                    "search.context_chars" => self.search.context_chars = positive(value).ok_or_else(|| invalid("must be a positive number"))?,
+                    "viewer.dpi" => self.viewer.dpi = positive(value).ok_or_else(|| invalid("must be a positive number"))?,
+                    "viewer.thumbnail_dpi" => self.viewer.thumbnail_dpi = positive(value).ok_or_else(|| invalid("must be a positive number"))?,
+                    "analysis.measure" => self.analysis.measure = parsed(value).map_err(|e| invalid(&e))?,
+                    "analysis.threshold" => {
+                        self.analysis.threshold = value.as_float()
+                            .or_else(|| value.as_integer().map(|i| i as f64))
+                            .ok_or_else(|| invalid("must be a number"))?;
+                    },
+                    "analysis.ranking" => self.analysis.ranking = parsed(value).map_err(|e| invalid(&e))?,
+                    "analysis.window" => self.analysis.window = parsed(value).map_err(|e| invalid(&e))?,
+                    "analysis.language" => self.analysis.language = parsed(value).map_err(|e| invalid(&e))?,
+                    "cache.location" => self.cache.location = parsed(value).map_err(|e| invalid(&e))?,
+                    "cache.central_directory" => {
+                        let directory = value.as_str().filter(|directory| !directory.is_empty());
+                        self.cache.central_directory = Some(base.join(directory.ok_or_else(|| invalid("must be a directory"))?));
+                    },
+                    "cache.directory_name" => {
+                        let name = value.as_str().filter(|name| !name.is_empty() && !name.contains(['/', '\\']));
+                        self.cache.directory_name = name.ok_or_else(|| invalid("must be a directory name"))?.to_string();
+                    },
+                    "extraction.isolate" => self.isolation.enabled = value.as_bool().ok_or_else(|| invalid("must be true or false"))?,
+                    "extraction.timeout_secs" => {
+                        self.isolation.timeout = Duration::from_secs(positive(value).ok_or_else(|| invalid("must be a positive number"))?);
+                    },
+                    "extraction.memory_limit_mb" => {
+                        // 0 turns the limit off
+                        self.isolation.memory_limit_mb = match value.as_integer() {
+                            Some(0) => None,
+                            _ => Some(positive(value).ok_or_else(|| invalid("must be a number of MB, or 0 for no limit"))?),
+                        };
+                    },
+                    "performance.threads" => self.threads = Some(positive(value).ok_or_else(|| invalid("must be a positive number"))?),
+                    _ => return Err(ConfigError::UnknownKey(path.to_path_buf(), name)),
+                }
+            }
+        }
+
+        self.sources.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Render the settings as a TOML document, as printed by `pdfscan config show`
+    pub fn to_toml(&self) -> String {
+        let mut toml = String::new();
+        if self.sources.is_empty() {
+            toml.push_str("# No configuration files found; these are the built-in defaults\n");
+        }
+        for source in &self.sources {
+            toml.push_str(&format!("# Read from {}\n", source.display()));
+        }
+
+        let roots: Vec<String> = self.search.roots.iter().map(|root| toml_string(&root.to_string_lossy())).collect();
+        toml.push_str("\n[search]\n");
+        toml.push_str(&format!("roots = [{}]\n", roots.join(", ")));
+        toml.push_str(&format!("context_chars = {}\n", self.search.context_chars));
+
+        toml.push_str("\n[viewer]\n");
+        toml.push_str(&format!("dpi = {}\n", self.viewer.dpi));
+        toml.push_str(&format!("thumbnail_dpi = {}\n", self.viewer.thumbnail_dpi));
+
+        toml.push_str("\n[analysis]\n");
+        toml.push_str(&format!("measure = {}\n", toml_string(self.analysis.measure.name())));
+        toml.push_str(&format!("threshold = {:?}\n", self.analysis.threshold));
+        toml.push_str(&format!("ranking = {}\n", toml_string(self.analysis.ranking.name())));
+        toml.push_str(&format!("window = {}\n", toml_string(&self.analysis.window.to_string())));
+        toml.push_str(&format!("language = {}\n", toml_string(self.analysis.language.name())));
+
+        toml.push_str("\n[cache]\n");
+        toml.push_str(&format!("location = {}\n", toml_string(self.cache.location.name())));
+        toml.push_str(&format!("directory_name = {}\n", toml_string(&self.cache.directory_name)));
+        match &self.cache.central_directory {
+            Some(directory) => toml.push_str(&format!("central_directory = {}\n", toml_string(&directory.to_string_lossy()))),
+            None => toml.push_str("# central_directory = <user cache directory>/pdfscan\n"),
+        }
+
+        toml.push_str("\n[extraction]\n");
+        toml.push_str(&format!("isolate = {}\n", self.isolation.enabled));
+        toml.push_str(&format!("timeout_secs = {}\n", self.isolation.timeout.as_secs()));
+        toml.push_str(&format!("memory_limit_mb = {}\n", self.isolation.memory_limit_mb.unwrap_or(0)));
+
+        toml.push_str("\n[performance]\n");
+        match self.threads {
+            Some(threads) => toml.push_str(&format!("threads = {}\n", threads)),
+            None => toml.push_str("# threads = <number of cores>\n"),
+        }
+
+        toml
+    }
+}
+
+/// A positive integer setting
+fn positive<T: TryFrom<i64>>(value: &toml::Value) -> Option<T> {
+    value.as_integer().filter(|&i| i > 0).and_then(|i| T::try_from(i).ok())
+}
+
+/// A setting given by name, such as a correlation measure
+fn parsed<T: std::str::FromStr<Err = String>>(value: &toml::Value) -> Result<T, String> {
+    value.as_str().ok_or_else(|| "must be a string".to_string())?.parse()
+}
+
+/// Quote a TOML basic string
+fn toml_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Directory holding pdfscan's configuration and state files
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("pdfscan"))
+}
+
+/// The user's configuration file, `config.toml` in the pdfscan config directory
+pub fn user_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// The nearest `.pdfscan.toml` in a directory or its parents
+pub fn project_config_path(start: &Path) -> Option<PathBuf> {
+    start.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Read the user's configuration and then the project's, each overriding the defaults
+/// and what was read before
+pub fn load() -> Result<Config, ConfigError> {
+    let mut config = Config::default();
+    let project = std::env::current_dir().ok().and_then(|dir| project_config_path(&dir));
+
+    for path in user_config_path().into_iter().chain(project) {
+        if !path.is_file() {
+            continue;
+        }
+        let content = fs::read_to_string(&path).map_err(|e| ConfigError::IoError(path.clone(), e))?;
+        config.merge_toml(&content, &path)?;
+    }
+
+    Ok(config)
+}
+
+/// Make a configuration the one in effect; ignored once `get` has been called
+pub fn init(config: Config) {
+    if let Some(threads) = config.threads {
+        // Fails only if the pool already exists
+        let _ = rayon::ThreadPoolBuilder::new().num_threads(threads).build_global();
+    }
+    let _ = CONFIG.set(config);
+}
+
+/// The configuration in effect, loaded on first use if `init` wasn't called;
+/// an unreadable configuration is reported and the defaults used
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        load().unwrap_or_else(|e| {
+            eprintln!("Error: {}; using the default settings", e);
+            Config::default()
+        })
+    })
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::str::FromStr;
+
+use crate::stats::{extract_texts, StatsError};
+use crate::stopwords::{Language, Stopwords};
+
+/// How candidate keywords are scored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiscoveryMethod {
+    /// Term frequency weighted by inverse document frequency
+    #[default]
+    TfIdf,
+    /// Rapid Automatic Keyword Extraction: word degree over frequency within phrases
+    Rake,
+    /// PageRank over the graph of adjacent content words
+    TextRank,
+}
+
+impl DiscoveryMethod {
+    pub const ALL: [DiscoveryMethod; 3] = [
+        DiscoveryMethod::TfIdf,
+        DiscoveryMethod::Rake,
+        DiscoveryMethod::TextRank,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiscoveryMethod::TfIdf => "tfidf",
+            DiscoveryMethod::Rake => "rake",
+            DiscoveryMethod::TextRank => "textrank",
+        }
+    }
+}
+
+impl fmt::Display for DiscoveryMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DiscoveryMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tfidf" | "tf-idf" => Ok(DiscoveryMethod::TfIdf),
+            "rake" => Ok(DiscoveryMethod::Rake),
+            "textrank" | "text-rank" => Ok(DiscoveryMethod::TextRank),
+            _ => Err(format!(
+                "unknown discovery method '{}' (expected one of: {})",
+                s,
+                DiscoveryMethod::ALL.iter().map(|m| m.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Options for keyword discovery
+#[derive(Debug, Clone, Copy)]
+pub struct DiscoveryOptions {
+    pub method: DiscoveryMethod,
+    pub language: Language,
+    /// Number of terms to propose, corpus-wide and per document
+    pub top: usize,
+    /// Longest phrase considered, in words
+    pub max_ngram: usize,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            method: DiscoveryMethod::default(),
+            language: Language::default(),
+            top: 10,
+            max_ngram: 3,
+        }
+    }
+}
+
+/// A proposed keyword or key phrase
+#[derive(Debug, Clone)]
+pub struct Term {
+    pub text: String,
+    pub score: f64,
+    /// Number of documents containing the term
+    pub document_frequency: usize,
+}
+
+/// The top terms of one document
+#[derive(Debug, Clone)]
+pub struct DocumentTerms {
+    pub path: PathBuf,
+    pub terms: Vec<Term>,
+}
+
+/// Result of keyword discovery over a set of documents
+#[derive(Debug, Clone)]
+pub struct DiscoveryReport {
+    pub method: DiscoveryMethod,
+    pub language: Language,
+    /// Top terms over all documents
+    pub corpus: Vec<Term>,
+    pub documents: Vec<DocumentTerms>,
+}
+
+impl DiscoveryReport {
+    /// The corpus-wide terms, ready to be passed to the correlation analysis
+    pub fn keywords(&self) -> Vec<String> {
+        self.corpus.iter().map(|term| term.text.clone()).collect()
+    }
+
+    /// Render the plain text report written by `pdfscan discover`
+    pub fn to_text(&self) -> String {
+        let mut report = String::new();
+        report.push_str("PDFScan Keyword Discovery Report\n");
+        report.push_str("================================\n\n");
+        report.push_str(&format!("Method: {}\n", self.method));
+        report.push_str(&format!("Stopwords: {}\n", self.language));
+        report.push_str(&format!("Total documents analyzed: {}\n\n", self.documents.len()));
+
+        report.push_str("Top Terms Across All Documents:\n");
+        report.push_str("==========================================\n");
+        for (i, term) in self.corpus.iter().enumerate() {
+            report.push_str(&format!(
+                "{}. {} (score: {:.2}, documents: {})\n",
+                i + 1,
+                term.text,
+                term.score,
+                term.document_frequency
+            ));
+        }
+
+        let quoted: Vec<String> = self.corpus.iter().map(|term| format!("\"{}\"", term.text)).collect();
+        report.push_str(&format!("\nSuggested keywords: --keywords {}\n", quoted.join(" ")));
+
+        report.push_str("\n\nTop Terms per Document:\n");
+        report.push_str("==========================================\n");
+        for document in &self.documents {
+            let filename = document.path.file_name().unwrap_or_default().to_string_lossy();
+            let terms: Vec<String> = document.terms
+                .iter()
+                .map(|term| format!("{} ({:.2})", term.text, term.score))
+                .collect();
+            report.push_str(&format!("{}: {}\n", filename, terms.join(", ")));
+        }
+
+        report
+    }
+}
+
+/// Phrases of more than one word must occur at least this often to be proposed
+const MIN_PHRASE_COUNT: usize = 2;
+
+/// TextRank damping factor and iteration limit
+const DAMPING: f64 = 0.85;
+const TEXTRANK_ITERATIONS: usize = 50;
+
+/// Content words of one text: segments split at punctuation, each split into
+/// runs of consecutive non-stopwords
+type ContentWords = Vec<Vec<Vec<String>>>;
+
+/// Propose keywords for already extracted document texts
+pub fn discover_texts(texts: &[(PathBuf, String)], options: &DiscoveryOptions) -> DiscoveryReport {
+    let stopwords = Stopwords::new(&[options.language]);
+    let max_ngram = options.max_ngram.max(1);
+
+    let content: Vec<ContentWords> = texts
+        .iter()
+        .map(|(_, text)| content_words(text, &stopwords))
+        .collect();
+    let document_counts: Vec<HashMap<String, usize>> = content
+        .iter()
+        .map(|segments| ngram_counts(segments, max_ngram))
+        .collect();
+
+    let mut document_frequency: HashMap<String, usize> = HashMap::new();
+    let mut corpus_counts: HashMap<String, usize> = HashMap::new();
+    for counts in &document_counts {
+        for (term, &count) in counts {
+            *document_frequency.entry(term.clone()).or_insert(0) += 1;
+            *corpus_counts.entry(term.clone()).or_insert(0) += count;
+        }
+    }
+
+    let (document_scores, corpus_scores) = match options.method {
+        DiscoveryMethod::TfIdf => tfidf(&document_counts, &document_frequency),
+        DiscoveryMethod::Rake => (
+            content.iter().zip(&document_counts).map(|(c, counts)| rake(c, counts, max_ngram)).collect(),
+            rake(&content.concat(), &corpus_counts, max_ngram),
+        ),
+        DiscoveryMethod::TextRank => (
+            content.iter().zip(&document_counts).map(|(c, counts)| textrank(c, counts)).collect(),
+            textrank(&content.concat(), &corpus_counts),
+        ),
+    };
+
+    let documents = texts
+        .iter()
+        .zip(document_scores)
+        .map(|((path, _), scores)| DocumentTerms {
+            path: path.clone(),
+            terms: top_terms(scores, &document_frequency, options.top),
+        })
+        .collect();
+
+    DiscoveryReport {
+        method: options.method,
+        language: options.language,
+        corpus: top_terms(corpus_scores, &document_frequency, options.top),
+        documents,
+    }
+}
+
+/// Propose keywords for the PDF files in the given paths; stops with an error once `cancel` is set
+pub fn discover(input_paths: &[String], options: &DiscoveryOptions, cancel: &AtomicBool) -> Result<DiscoveryReport, StatsError> {
+    let texts = extract_texts(input_paths, cancel)?;
+
+    Ok(discover_texts(&texts, options))
+}
+
+/// Run keyword discovery, writing the report to a file or stdout
+pub fn run(input_paths: &[String], output_file: Option<&str>, options: &DiscoveryOptions) -> Result<(), Box<dyn Error>> {
+    let report = discover(input_paths, options, &AtomicBool::new(false))?;
+
+    match output_file {
+        Some(output_file) => {
+            fs::write(output_file, report.to_text())?;
+            println!("Successfully generated keyword discovery report in '{}'", output_file);
+        },
+        None => print!("{}", report.to_text()),
+    }
+
+    Ok(())
+}
+
+/// Split text into segments of content-word runs; stopwords, numbers and
+/// punctuation end a run
+fn content_words(text: &str, stopwords: &Stopwords) -> ContentWords {
+    crate::text::segments(text)
+        .into_iter()
+        .map(|segment| {
+            let mut runs = Vec::new();
+            let mut run = Vec::new();
+            for token in segment {
+                if crate::text::is_word(&token) && !stopwords.contains(&token) {
+                    run.push(token);
+                } else if !run.is_empty() {
+                    runs.push(std::mem::take(&mut run));
+                }
+            }
+            if !run.is_empty() {
+                runs.push(run);
+            }
+            runs
+        })
+        .filter(|runs| !runs.is_empty())
+        .collect()
+}
+
+/// Count every n-gram of up to `max_ngram` words within the runs
+fn ngram_counts(segments: &ContentWords, max_ngram: usize) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for run in segments.iter().flatten() {
+        for n in 1..=max_ngram {
+            for words in run.windows(n) {
+                *counts.entry(words.join(" ")).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Whether a term may be proposed given how often it occurs
+fn frequent_enough(term: &str, count: usize) -> bool {
+    !term.contains(' ') || count >= MIN_PHRASE_COUNT
+}
+
+/// TF-IDF per document and summed over the corpus.
+///
+/// Term frequency is per 1,000 content words and phrases count once per word they
+/// cover, so a phrase scores at least as high as its rarest word would alone.
+fn tfidf(
+    document_counts: &[HashMap<String, usize>],
+    document_frequency: &HashMap<String, usize>,
+) -> (Vec<HashMap<String, f64>>, HashMap<String, f64>) {
+    let doc_count = document_counts.len() as f64;
+    let mut corpus = HashMap::new();
+
+    let documents = document_counts
+        .iter()
+        .map(|counts| {
+            let words: usize = counts
+                .iter()
+                .filter(|(term, _)| !term.contains(' '))
+                .map(|(_, &count)| count)
+                .sum();
+
+            let mut scores = HashMap::new();
+            for (term, &count) in counts.iter().filter(|(term, count)| frequent_enough(term, **count)) {
+                let length = term.split(' ').count();
+                let df = document_frequency.get(term).copied().unwrap_or(1) as f64;
+                let idf = ((1.0 + doc_count) / (1.0 + df)).ln() + 1.0;
+                let score = (count * length) as f64 * 1000.0 / words.max(1) as f64 * idf;
+
+                scores.insert(term.clone(), score);
+                *corpus.entry(term.clone()).or_insert(0.0) += score;
+            }
+            scores
+        })
+        .collect();
+
+    (documents, corpus)
+}
+
+/// RAKE scores: word `degree / frequency` is measured over runs of content words
+/// (split into `max_ngram`-word chunks), and each candidate n-gram scores the sum
+/// over its words
+fn rake(segments: &ContentWords, counts: &HashMap<String, usize>, max_ngram: usize) -> HashMap<String, f64> {
+    let mut frequency: HashMap<&str, f64> = HashMap::new();
+    let mut degree: HashMap<&str, f64> = HashMap::new();
+    for phrase in segments.iter().flatten().flat_map(|run| run.chunks(max_ngram)) {
+        for word in phrase {
+            *frequency.entry(word.as_str()).or_insert(0.0) += 1.0;
+            *degree.entry(word.as_str()).or_insert(0.0) += phrase.len() as f64;
+        }
+    }
+
+    counts
+        .iter()
+        .filter(|(term, count)| frequent_enough(term, **count))
+        .map(|(term, _)| {
+            let score = term.split(' ').map(|word| degree[word] / frequency[word]).sum();
+            (term.clone(), score)
+        })
+        .collect()
+}
+
+/// TextRank scores: content words adjacent within a segment (stopwords skipped)
+/// are linked, ranked with PageRank, and phrases score the sum of their word ranks
+fn textrank(segments: &ContentWords, counts: &HashMap<String, usize>) -> HashMap<String, f64> {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+
+    for segment in segments {
+        let mut previous: Option<usize> = None;
+        for word in segment.iter().flatten() {
+            let vertex = *index.entry(word.as_str()).or_insert_with(|| {
+                edges.push(Vec::new());
+                edges.len() - 1
+            });
+            if let Some(previous) = previous {
+                if previous != vertex && !edges[vertex].contains(&previous) {
+                    edges[vertex].push(previous);
+                    edges[previous].push(vertex);
+                }
+            }
+            previous = Some(vertex);
+        }
+    }
+
+    let mut ranks = vec![1.0; edges.len()];
+    for _ in 0..TEXTRANK_ITERATIONS {
+        let next: Vec<f64> = edges
+            .iter()
+            .map(|neighbours| {
+                let incoming: f64 = neighbours.iter().map(|&j| ranks[j] / edges[j].len() as f64).sum();
+                (1.0 - DAMPING) + DAMPING * incoming
+            })
+            .collect();
+        let change = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        ranks = next;
+        if change < 1e-6 {
+            break;
+        }
+    }
+
+    counts
+        .iter()
+        .filter(|(term, count)| frequent_enough(term, **count))
+        .map(|(term, _)| {
+            let score = term.split(' ').map(|word| index.get(word).map(|&i| ranks[i]).unwrap_or(0.0)).sum();
+            (term.clone(), score)
+        })
+        .collect()
+}
+
+/// The highest scoring terms, skipping words of a phrase that is already listed
+fn top_terms(scores: HashMap<String, f64>, document_frequency: &HashMap<String, usize>, top: usize) -> Vec<Term> {
+    let mut scored: Vec<(String, f64)> = scores
+        .into_iter()
+        .filter(|(_, score)| score.is_finite() && *score > 0.0)
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(&b.0)));
+
+    let mut terms: Vec<Term> = Vec::new();
+    for (text, score) in scored {
+        if terms.len() >= top {
+            break;
+        }
+        let padded = format!(" {} ", text);
+        if terms.iter().any(|term| format!(" {} ", term.text).contains(&padded)) {
+            continue;
+        }
+        terms.push(Term {
+            document_frequency: document_frequency.get(&text).copied().unwrap_or(0),
+            text,
+            score,
+        });
+    }
+    terms
+}
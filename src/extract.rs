@@ -1,7 +1,7 @@
 use indicatif::ProgressBar;
 use rayon::prelude::*;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::io;
 use walkdir::WalkDir;
 use std::error::Error;
@@ -49,15 +49,31 @@ pub fn run(output_file: &str, input_paths: &[String]) -> Result<(), Box<dyn Erro
     pb.set_message("Processing PDFs");
 
     // Process PDFs in parallel
-    let extracted_texts = process_pdfs(&pdf_paths, &pb);
+    let results = process_pdfs(&pdf_paths, &pb);
     
     // Finish progress bar
     pb.finish_with_message("Done");
 
     // Write to output file
+    let extracted_texts: Vec<&str> = results.iter().filter_map(|result| result.as_deref().ok()).collect();
     fs::write(output_file, extracted_texts.join("\n"))?;
     
-    println!("Successfully extracted text from {} PDFs to '{}'", pdf_paths.len(), output_file);
+    println!(
+        "Successfully extracted text from {} of {} PDFs to '{}'",
+        extracted_texts.len(), pdf_paths.len(), output_file
+    );
+    
+    // Record every failure, so one bad file in a large batch is easy to find
+    let failures: Vec<(&PathBuf, &ExtractError)> = pdf_paths.iter()
+        .zip(&results)
+        .filter_map(|(path, result)| result.as_ref().err().map(|e| (path, e)))
+        .collect();
+    if !failures.is_empty() {
+        eprintln!("{} PDFs could not be extracted:", failures.len());
+        for (path, e) in failures {
+            eprintln!("  {}: {}", path.display(), e);
+        }
+    }
     Ok(())
 }
 
@@ -87,38 +103,30 @@ fn collect_pdf_paths(input_paths: &[String]) -> Result<Vec<PathBuf>, ExtractErro
     Ok(pdf_paths)
 }
 
-/// Process PDFs in parallel and extract text
-fn process_pdfs(pdf_paths: &[PathBuf], pb: &ProgressBar) -> Vec<String> {
+/// Process PDFs in parallel and extract text, keeping each file's error
+fn process_pdfs(pdf_paths: &[PathBuf], pb: &ProgressBar) -> Vec<Result<String, ExtractError>> {
     pdf_paths
         .par_iter()
         .map(|path| {
-            let filename = path.file_name().unwrap().to_str().unwrap();
-            match extract_text_from_pdf(path) {
-                Ok(text) => format!(
-                    "[Start of document: {}]\n{}\n[End of document: {}]\n",
-                    filename, text, filename
-                ),
-                Err(e) => {
-                    eprintln!("Error processing {}: {}", filename, e);
-                    String::new()
-                }
-            }
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            extract_text_from_pdf(path).map(|text| format!(
+                "[Start of document: {}]\n{}\n[End of document: {}]\n",
+                filename, text, filename
+            ))
         })
         .inspect(|_| pb.inc(1))
         .collect()
 }
 
 /// Extract text from a single PDF file
-fn extract_text_from_pdf(path: &PathBuf) -> Result<String, ExtractError> {
+fn extract_text_from_pdf(path: &Path) -> Result<String, ExtractError> {
     let filename = path.file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown");
         
-    let bytes = fs::read(path)
-        .map_err(|e| ExtractError::IoError(e))?;
-        
-    let text = pdf_extract::extract_text_from_mem(&bytes)
+    // Isolated in a worker process when configured, so a hostile file can't stall the batch
+    let record = crate::cache::extract_record(path)
         .map_err(|e| ExtractError::PdfError(format!("Error extracting text from {}: {}", filename, e)))?;
         
-    Ok(text)
+    Ok(record.text())
 } 
\ No newline at end of file
@@ -1,45 +1,110 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 
-use egui::{Context, Ui, RichText, Color32, TextEdit, Vec2};
+use egui::{Align2, Context, FontId, Pos2, Rect, Sense, Stroke, Ui, RichText, Color32, TextEdit, Vec2};
+use egui::epaint::TextShape;
 
+use crate::discover::{DiscoveryMethod, DiscoveryOptions};
+use crate::report::{heat_rgb, render, ReportFormat};
+use crate::stats::{AnalysisOptions, AnalysisReport, CooccurrenceWindow, CorrelationMeasure, Document, RankingMethod};
+use crate::stopwords::Language;
+use crate::text::MatchOptions;
+use crate::topics::{ClusteringMethod, TopicOptions, TopicReport};
+use crate::trends::{GroupBy, Period, TrendOptions, TrendReport};
+
+use super::jobs::{JobHandle, JobKind, JobManager};
+use super::keyword_graph::KeywordGraph;
 use super::pdf_viewer::PdfViewer;
+use super::tabs::DocumentTabs;
+use super::trend_chart::{show_trend_chart, ChartStyle};
+use super::utils::truncate_string;
+
+/// What the analysis looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnalysisMode {
+    /// Correlations between the given keywords
+    Keywords,
+    /// Unsupervised grouping of the documents into topics
+    Topics,
+    /// Keyword frequencies over folders or document dates
+    Trends,
+}
+
+/// Result of an analysis job: the report and the files that could not be read, with the reason
+type AnalysisResult = (AnalysisReport, Vec<(PathBuf, String)>);
 
 /// Analysis panel component
 pub struct AnalysisPanel {
+    mode: AnalysisMode,
     keywords: String,
     input_paths: Vec<PathBuf>,
-    correlation_threshold: f32,
-    results: Option<AnalysisResult>,
-    is_analyzing: bool,
+    correlation_threshold: f64,
+    measure: CorrelationMeasure,
+    ranking: RankingMethod,
+    window: CooccurrenceWindow,
+    case_sensitive: bool,
+    stemming: bool,
+    language: Language,
+    discovery_method: DiscoveryMethod,
+    discovery_job: Option<JobHandle<Vec<String>>>,
+    results: Option<AnalysisReport>,
+    analysis_job: Option<JobHandle<AnalysisResult>>,
+    /// Files of the last analysis that could not be read, with the reason
+    failed_files: Vec<(PathBuf, String)>,
+    /// Number of files found when the last analysis was cancelled before processing them all
+    cancelled_of: Option<usize>,
+    /// Keyword pair picked in the heatmap; limits the ranked documents to where both co-occur
+    heatmap_filter: Option<(usize, usize)>,
+    keyword_graph: KeywordGraph,
+    clustering: ClusteringMethod,
+    topic_count: usize,
+    topics: Option<TopicReport>,
+    topics_job: Option<JobHandle<TopicReport>>,
+    group_by: GroupBy,
+    period: Period,
+    chart_style: ChartStyle,
+    /// Chart occurrences per 1,000 words instead of counts
+    relative_frequency: bool,
+    trends: Option<TrendReport>,
+    trends_job: Option<JobHandle<TrendReport>>,
     error_message: Option<String>,
-}
-
-/// Analysis result
-struct AnalysisResult {
-    keywords: Vec<String>,
-    correlation_matrix: Vec<Vec<f32>>,
-    ranked_docs: Vec<RankedDocument>,
-    total_documents: usize,
-}
-
-/// Document with rank
-#[derive(Clone)]
-struct RankedDocument {
-    name: String,
-    path: PathBuf,
-    score: f32,
+    jobs: JobManager,
 }
 
 impl AnalysisPanel {
-    pub fn new() -> Self {
+    pub fn new(jobs: JobManager) -> Self {
+        let defaults = &crate::config::get().analysis;
         Self {
+            mode: AnalysisMode::Keywords,
             keywords: String::new(),
             input_paths: Vec::new(),
-            correlation_threshold: 0.1,
+            correlation_threshold: defaults.threshold,
+            measure: defaults.measure,
+            ranking: defaults.ranking,
+            window: defaults.window,
+            case_sensitive: false,
+            stemming: false,
+            language: defaults.language,
+            discovery_method: DiscoveryMethod::default(),
+            discovery_job: None,
             results: None,
-            is_analyzing: false,
+            analysis_job: None,
+            failed_files: Vec::new(),
+            cancelled_of: None,
+            heatmap_filter: None,
+            keyword_graph: KeywordGraph::new(),
+            clustering: ClusteringMethod::default(),
+            topic_count: TopicOptions::default().clusters,
+            topics: None,
+            topics_job: None,
+            group_by: GroupBy::default(),
+            period: Period::default(),
+            chart_style: ChartStyle::Line,
+            relative_frequency: true,
+            trends: None,
+            trends_job: None,
             error_message: None,
+            jobs,
         }
     }
     
@@ -47,18 +112,103 @@ impl AnalysisPanel {
     pub fn show_options(&mut self, ui: &mut Ui, pdf_viewer: &PdfViewer) {
         ui.heading("Analysis Options");
         
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.mode, AnalysisMode::Keywords, "Keywords");
+            ui.selectable_value(&mut self.mode, AnalysisMode::Topics, "Topics")
+                .on_hover_text("Group the documents by subject without keywords");
+            ui.selectable_value(&mut self.mode, AnalysisMode::Trends, "Trends")
+                .on_hover_text("Chart keyword frequencies over folders or dates");
+        });
+        
+        if self.mode == AnalysisMode::Topics {
+            self.show_topic_options(ui, pdf_viewer);
+            return;
+        }
+        
         // Keywords input
         ui.label("Keywords (comma separated):");
         let text_edit = TextEdit::multiline(&mut self.keywords)
-            .hint_text("machine learning, neural networks, ...")
+            .hint_text("machine learning, car|automobile, ...")
             .desired_width(ui.available_width())
             .desired_rows(3);
         
-        ui.add(text_edit);
+        ui.add(text_edit)
+            .on_hover_text("Separate synonyms counted as one keyword with |");
+        
+        // Keyword matching
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.case_sensitive, "Match case");
+            ui.checkbox(&mut self.stemming, "Stem words")
+                .on_hover_text("Match inflected forms, e.g. \"network\" and \"networks\"");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Language:");
+            language_selector(ui, &mut self.language, "sidebar_language");
+        })
+        .response
+        .on_hover_text("Used for stemming and for discovery stopwords");
+        
+        // Keyword discovery
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("sidebar_discovery_method")
+                .selected_text(self.discovery_method.name())
+                .show_ui(ui, |ui| {
+                    for method in DiscoveryMethod::ALL {
+                        ui.selectable_value(&mut self.discovery_method, method, method.name());
+                    }
+                });
+            
+            let button_text = if self.discovery_job.is_some() { "Discovering..." } else { "Discover Keywords" };
+            let enabled = self.discovery_job.is_none() && !self.input_paths.is_empty();
+            if ui.add_enabled(enabled, egui::Button::new(button_text))
+                .on_hover_text("Propose keywords from the selected sources")
+                .clicked()
+            {
+                self.discover_keywords();
+            }
+        });
+        
+        ui.add_space(10.0);
+        
+        self.show_sources(ui, pdf_viewer);
         
         ui.add_space(10.0);
         
-        // Input paths
+        if self.mode == AnalysisMode::Trends {
+            self.show_trend_options(ui);
+            return;
+        }
+        
+        // Correlation measure and threshold
+        ui.label("Correlation Measure:");
+        measure_selector(ui, &mut self.measure, "sidebar_measure");
+        
+        ui.label("Correlation Threshold:");
+        ui.add(egui::Slider::new(&mut self.correlation_threshold, threshold_range(self.measure)).text("threshold"))
+            .on_hover_text(self.measure.scale());
+        
+        ui.label("Ranking:");
+        ranking_selector(ui, &mut self.ranking, "sidebar_ranking");
+        
+        ui.label("Co-occurrence Window:");
+        window_selector(ui, &mut self.window, "sidebar_window");
+        
+        ui.add_space(15.0);
+        
+        // Analyze button
+        let button_text = if self.analysis_job.is_some() {
+            "Analyzing..."
+        } else {
+            "Analyze"
+        };
+        
+        if ui.button(button_text).clicked() && self.analysis_job.is_none() && !self.keywords.is_empty() && !self.input_paths.is_empty() {
+            self.perform_analysis();
+        }
+    }
+    
+    /// Show the input source selection in the sidebar
+    fn show_sources(&mut self, ui: &mut Ui, pdf_viewer: &PdfViewer) {
         ui.label("Input Sources:");
         
         // Add current document
@@ -109,41 +259,138 @@ impl AnalysisPanel {
                 }
             });
         }
+    }
+    
+    /// Show topic clustering options in the sidebar
+    fn show_topic_options(&mut self, ui: &mut Ui, pdf_viewer: &PdfViewer) {
+        self.show_sources(ui, pdf_viewer);
         
         ui.add_space(10.0);
         
-        // Correlation threshold slider
-        ui.label("Correlation Threshold:");
-        ui.add(egui::Slider::new(&mut self.correlation_threshold, 0.0..=1.0).text("threshold"));
+        ui.horizontal(|ui| {
+            ui.label("Language:");
+            language_selector(ui, &mut self.language, "sidebar_topic_language");
+        })
+        .response
+        .on_hover_text("Stopwords left out of the document vectors");
+        
+        ui.label("Clustering:");
+        egui::ComboBox::from_id_source("sidebar_clustering")
+            .selected_text(self.clustering.name())
+            .show_ui(ui, |ui| {
+                for method in ClusteringMethod::ALL {
+                    ui.selectable_value(&mut self.clustering, method, method.name());
+                }
+            });
+        
+        ui.horizontal(|ui| {
+            ui.label("Topics:");
+            ui.add(egui::DragValue::new(&mut self.topic_count).clamp_range(2..=50));
+        });
         
         ui.add_space(15.0);
         
-        // Analyze button
-        let button_text = if self.is_analyzing {
-            "Analyzing..."
-        } else {
-            "Analyze"
+        let button_text = if self.topics_job.is_some() { "Clustering..." } else { "Find Topics" };
+        if ui.add_enabled(self.topics_job.is_none() && !self.input_paths.is_empty(), egui::Button::new(button_text)).clicked() {
+            self.find_topics();
+        }
+    }
+    
+    /// Show keyword trend options in the sidebar
+    fn show_trend_options(&mut self, ui: &mut Ui) {
+        ui.label("Group Documents By:");
+        egui::ComboBox::from_id_source("sidebar_group_by")
+            .selected_text(self.group_by.name())
+            .show_ui(ui, |ui| {
+                for group_by in GroupBy::ALL {
+                    ui.selectable_value(&mut self.group_by, group_by, group_by.name());
+                }
+            });
+        
+        if self.group_by.is_date() {
+            ui.label("Period:");
+            egui::ComboBox::from_id_source("sidebar_period")
+                .selected_text(self.period.name())
+                .show_ui(ui, |ui| {
+                    for period in Period::ALL {
+                        ui.selectable_value(&mut self.period, period, period.name());
+                    }
+                });
+        }
+        
+        ui.add_space(15.0);
+        
+        let button_text = if self.trends_job.is_some() { "Counting..." } else { "Chart Trends" };
+        let enabled = self.trends_job.is_none() && !self.keywords.trim().is_empty() && !self.input_paths.is_empty();
+        if ui.add_enabled(enabled, egui::Button::new(button_text)).clicked() {
+            self.chart_trends();
+        }
+    }
+    
+    /// Keywords from the comma separated keyword field
+    fn parsed_keywords(&self) -> Vec<String> {
+        self.keywords
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+    
+    /// How keywords are matched, from the sidebar settings
+    fn match_options(&self) -> MatchOptions {
+        MatchOptions {
+            case_sensitive: self.case_sensitive,
+            stemming: self.stemming.then_some(self.language),
+        }
+    }
+    
+    /// Count keywords per folder or date period in a background thread
+    fn chart_trends(&mut self) {
+        self.error_message = None;
+        
+        let keywords = self.parsed_keywords();
+        let input_paths: Vec<String> = self.input_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let options = TrendOptions {
+            group_by: self.group_by,
+            period: self.period,
+            matching: self.match_options(),
         };
         
-        if ui.button(button_text).clicked() && !self.is_analyzing && !self.keywords.is_empty() && !self.input_paths.is_empty() {
-            self.perform_analysis();
+        self.trends_job = Some(self.jobs.spawn(JobKind::Trends, "Count keyword trends", move |job| {
+            crate::trends::trends(&input_paths, &keywords, &options, job.cancel_flag())
+                .map_err(|e| format!("Error counting keywords: {}", e))
+        }));
+    }
+    
+    /// Pick up the result of a finished trend count
+    fn poll_trends(&mut self) {
+        let Some(job) = &self.trends_job else {
+            return;
+        };
+        let cancelled = job.is_cancelled();
+        let Some(result) = job.poll() else {
+            return;
+        };
+        
+        match result {
+            Ok(report) => self.trends = Some(report),
+            Err(e) if !cancelled => self.error_message = Some(e),
+            Err(_) => {},
         }
+        self.trends_job = None;
     }
     
     /// Perform analysis
     fn perform_analysis(&mut self) {
-        self.is_analyzing = true;
         self.error_message = None;
         
         // Parse keywords
-        let keywords: Vec<String> = self.keywords
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+        let keywords = self.parsed_keywords();
             
         if keywords.is_empty() {
-            self.is_analyzing = false;
             self.error_message = Some("No keywords specified".to_string());
             return;
         }
@@ -155,154 +402,192 @@ impl AnalysisPanel {
             .collect();
             
         if input_paths.is_empty() {
-            self.is_analyzing = false;
             self.error_message = Some("No input sources selected".to_string());
             return;
         }
         
-        // Create clones for the thread
-        let keywords_clone = keywords.clone();
-        let input_paths_clone = input_paths.clone();
-        let threshold = self.correlation_threshold as f64;
-        
-        // Create a temporary file to store analysis results
-        let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S").to_string();
-        let output_file = format!("pdf_analysis_{}.txt", timestamp);
-        
-        // Create shared results for thread communication
-        let results = Arc::new(Mutex::new(None));
-        let results_clone = results.clone();
-        let error_message = Arc::new(Mutex::new(None));
-        let error_message_clone = error_message.clone();
-        
-        // Run analysis in a background thread
-        std::thread::spawn(move || {
-            // Use the stats module to perform analysis
-            match crate::stats::run(&input_paths_clone, &keywords_clone, &output_file, threshold) {
-                Ok(_) => {
-                    println!("Analysis completed and saved to {}", output_file);
-                    
-                    // Create analysis result data
-                    let mut correlation_matrix = vec![vec![0.0; keywords_clone.len()]; keywords_clone.len()];
-                    let mut ranked_docs = Vec::new();
-                    
-                    // Try to read the analysis file
-                    match std::fs::read_to_string(&output_file) {
-                        Ok(content) => {
-                            // Parse correlation matrix
-                            // This is a simplified parsing approach - in a real app 
-                            // we would parse the file's structure more carefully
-                            let mut in_matrix = false;
-                            let mut in_ranked = false;
-                            
-                            for line in content.lines() {
-                                // Skip lines with Unicode warnings
-                                if line.contains("Unicode mismatch") || line.contains("unknown glyph") {
-                                    continue;
-                                }
-                                
-                                // Look for correlation matrix section
-                                if line.contains("Correlation Matrix") {
-                                    in_matrix = true;
-                                    in_ranked = false;
-                                    continue;
-                                }
-                                
-                                // Look for ranked documents section
-                                if line.contains("Ranked Documents") {
-                                    in_matrix = false;
-                                    in_ranked = true;
-                                    continue;
-                                }
-                                
-                                // Parse correlation matrix lines
-                                if in_matrix && line.starts_with(char::is_numeric) {
-                                    let parts: Vec<&str> = line.split_whitespace().collect();
-                                    if parts.len() >= 3 {
-                                        if let (Ok(i), Ok(j), Ok(val)) = (
-                                            parts[0].parse::<usize>(),
-                                            parts[1].parse::<usize>(),
-                                            parts[2].parse::<f32>()
-                                        ) {
-                                            if i < correlation_matrix.len() && j < correlation_matrix[i].len() {
-                                                correlation_matrix[i][j] = val;
-                                            }
-                                        }
-                                    }
-                                }
-                                
-                                // Parse ranked document lines
-                                if in_ranked && line.contains("). ") {
-                                    let parts: Vec<&str> = line.split("). ").collect();
-                                    if parts.len() >= 2 {
-                                        let name_parts: Vec<&str> = parts[1].split(" (score: ").collect();
-                                        if name_parts.len() >= 2 {
-                                            let name = name_parts[0].to_string();
-                                            if let Ok(score) = name_parts[1].trim_end_matches(')').parse::<f32>() {
-                                                // Create a path from the name (simplified approach)
-                                                let path = PathBuf::from(&name);
-                                                
-                                                ranked_docs.push(RankedDocument {
-                                                    name,
-                                                    path,
-                                                    score,
-                                                });
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            
-                            // Store the results
-                            let mut results = results_clone.lock().unwrap();
-                            *results = Some(AnalysisResult {
-                                keywords: keywords_clone,
-                                correlation_matrix,
-                                ranked_docs,
-                                total_documents: input_paths_clone.len(),
-                            });
-                        },
-                        Err(e) => {
-                            let mut error = error_message_clone.lock().unwrap();
-                            *error = Some(format!("Error reading analysis results: {}", e));
-                        }
+        let options = AnalysisOptions {
+            measure: self.measure,
+            threshold: self.correlation_threshold,
+            ranking: self.ranking,
+            window: self.window,
+            matching: self.match_options(),
+        };
+        self.failed_files.clear();
+        self.cancelled_of = None;
+        
+        // Run analysis in the background, reporting each file
+        self.analysis_job = Some(self.jobs.spawn(JobKind::Analysis, "Analyze keywords", move |job| {
+            let failed = Mutex::new(Vec::new());
+            let report = crate::stats::analyze_with_progress(&input_paths, &keywords, &options, &|progress| {
+                if let Some(error) = progress.error {
+                    failed.lock().unwrap().push((progress.path.clone(), error));
+                }
+                let name = progress.path.file_name().unwrap_or_default().to_string_lossy();
+                job.progress(progress.done, progress.total, name);
+            }, job.cancel_flag())
+                .map_err(|e| format!("Error performing analysis: {}", e))?;
+            
+            Ok((report, failed.into_inner().unwrap()))
+        }));
+    }
+    
+    /// Propose keywords from the selected sources in a background thread
+    fn discover_keywords(&mut self) {
+        self.error_message = None;
+        
+        let input_paths: Vec<String> = self.input_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let options = DiscoveryOptions {
+            method: self.discovery_method,
+            language: self.language,
+            ..Default::default()
+        };
+        
+        self.discovery_job = Some(self.jobs.spawn(JobKind::Discovery, "Discover keywords", move |job| {
+            crate::discover::discover(&input_paths, &options, job.cancel_flag())
+                .map(|report| report.keywords())
+                .map_err(|e| format!("Error discovering keywords: {}", e))
+        }));
+    }
+    
+    /// Add the keywords of a finished discovery to the keyword list
+    fn poll_discovery(&mut self) {
+        let Some(job) = &self.discovery_job else {
+            return;
+        };
+        let cancelled = job.is_cancelled();
+        let Some(result) = job.poll() else {
+            return;
+        };
+        
+        match result {
+            Ok(discovered) => {
+                let mut keywords: Vec<String> = self.keywords
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                for keyword in discovered {
+                    if !keywords.contains(&keyword) {
+                        keywords.push(keyword);
                     }
-                },
-                Err(e) => {
-                    let mut error = error_message_clone.lock().unwrap();
-                    *error = Some(format!("Error performing analysis: {}", e));
                 }
-            }
-        });
+                self.keywords = keywords.join(", ");
+            },
+            Err(e) if !cancelled => self.error_message = Some(e),
+            Err(_) => {},
+        }
+        self.discovery_job = None;
+    }
+    
+    /// Pick up the result of a finished analysis
+    fn poll_analysis(&mut self) {
+        let Some(job) = &self.analysis_job else {
+            return;
+        };
+        let progress = job.progress();
+        let cancelled = job.is_cancelled();
+        let Some(result) = job.poll() else {
+            return;
+        };
         
-        // Wait a bit for results (in a real app, we'd handle this asynchronously)
-        std::thread::sleep(std::time::Duration::from_millis(500));
-        
-        // Get any results so far
-        let locked_results = results.lock().unwrap();
-        if let Some(analysis_result) = &*locked_results {
-            // Clone the result for our instance
-            self.results = Some(AnalysisResult {
-                keywords: analysis_result.keywords.clone(),
-                correlation_matrix: analysis_result.correlation_matrix.clone(),
-                ranked_docs: analysis_result.ranked_docs.clone(),
-                total_documents: analysis_result.total_documents,
-            });
+        match result {
+            Ok((report, failed)) => {
+                if cancelled && report.total_documents() < progress.total {
+                    self.cancelled_of = Some(progress.total);
+                }
+                self.failed_files = failed;
+                self.results = Some(report);
+                self.heatmap_filter = None;
+            },
+            Err(e) => self.error_message = Some(e),
         }
+        self.analysis_job = None;
+    }
+    
+    /// Show how far the running analysis is, with a button to cancel it
+    fn show_progress(&self, ui: &mut Ui) {
+        let Some(job) = &self.analysis_job else {
+            return;
+        };
+        let progress = job.progress();
         
-        // Check for errors
-        let locked_error = error_message.lock().unwrap();
-        if let Some(error) = &*locked_error {
-            self.error_message = Some(error.clone());
+        match progress.fraction() {
+            Some(fraction) => {
+                ui.add(egui::ProgressBar::new(fraction)
+                    .text(format!("{} of {} files", progress.done, progress.total)));
+                ui.label(RichText::new(truncate_string(&progress.message, 40)).small().weak());
+            },
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Collecting PDF files...");
+                });
+            },
         }
         
-        self.is_analyzing = false;
+        if job.is_cancelled() {
+            ui.label("Cancelling...");
+        } else if ui.button("Cancel").clicked() {
+            job.cancel();
+        }
+    }
+    
+    /// Group the selected sources into topics in a background thread
+    fn find_topics(&mut self) {
+        self.error_message = None;
+        
+        let input_paths: Vec<String> = self.input_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let options = TopicOptions {
+            method: self.clustering,
+            clusters: self.topic_count,
+            language: self.language,
+            ..Default::default()
+        };
+        
+        self.topics_job = Some(self.jobs.spawn(JobKind::Clustering, "Find topics", move |job| {
+            crate::topics::cluster_documents(&input_paths, &options, job.cancel_flag())
+                .map_err(|e| format!("Error clustering documents: {}", e))
+        }));
+    }
+    
+    /// Pick up the result of a finished topic clustering
+    fn poll_topics(&mut self) {
+        let Some(job) = &self.topics_job else {
+            return;
+        };
+        let cancelled = job.is_cancelled();
+        let Some(result) = job.poll() else {
+            return;
+        };
+        
+        match result {
+            Ok(report) => self.topics = Some(report),
+            Err(e) if !cancelled => self.error_message = Some(e),
+            Err(_) => {},
+        }
+        self.topics_job = None;
     }
     
     /// Show the analysis panel in the main content area
-    pub fn show(&mut self, ui: &mut Ui, _ctx: &Context, pdf_viewer: &mut PdfViewer) {
+    pub fn show(&mut self, ui: &mut Ui, _ctx: &Context, tabs: &mut DocumentTabs) {
+        self.poll_analysis();
+        self.poll_discovery();
+        self.poll_topics();
+        self.poll_trends();
+        
         ui.vertical(|ui| {
-            ui.heading("Keyword Analysis");
+            ui.heading(match self.mode {
+                AnalysisMode::Keywords => "Keyword Analysis",
+                AnalysisMode::Topics => "Topic Clustering",
+                AnalysisMode::Trends => "Keyword Trends",
+            });
             
             // Show error message if any
             let mut clear_error = false;
@@ -321,6 +606,18 @@ impl AnalysisPanel {
                 self.error_message = None;
             }
             
+            match self.mode {
+                AnalysisMode::Topics => {
+                    self.show_topics(ui, tabs);
+                    return;
+                },
+                AnalysisMode::Trends => {
+                    self.show_trends(ui);
+                    return;
+                },
+                AnalysisMode::Keywords => {},
+            }
+            
             // Analysis configuration
             ui.collapsing("Analysis Configuration", |ui| {
                 // Keywords
@@ -340,7 +637,7 @@ impl AnalysisPanel {
                         }
                     }
                     
-                    if let Some(current_pdf) = pdf_viewer.current_pdf() {
+                    if let Some(current_pdf) = tabs.active().current_pdf() {
                         if ui.button("Add Current Document").clicked() {
                             if !self.input_paths.contains(current_pdf) {
                                 self.input_paths.push(current_pdf.clone());
@@ -348,11 +645,18 @@ impl AnalysisPanel {
                         }
                     }
                     
-                    // Correlation threshold
+                    // Correlation measure and threshold
+                    ui.label("Measure:");
+                    measure_selector(ui, &mut self.measure, "config_measure");
+                    
                     ui.label("Threshold:");
-                    ui.add(egui::Slider::new(&mut self.correlation_threshold, 0.0..=1.0)
+                    ui.add(egui::Slider::new(&mut self.correlation_threshold, threshold_range(self.measure))
                         .text("threshold")
-                        .fixed_decimals(2));
+                        .fixed_decimals(2))
+                        .on_hover_text(self.measure.scale());
+                    
+                    ui.label("Window:");
+                    window_selector(ui, &mut self.window, "config_window");
                 });
                 
                 // Selected paths
@@ -379,91 +683,175 @@ impl AnalysisPanel {
                 }
                 
                 // Analyze button
-                if ui.button("Run Analysis").clicked() && !self.is_analyzing && !self.keywords.is_empty() && !self.input_paths.is_empty() {
+                if ui.button("Run Analysis").clicked() && self.analysis_job.is_none() && !self.keywords.is_empty() && !self.input_paths.is_empty() {
                     self.perform_analysis();
                 }
             });
             
             ui.separator();
             
+            if self.analysis_job.is_some() {
+                self.show_progress(ui);
+                ui.separator();
+            }
+            
             // Results section
-            if let Some(results) = &self.results {
+            if let Some(results) = &mut self.results {
                 ui.heading("Analysis Results");
                 
                 ui.label(format!("Analyzed {} documents with {} keywords", 
-                    results.total_documents, results.keywords.len()));
-                ui.label(format!("Correlation threshold: {:.2}", self.correlation_threshold));
+                    results.total_documents(), results.keywords.len()));
+                if let Some(total) = self.cancelled_of {
+                    ui.label(RichText::new(format!(
+                        "Cancelled: partial results from {} of {} files",
+                        results.total_documents(), total
+                    )).color(ui.visuals().warn_fg_color));
+                }
+                if !self.failed_files.is_empty() {
+                    ui.collapsing(format!("{} files could not be read", self.failed_files.len()), |ui| {
+                        for (path, error) in &self.failed_files {
+                            ui.label(path.file_name().unwrap_or_default().to_string_lossy())
+                                .on_hover_text(error);
+                        }
+                    });
+                }
+                ui.label(format!("Co-occurrence window: {}", results.window));
+                ui.label(format!("Correlation measure: {} ({})", results.measure, results.measure.scale()));
+                ui.label(format!("Correlation threshold: {:.2}", results.threshold));
+                
+                if ui.button("Export Report...")
+                    .on_hover_text("Save as HTML, Markdown, JSON, CSV or text, chosen by the file extension")
+                    .clicked()
+                {
+                    if let Err(e) = export_report(results) {
+                        self.error_message = Some(e);
+                    }
+                }
                 
                 ui.separator();
                 
-                // Correlation matrix
+                // Correlation heatmap
                 ui.collapsing("Keyword Correlation Matrix", |ui| {
                     if results.keywords.len() <= 1 {
                         ui.label("Need at least 2 keywords to show correlations");
                     } else {
-                        // Draw correlation matrix
-                        let _matrix_size = Vec2::new(
-                            (results.keywords.len() * 60) as f32, 
-                            (results.keywords.len() * 30) as f32
-                        );
-                        
-                        egui::ScrollArea::both().max_height(300.0).show(ui, |ui| {
-                            // Matrix header row
-                            ui.horizontal(|ui| {
-                                ui.add_space(60.0); // For the row headers
-                                
-                                for keyword in &results.keywords {
-                                    ui.label(RichText::new(keyword).strong());
-                                }
-                            });
-                            
-                            // Matrix data rows
-                            for (i, row_keyword) in results.keywords.iter().enumerate() {
-                                ui.horizontal(|ui| {
-                                    ui.label(RichText::new(row_keyword).strong());
-                                    
-                                    for (j, _) in results.keywords.iter().enumerate() {
-                                        let value = if i == j {
-                                            String::from("—")
-                                        } else if i < j {
-                                            format!("{:.2}", results.correlation_matrix[i][j])
-                                        } else {
-                                            format!("{:.2}", results.correlation_matrix[j][i])
-                                        };
-                                        
-                                        ui.label(value);
-                                    }
-                                });
-                            }
+                        egui::ScrollArea::both().id_source("correlation_heatmap").max_height(500.0).show(ui, |ui| {
+                            show_heatmap(ui, results, &mut self.heatmap_filter);
                         });
                     }
                 });
                 
                 ui.separator();
                 
+                // Keyword co-occurrence graph
+                ui.collapsing("Keyword Network", |ui| {
+                    self.keyword_graph.show(ui, results);
+                });
+                
+                ui.separator();
+                
                 // Ranked documents
                 ui.collapsing("Ranked Documents", |ui| {
-                    if results.ranked_docs.is_empty() {
+                    // Switching the ranking only re-scores the counted keywords
+                    let mut ranking = results.ranking;
+                    ui.horizontal(|ui| {
+                        ui.label("Rank by:");
+                        ranking_selector(ui, &mut ranking, "results_ranking");
+                    });
+                    if ranking != results.ranking {
+                        results.rerank(ranking);
+                        self.ranking = ranking;
+                    }
+                    
+                    // A clicked heatmap cell limits the list to documents where its keywords co-occur
+                    let documents: Vec<&Document> = match self.heatmap_filter {
+                        Some((i, j)) => {
+                            let mut clear = false;
+                            ui.horizontal(|ui| {
+                                ui.label(format!(
+                                    "Documents where \"{}\" and \"{}\" co-occur",
+                                    results.keywords[i], results.keywords[j]
+                                ));
+                                clear = ui.small_button("Clear filter").clicked();
+                            });
+                            if clear {
+                                self.heatmap_filter = None;
+                            }
+                            results.cooccurring_documents(i, j).collect()
+                        },
+                        None => results.ranked_documents().collect(),
+                    };
+                    
+                    if documents.is_empty() {
                         ui.label("No documents matched the analysis criteria");
                     } else {
-                        egui::ScrollArea::vertical().show(ui, |ui| {
-                            for (i, doc) in results.ranked_docs.iter().enumerate() {
+                        egui::ScrollArea::vertical().id_source("ranked_documents").show(ui, |ui| {
+                            for (i, doc) in documents.iter().enumerate() {
                                 ui.horizontal(|ui| {
                                     ui.label(format!("{}. ", i+1));
-                                    ui.label(RichText::new(&doc.name).strong());
+                                    ui.label(RichText::new(&doc.filename).strong());
                                     ui.label(format!("(score: {:.2})", doc.score));
                                     
                                     if ui.button("Open").clicked() {
-                                        pdf_viewer.load_pdf(&doc.path);
+                                        tabs.open_result(&doc.path);
                                     }
                                 });
                             }
                         });
                     }
                 });
-            } else if self.is_analyzing {
-                ui.label("Analyzing documents...");
-            } else {
+                
+                ui.separator();
+                
+                // Windows where the keywords occur together
+                if !results.contexts.is_empty() {
+                    ui.collapsing("Co-occurring Contexts", |ui| {
+                        egui::ScrollArea::vertical().id_source("cooccurrence_contexts").max_height(300.0).show(ui, |ui| {
+                            for (i, context) in results.contexts.iter().enumerate() {
+                                ui.group(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(format!("{}. ", i + 1));
+                                        ui.label(RichText::new(&context.filename).strong());
+                                        ui.label(format!("page {}", context.page + 1));
+                                        ui.label(format!("[{}]", context.keywords.join(", ")));
+                                        ui.label(format!("strength {}", context.strength));
+                                        
+                                        if ui.button("Open").clicked() {
+                                            let ctx = ui.ctx().clone();
+                                            tabs.open_result(&context.path)
+                                                .jump_to_page_when_loaded(context.page, &context.keywords[0], &ctx);
+                                        }
+                                    });
+                                    ui.label(RichText::new(&context.snippet).italics());
+                                });
+                            }
+                        });
+                    });
+                    
+                    ui.separator();
+                }
+                
+                // Keyword counts per document
+                ui.collapsing("Keyword Counts", |ui| {
+                    egui::ScrollArea::both().id_source("keyword_counts").max_height(300.0).show(ui, |ui| {
+                        egui::Grid::new("keyword_counts_grid").striped(true).show(ui, |ui| {
+                            ui.label(RichText::new("Document").strong());
+                            for keyword in &results.keywords {
+                                ui.label(RichText::new(keyword).strong());
+                            }
+                            ui.end_row();
+                            
+                            for doc in &results.documents {
+                                ui.label(&doc.filename).on_hover_text(doc.path.to_string_lossy());
+                                for keyword in &results.keywords {
+                                    ui.label(doc.count(keyword).to_string());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                });
+            } else if self.analysis_job.is_none() {
                 ui.vertical_centered(|ui| {
                     ui.add_space(50.0);
                     ui.label("No analysis results yet");
@@ -473,8 +861,301 @@ impl AnalysisPanel {
         });
     }
 
-    fn show_heatmap(&mut self, _ui: &mut Ui, _data: &[f32]) {
-        let _matrix_size = Vec2::new(100.0, 100.0);
-        // ... existing code (if any) ...
+    /// Show the keyword frequency chart
+    fn show_trends(&mut self, ui: &mut Ui) {
+        let Some(report) = &self.trends else {
+            if self.trends_job.is_some() {
+                ui.label("Counting keywords...");
+            } else {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(50.0);
+                    ui.label("No trends yet");
+                    ui.label("Enter keywords, add sources in the sidebar and click Chart Trends");
+                });
+            }
+            return;
+        };
+        
+        ui.horizontal(|ui| {
+            ui.label(format!("{} groups by {}", report.groups.len(), report.grouping()));
+            ui.separator();
+            ui.selectable_value(&mut self.chart_style, ChartStyle::Line, "Line");
+            ui.selectable_value(&mut self.chart_style, ChartStyle::Bar, "Bar");
+            ui.separator();
+            ui.selectable_value(&mut self.relative_frequency, true, "Per 1,000 words");
+            ui.selectable_value(&mut self.relative_frequency, false, "Counts");
+            ui.separator();
+            
+            if ui.button("Export CSV...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_file_name("keyword_trends.csv")
+                    .add_filter("CSV Files", &["csv"])
+                    .save_file()
+                {
+                    if let Err(e) = std::fs::write(&path, report.to_csv()) {
+                        self.error_message = Some(format!("Error writing {}: {}", path.display(), e));
+                    }
+                }
+            }
+        });
+        if !report.undated.is_empty() {
+            ui.label(RichText::new(format!(
+                "{} documents have no date and are not charted",
+                report.undated.len()
+            )).italics());
+        }
+        
+        ui.separator();
+        
+        show_trend_chart(ui, report, self.chart_style, self.relative_frequency);
+    }
+    
+    /// Show the topics found by clustering
+    fn show_topics(&self, ui: &mut Ui, tabs: &mut DocumentTabs) {
+        let Some(report) = &self.topics else {
+            if self.topics_job.is_some() {
+                ui.label("Clustering documents...");
+            } else {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(50.0);
+                    ui.label("No topics yet");
+                    ui.label("Add sources in the sidebar and click Find Topics");
+                });
+            }
+            return;
+        };
+        
+        ui.label(format!(
+            "{} documents in {} topics ({} clustering, {} terms)",
+            report.total_documents,
+            report.topics.len(),
+            report.method,
+            report.vocabulary_size
+        ));
+        if !report.without_text.is_empty() {
+            ui.label(RichText::new(format!(
+                "{} documents have no extractable text and were left out",
+                report.without_text.len()
+            )).italics());
+        }
+        
+        ui.separator();
+        
+        egui::ScrollArea::vertical().id_source("topics").show(ui, |ui| {
+            for (i, topic) in report.topics.iter().enumerate() {
+                let title = format!("Topic {}: {} ({} documents)", i + 1, topic.label(), topic.members.len());
+                egui::CollapsingHeader::new(title)
+                    .id_source(("topic", i))
+                    .default_open(i < 5)
+                    .show(ui, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            for (term, weight) in &topic.terms {
+                                ui.label(RichText::new(term).strong())
+                                    .on_hover_text(format!("mean TF-IDF weight {:.3}", weight));
+                            }
+                        });
+                        
+                        for member in &topic.members {
+                            ui.horizontal(|ui| {
+                                ui.label(member.path.file_name().unwrap_or_default().to_string_lossy())
+                                    .on_hover_text(member.path.to_string_lossy());
+                                ui.label(RichText::new(format!("similarity {:.2}", member.similarity)).weak());
+                                
+                                if ui.small_button("Open").clicked() {
+                                    tabs.open_result(&member.path);
+                                }
+                            });
+                        }
+                    });
+            }
+        });
     }
-} 
\ No newline at end of file
+}
+
+/// Draw the correlation matrix as a colour-scaled heatmap. Hovering a cell shows its
+/// value and the documents where both keywords co-occur; clicking it toggles the
+/// filter of the ranked document list.
+fn show_heatmap(ui: &mut Ui, results: &AnalysisReport, filter: &mut Option<(usize, usize)>) {
+    let n = results.keywords.len();
+    let font = FontId::proportional(12.0);
+    let labels: Vec<String> = results.keywords.iter().map(|keyword| truncate_string(keyword, 20)).collect();
+    let label_size = ui.fonts(|fonts| {
+        labels
+            .iter()
+            .map(|label| fonts.layout_no_wrap(label.clone(), font.clone(), Color32::WHITE).size().x)
+            .fold(0.0, f32::max)
+    }) + 8.0;
+    let cell = ((ui.available_width() - label_size) / n as f32).clamp(16.0, 48.0);
+    
+    let (response, painter) = ui.allocate_painter(Vec2::splat(label_size + cell * n as f32), Sense::click());
+    let origin = response.rect.min + Vec2::splat(label_size);
+    let cell_rect = |i: usize, j: usize| {
+        Rect::from_min_size(origin + Vec2::new(j as f32 * cell, i as f32 * cell), Vec2::splat(cell))
+    };
+    
+    // Values are coloured relative to the strongest correlation
+    let scale = results.correlations.iter().flatten().fold(0.0f64, |max, value| max.max(value.abs())).max(f64::EPSILON);
+    let visuals = ui.visuals();
+    let text_color = visuals.text_color();
+    
+    for (i, label) in labels.iter().enumerate() {
+        painter.text(
+            Pos2::new(origin.x - 4.0, origin.y + (i as f32 + 0.5) * cell),
+            Align2::RIGHT_CENTER,
+            label,
+            font.clone(),
+            text_color,
+        );
+        let galley = painter.layout_no_wrap(label.clone(), font.clone(), text_color);
+        let pos = Pos2::new(origin.x + (i as f32 + 0.5) * cell - galley.size().y / 2.0, origin.y - 4.0);
+        painter.add(TextShape::new(pos, galley, text_color).with_angle(-std::f32::consts::FRAC_PI_2));
+        
+        for j in 0..n {
+            let rect = cell_rect(i, j);
+            if i == j {
+                painter.rect_filled(rect.shrink(0.5), 0.0, visuals.faint_bg_color);
+                continue;
+            }
+            
+            let value = results.correlations[i][j];
+            let [r, g, b] = heat_rgb(value / scale, visuals.dark_mode);
+            painter.rect_filled(rect.shrink(0.5), 0.0, Color32::from_rgb(r, g, b));
+            if value > 0.0 && value >= results.threshold {
+                painter.rect_stroke(rect.shrink(1.5), 0.0, Stroke::new(1.0, text_color));
+            }
+            if *filter == Some((i.min(j), i.max(j))) {
+                painter.rect_stroke(rect.shrink(1.0), 0.0, Stroke::new(2.5, visuals.selection.stroke.color));
+            }
+            if cell >= 36.0 {
+                painter.text(rect.center(), Align2::CENTER_CENTER, format!("{:.2}", value), FontId::proportional(10.0), text_color);
+            }
+        }
+    }
+    
+    let hovered = response.hover_pos().and_then(|pointer| {
+        let offset = (pointer - origin) / cell;
+        let (i, j) = (offset.y.floor(), offset.x.floor());
+        (i >= 0.0 && j >= 0.0 && (i as usize) < n && (j as usize) < n && i != j).then_some((i as usize, j as usize))
+    });
+    let Some((i, j)) = hovered else {
+        return;
+    };
+    
+    if response.clicked() {
+        let pair = (i.min(j), i.max(j));
+        *filter = if *filter == Some(pair) { None } else { Some(pair) };
+    }
+    
+    let documents: Vec<&Document> = results.cooccurring_documents(i, j).collect();
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.label(RichText::new(format!("{} × {}", results.keywords[i], results.keywords[j])).strong());
+        ui.label(format!("{}: {:.3}", results.measure, results.correlations[i][j]));
+        if let Some(statistics) = &results.statistics {
+            ui.label(format!("statistic: {:.3}", statistics[i][j]));
+        }
+        if let Some(p_values) = &results.p_values {
+            ui.label(format!("p-value: {:.4}", p_values[i][j]));
+        }
+        
+        ui.separator();
+        ui.label(format!("Co-occur in {} documents", documents.len()));
+        for doc in documents.iter().take(10) {
+            ui.label(RichText::new(&doc.filename).weak());
+        }
+        if documents.len() > 10 {
+            ui.label(RichText::new(format!("and {} more", documents.len() - 10)).weak());
+        }
+        ui.label(RichText::new("Click to filter the ranked documents").italics());
+    });
+}
+
+/// Ask for a file and write the analysis report in the format of its extension
+fn export_report(report: &AnalysisReport) -> Result<(), String> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name("pdf_analysis_report.html")
+        .add_filter("HTML", &["html"])
+        .add_filter("Markdown", &["md"])
+        .add_filter("JSON", &["json"])
+        .add_filter("CSV", &["csv"])
+        .add_filter("Text", &["txt"])
+        .save_file()
+    else {
+        return Ok(());
+    };
+    
+    let format = ReportFormat::from_path(&path).unwrap_or(ReportFormat::Html);
+    std::fs::write(&path, render(report, format))
+        .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+}
+
+/// Combo box for choosing the correlation measure
+fn measure_selector(ui: &mut Ui, measure: &mut CorrelationMeasure, id: &str) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(measure.name())
+        .show_ui(ui, |ui| {
+            for option in CorrelationMeasure::ALL {
+                ui.selectable_value(measure, option, option.name())
+                    .on_hover_text(option.scale());
+            }
+        });
+}
+
+/// Combo box for choosing the document ranking
+fn ranking_selector(ui: &mut Ui, ranking: &mut RankingMethod, id: &str) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(ranking.title())
+        .show_ui(ui, |ui| {
+            for option in RankingMethod::ALL {
+                ui.selectable_value(ranking, option, option.title());
+            }
+        });
+}
+
+/// Combo box for choosing the language
+fn language_selector(ui: &mut Ui, language: &mut Language, id: &str) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(language.name())
+        .show_ui(ui, |ui| {
+            for option in Language::ALL {
+                ui.selectable_value(language, option, option.name());
+            }
+        });
+}
+
+/// Combo box for choosing the co-occurrence window, with the size of word windows
+fn window_selector(ui: &mut Ui, window: &mut CooccurrenceWindow, id: &str) {
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(match *window {
+                CooccurrenceWindow::Words(_) => "words".to_string(),
+                other => other.to_string(),
+            })
+            .show_ui(ui, |ui| {
+                for option in [
+                    CooccurrenceWindow::Document,
+                    CooccurrenceWindow::Sentence,
+                    CooccurrenceWindow::Paragraph,
+                    CooccurrenceWindow::Page,
+                ] {
+                    ui.selectable_value(window, option, option.to_string());
+                }
+                let is_words = matches!(window, CooccurrenceWindow::Words(_));
+                if ui.selectable_label(is_words, "words").clicked() && !is_words {
+                    *window = CooccurrenceWindow::Words(50);
+                }
+            });
+        
+        if let CooccurrenceWindow::Words(n) = window {
+            ui.add(egui::DragValue::new(n).clamp_range(1..=10_000).suffix(" words"));
+        }
+    });
+}
+
+/// Sensible threshold slider range for a measure
+fn threshold_range(measure: CorrelationMeasure) -> std::ops::RangeInclusive<f64> {
+    match measure {
+        CorrelationMeasure::CoOccurrence => 0.0..=10.0,
+        CorrelationMeasure::Jaccard | CorrelationMeasure::Dice => 0.0..=1.0,
+        _ => -1.0..=1.0,
+    }
+}
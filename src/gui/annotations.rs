@@ -0,0 +1,817 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use egui::{Color32, Painter, Pos2, Rect, Rounding, Stroke, Vec2};
+use lopdf::{Dictionary, Document, IncrementalDocument, Object, ObjectId, StringFormat};
+
+/// Default page box (US Letter) used when a page has no usable /MediaBox
+pub const DEFAULT_MEDIA_BOX: [f32; 4] = [0.0, 0.0, 612.0, 792.0];
+
+/// Custom error type for annotation operations
+#[derive(Debug)]
+pub enum AnnotationError {
+    IoError(std::io::Error),
+    PdfError(String),
+    OtherError(String),
+}
+
+impl fmt::Display for AnnotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationError::IoError(e) => write!(f, "I/O error: {}", e),
+            AnnotationError::PdfError(e) => write!(f, "PDF error: {}", e),
+            AnnotationError::OtherError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for AnnotationError {}
+
+impl From<std::io::Error> for AnnotationError {
+    fn from(err: std::io::Error) -> Self {
+        AnnotationError::IoError(err)
+    }
+}
+
+impl From<lopdf::Error> for AnnotationError {
+    fn from(err: lopdf::Error) -> Self {
+        AnnotationError::PdfError(err.to_string())
+    }
+}
+
+/// Annotation subtypes the viewer knows how to display
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnnotationKind {
+    Highlight,
+    Underline,
+    StrikeOut,
+    Squiggly,
+    Note,
+    FreeText,
+    Link,
+    Ink,
+    Other,
+}
+
+impl AnnotationKind {
+    fn from_subtype(subtype: &[u8]) -> Self {
+        match subtype {
+            b"Highlight" => AnnotationKind::Highlight,
+            b"Underline" => AnnotationKind::Underline,
+            b"StrikeOut" => AnnotationKind::StrikeOut,
+            b"Squiggly" => AnnotationKind::Squiggly,
+            b"Text" => AnnotationKind::Note,
+            b"FreeText" => AnnotationKind::FreeText,
+            b"Link" => AnnotationKind::Link,
+            b"Ink" => AnnotationKind::Ink,
+            _ => AnnotationKind::Other,
+        }
+    }
+
+    /// PDF /Subtype name for this kind
+    fn subtype(&self) -> &'static str {
+        match self {
+            AnnotationKind::Highlight => "Highlight",
+            AnnotationKind::Underline => "Underline",
+            AnnotationKind::StrikeOut => "StrikeOut",
+            AnnotationKind::Squiggly => "Squiggly",
+            AnnotationKind::Note => "Text",
+            AnnotationKind::FreeText => "FreeText",
+            AnnotationKind::Link => "Link",
+            AnnotationKind::Ink => "Ink",
+            AnnotationKind::Other => "Square",
+        }
+    }
+
+    /// Human readable label
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnnotationKind::Highlight => "Highlight",
+            AnnotationKind::Underline => "Underline",
+            AnnotationKind::StrikeOut => "Strike-out",
+            AnnotationKind::Squiggly => "Squiggly",
+            AnnotationKind::Note => "Note",
+            AnnotationKind::FreeText => "Free text",
+            AnnotationKind::Link => "Link",
+            AnnotationKind::Ink => "Ink",
+            AnnotationKind::Other => "Annotation",
+        }
+    }
+
+    /// Colour used when the annotation has no /C entry
+    fn default_color(&self) -> Color32 {
+        match self {
+            AnnotationKind::Highlight => Color32::from_rgb(255, 230, 0),
+            AnnotationKind::Underline | AnnotationKind::Squiggly => Color32::from_rgb(0, 160, 80),
+            AnnotationKind::StrikeOut => Color32::from_rgb(220, 40, 40),
+            AnnotationKind::Note => Color32::from_rgb(255, 200, 60),
+            AnnotationKind::Link => Color32::from_rgb(90, 170, 255),
+            AnnotationKind::Ink => Color32::from_rgb(220, 40, 40),
+            AnnotationKind::FreeText | AnnotationKind::Other => Color32::from_rgb(160, 160, 170),
+        }
+    }
+}
+
+/// A single annotation on a page, in PDF user space coordinates
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    /// Zero-based page index
+    pub page: usize,
+    /// Bounding box as [x1, y1, x2, y2] with x1 <= x2 and y1 <= y2
+    pub rect: [f32; 4],
+    pub contents: String,
+    pub author: String,
+    pub color: Color32,
+    /// Quadrilaterals for text markup annotations (8 numbers each)
+    pub quad_points: Vec<[f32; 8]>,
+    /// Strokes for ink annotations
+    pub ink: Vec<Vec<(f32, f32)>>,
+    /// Target of a link annotation
+    pub link: Option<LinkTarget>,
+    /// Whether this annotation was authored in this session and is not yet saved
+    pub unsaved: bool,
+}
+
+/// Where a link annotation points to
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    /// Zero-based page index inside the same document
+    Page(usize),
+    /// External URI handed to the system opener
+    Uri(String),
+}
+
+impl Annotation {
+    /// Create a new highlight covering the given rectangle
+    pub fn new_highlight(page: usize, rect: [f32; 4], color: Color32) -> Self {
+        let rect = normalize_rect(rect);
+        Self {
+            kind: AnnotationKind::Highlight,
+            page,
+            rect,
+            contents: String::new(),
+            author: default_author(),
+            color,
+            quad_points: vec![[rect[0], rect[3], rect[2], rect[3], rect[0], rect[1], rect[2], rect[1]]],
+            ink: Vec::new(),
+            link: None,
+            unsaved: true,
+        }
+    }
+
+    /// Create a new sticky note anchored at the given point
+    pub fn new_note(page: usize, x: f32, y: f32, contents: String) -> Self {
+        Self {
+            kind: AnnotationKind::Note,
+            page,
+            rect: [x, y - 20.0, x + 20.0, y],
+            contents,
+            author: default_author(),
+            color: AnnotationKind::Note.default_color(),
+            quad_points: Vec::new(),
+            ink: Vec::new(),
+            link: None,
+            unsaved: true,
+        }
+    }
+
+    /// Create a new ink annotation from a single stroke
+    pub fn new_ink(page: usize, stroke: Vec<(f32, f32)>, color: Color32) -> Self {
+        let mut rect = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+        for &(x, y) in &stroke {
+            rect[0] = rect[0].min(x);
+            rect[1] = rect[1].min(y);
+            rect[2] = rect[2].max(x);
+            rect[3] = rect[3].max(y);
+        }
+        Self {
+            kind: AnnotationKind::Ink,
+            page,
+            rect: [rect[0] - 2.0, rect[1] - 2.0, rect[2] + 2.0, rect[3] + 2.0],
+            contents: String::new(),
+            author: default_author(),
+            color,
+            quad_points: Vec::new(),
+            ink: vec![stroke],
+            link: None,
+            unsaved: true,
+        }
+    }
+
+    /// Check whether a point in PDF user space lies inside the annotation
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.rect[0] && x <= self.rect[2] && y >= self.rect[1] && y <= self.rect[3]
+    }
+}
+
+/// Annotations and page boxes parsed from a document
+#[derive(Default)]
+pub struct DocumentAnnotations {
+    pub by_page: HashMap<usize, Vec<Annotation>>,
+    pub media_boxes: HashMap<usize, [f32; 4]>,
+}
+
+impl DocumentAnnotations {
+    /// Page box for a page, falling back to US Letter
+    pub fn media_box(&self, page: usize) -> [f32; 4] {
+        self.media_boxes.get(&page).copied().unwrap_or(DEFAULT_MEDIA_BOX)
+    }
+
+    /// Annotations on a page
+    pub fn page(&self, page: usize) -> &[Annotation] {
+        self.by_page.get(&page).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Total number of annotations in the document
+    pub fn len(&self) -> usize {
+        self.by_page.values().map(|v| v.len()).sum()
+    }
+
+    /// Whether the document has no annotations
+    pub fn is_empty(&self) -> bool {
+        self.by_page.values().all(|v| v.is_empty())
+    }
+
+    /// Number of annotations that have not been saved yet
+    pub fn unsaved_count(&self) -> usize {
+        self.by_page.values().flatten().filter(|a| a.unsaved).count()
+    }
+
+    /// Add an annotation authored in the viewer
+    pub fn add(&mut self, annotation: Annotation) {
+        self.by_page.entry(annotation.page).or_default().push(annotation);
+    }
+
+    /// Annotations authored in this session that still need saving
+    pub fn unsaved(&self) -> Vec<Annotation> {
+        let mut result: Vec<Annotation> = self.by_page.values()
+            .flatten()
+            .filter(|a| a.unsaved)
+            .cloned()
+            .collect();
+        result.sort_by_key(|a| a.page);
+        result
+    }
+
+    /// Drop all annotations that have not been saved
+    pub fn discard_unsaved(&mut self) {
+        for annotations in self.by_page.values_mut() {
+            annotations.retain(|a| !a.unsaved);
+        }
+    }
+}
+
+/// Parse all annotations and page boxes from a loaded document
+pub fn load_annotations(doc: &Document) -> DocumentAnnotations {
+    let mut result = DocumentAnnotations::default();
+    let page_indices = page_index_map(doc);
+
+    for (page_num, page_id) in doc.get_pages() {
+        let page_index = (page_num as usize).saturating_sub(1);
+        let page = match doc.get_dictionary(page_id) {
+            Ok(page) => page,
+            Err(_) => continue,
+        };
+
+        if let Some(media_box) = page_media_box(doc, page) {
+            result.media_boxes.insert(page_index, media_box);
+        }
+
+        let annots = match page.get(b"Annots").and_then(|o| resolve(doc, o).as_array()) {
+            Ok(annots) => annots,
+            Err(_) => continue,
+        };
+
+        let mut annotations = Vec::new();
+        for annot in annots {
+            if let Ok(dict) = resolve(doc, annot).as_dict() {
+                if let Some(annotation) = parse_annotation(doc, dict, page_index, &page_indices) {
+                    annotations.push(annotation);
+                }
+            }
+        }
+
+        if !annotations.is_empty() {
+            result.by_page.insert(page_index, annotations);
+        }
+    }
+
+    result
+}
+
+/// Parse a single annotation dictionary
+fn parse_annotation(
+    doc: &Document,
+    dict: &Dictionary,
+    page: usize,
+    page_indices: &HashMap<ObjectId, usize>,
+) -> Option<Annotation> {
+    let subtype = dict.get(b"Subtype").and_then(Object::as_name).ok()?;
+    // Popups are rendered as part of their parent annotation
+    if subtype == b"Popup" || subtype == b"Widget" {
+        return None;
+    }
+    let kind = AnnotationKind::from_subtype(subtype);
+    let rect = dict.get(b"Rect")
+        .ok()
+        .and_then(|o| number_array(doc, o))
+        .filter(|v| v.len() >= 4)
+        .map(|v| normalize_rect([v[0], v[1], v[2], v[3]]))?;
+
+    let color = dict.get(b"C")
+        .ok()
+        .and_then(|o| number_array(doc, o))
+        .and_then(|v| color_from_components(&v))
+        .unwrap_or_else(|| kind.default_color());
+
+    let quad_points = dict.get(b"QuadPoints")
+        .ok()
+        .and_then(|o| number_array(doc, o))
+        .map(|v| v.chunks_exact(8)
+            .map(|c| [c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])
+            .collect())
+        .unwrap_or_default();
+
+    let ink = dict.get(b"InkList")
+        .ok()
+        .and_then(|o| resolve(doc, o).as_array().ok())
+        .map(|strokes| strokes.iter()
+            .filter_map(|s| number_array(doc, s))
+            .map(|v| v.chunks_exact(2).map(|c| (c[0], c[1])).collect())
+            .collect())
+        .unwrap_or_default();
+
+    let link = if kind == AnnotationKind::Link {
+        parse_link_target(doc, dict, page_indices)
+    } else {
+        None
+    };
+
+    Some(Annotation {
+        kind,
+        page,
+        rect,
+        contents: text_entry(doc, dict, b"Contents"),
+        author: text_entry(doc, dict, b"T"),
+        color,
+        quad_points,
+        ink,
+        link,
+        unsaved: false,
+    })
+}
+
+/// Map page object ids to zero-based page indices
+pub(crate) fn page_index_map(doc: &Document) -> HashMap<ObjectId, usize> {
+    doc.get_pages()
+        .into_iter()
+        .map(|(num, id)| (id, (num as usize).saturating_sub(1)))
+        .collect()
+}
+
+/// Work out where a /Link annotation points: /Dest, /A /GoTo or /A /URI
+fn parse_link_target(
+    doc: &Document,
+    dict: &Dictionary,
+    page_indices: &HashMap<ObjectId, usize>,
+) -> Option<LinkTarget> {
+    if let Ok(dest) = dict.get(b"Dest") {
+        return resolve_destination(doc, dest, page_indices).map(LinkTarget::Page);
+    }
+
+    let action = dict.get(b"A").ok().and_then(|o| resolve(doc, o).as_dict().ok())?;
+    match action.get(b"S").and_then(Object::as_name).ok()? {
+        b"GoTo" => {
+            let dest = action.get(b"D").ok()?;
+            resolve_destination(doc, dest, page_indices).map(LinkTarget::Page)
+        },
+        b"URI" => {
+            let uri = action.get(b"URI").ok().and_then(|o| resolve(doc, o).as_str().ok())?;
+            Some(LinkTarget::Uri(String::from_utf8_lossy(uri).to_string()))
+        },
+        _ => None,
+    }
+}
+
+/// Resolve an explicit or named destination to a zero-based page index
+pub(crate) fn resolve_destination(
+    doc: &Document,
+    dest: &Object,
+    page_indices: &HashMap<ObjectId, usize>,
+) -> Option<usize> {
+    match resolve(doc, dest) {
+        Object::Array(items) => match items.first()? {
+            Object::Reference(id) => page_indices.get(id).copied(),
+            // Some producers write a page number instead of a page reference
+            Object::Integer(n) => usize::try_from(*n).ok(),
+            _ => None,
+        },
+        // A destination dictionary wraps the array in /D
+        Object::Dictionary(d) => resolve_destination(doc, d.get(b"D").ok()?, page_indices),
+        Object::Name(name) => lookup_named_destination(doc, name)
+            .and_then(|d| resolve_destination(doc, &d, page_indices)),
+        Object::String(name, _) => lookup_named_destination(doc, name)
+            .and_then(|d| resolve_destination(doc, &d, page_indices)),
+        _ => None,
+    }
+}
+
+/// Look up a named destination in the catalog /Dests dictionary or the /Names tree
+fn lookup_named_destination(doc: &Document, name: &[u8]) -> Option<Object> {
+    let catalog = doc.catalog().ok()?;
+
+    if let Some(dests) = catalog.get(b"Dests").ok().and_then(|o| resolve(doc, o).as_dict().ok()) {
+        if let Ok(dest) = dests.get(name) {
+            return Some(resolve(doc, dest).clone());
+        }
+    }
+
+    let tree = catalog.get(b"Names").ok()
+        .and_then(|o| resolve(doc, o).as_dict().ok())?
+        .get(b"Dests").ok()
+        .and_then(|o| resolve(doc, o).as_dict().ok())?;
+    lookup_name_tree(doc, tree, name, 0)
+}
+
+/// Search a name tree node (and its kids) for a key
+fn lookup_name_tree(doc: &Document, node: &Dictionary, name: &[u8], depth: usize) -> Option<Object> {
+    if depth > 32 {
+        return None;
+    }
+
+    if let Ok(names) = node.get(b"Names").and_then(|o| resolve(doc, o).as_array()) {
+        for pair in names.chunks_exact(2) {
+            if resolve(doc, &pair[0]).as_str().ok() == Some(name) {
+                return Some(resolve(doc, &pair[1]).clone());
+            }
+        }
+    }
+
+    if let Ok(kids) = node.get(b"Kids").and_then(|o| resolve(doc, o).as_array()) {
+        for kid in kids {
+            if let Ok(kid) = resolve(doc, kid).as_dict() {
+                if let Some(found) = lookup_name_tree(doc, kid, name, depth + 1) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the page box, following /Parent for inherited attributes
+fn page_media_box(doc: &Document, page: &Dictionary) -> Option<[f32; 4]> {
+    let mut node = page;
+    for _ in 0..32 {
+        for key in [&b"CropBox"[..], &b"MediaBox"[..]] {
+            if let Some(v) = node.get(key).ok().and_then(|o| number_array(doc, o)) {
+                if v.len() >= 4 {
+                    return Some(normalize_rect([v[0], v[1], v[2], v[3]]));
+                }
+            }
+        }
+        node = node.get(b"Parent")
+            .and_then(Object::as_reference)
+            .and_then(|id| doc.get_dictionary(id))
+            .ok()?;
+    }
+    None
+}
+
+/// Follow an indirect reference if the object is one
+pub(crate) fn resolve<'a>(doc: &'a Document, object: &'a Object) -> &'a Object {
+    doc.dereference(object).map(|(_, o)| o).unwrap_or(object)
+}
+
+/// Read an array of numbers, resolving references
+fn number_array(doc: &Document, object: &Object) -> Option<Vec<f32>> {
+    resolve(doc, object).as_array().ok().map(|items| {
+        items.iter()
+            .filter_map(|item| resolve(doc, item).as_float().ok())
+            .collect()
+    })
+}
+
+/// Read a text string entry from a dictionary
+pub(crate) fn text_entry(doc: &Document, dict: &Dictionary, key: &[u8]) -> String {
+    dict.get(key)
+        .ok()
+        .and_then(|o| resolve(doc, o).as_str().ok())
+        .map(decode_pdf_string)
+        .unwrap_or_default()
+}
+
+/// Decode a PDF text string (UTF-16BE with BOM or PDFDocEncoding)
+pub(crate) fn decode_pdf_string(bytes: &[u8]) -> String {
+    if bytes.len() >= 2 && bytes[0] == 0xfe && bytes[1] == 0xff {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        // PDFDocEncoding matches Latin-1 for the printable range
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+/// Encode a text string for writing into a PDF
+fn encode_pdf_string(text: &str) -> Object {
+    if text.is_ascii() {
+        Object::String(text.as_bytes().to_vec(), StringFormat::Literal)
+    } else {
+        let mut bytes = vec![0xfe, 0xff];
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+        Object::String(bytes, StringFormat::Hexadecimal)
+    }
+}
+
+/// Convert /C colour components (gray, RGB or CMYK) into a colour
+fn color_from_components(c: &[f32]) -> Option<Color32> {
+    let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    match c.len() {
+        1 => Some(Color32::from_gray(to_u8(c[0]))),
+        3 => Some(Color32::from_rgb(to_u8(c[0]), to_u8(c[1]), to_u8(c[2]))),
+        4 => {
+            let k = 1.0 - c[3];
+            Some(Color32::from_rgb(
+                to_u8((1.0 - c[0]) * k),
+                to_u8((1.0 - c[1]) * k),
+                to_u8((1.0 - c[2]) * k),
+            ))
+        },
+        _ => None,
+    }
+}
+
+/// Order rectangle corners so that x1 <= x2 and y1 <= y2
+fn normalize_rect(r: [f32; 4]) -> [f32; 4] {
+    [r[0].min(r[2]), r[1].min(r[3]), r[0].max(r[2]), r[1].max(r[3])]
+}
+
+/// Author name stored in new annotations
+fn default_author() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "PDFScan".to_string())
+}
+
+/// Map a point in PDF user space to screen coordinates of the rendered page image
+pub fn pdf_to_screen(media_box: [f32; 4], image_rect: Rect, x: f32, y: f32) -> Pos2 {
+    let width = (media_box[2] - media_box[0]).max(1.0);
+    let height = (media_box[3] - media_box[1]).max(1.0);
+    Pos2::new(
+        image_rect.min.x + (x - media_box[0]) / width * image_rect.width(),
+        image_rect.min.y + (media_box[3] - y) / height * image_rect.height(),
+    )
+}
+
+/// Map a screen position on the rendered page image to PDF user space
+pub fn screen_to_pdf(media_box: [f32; 4], image_rect: Rect, pos: Pos2) -> (f32, f32) {
+    let width = (media_box[2] - media_box[0]).max(1.0);
+    let height = (media_box[3] - media_box[1]).max(1.0);
+    (
+        media_box[0] + (pos.x - image_rect.min.x) / image_rect.width().max(1.0) * width,
+        media_box[3] - (pos.y - image_rect.min.y) / image_rect.height().max(1.0) * height,
+    )
+}
+
+/// Map an annotation rectangle to screen coordinates
+pub fn rect_to_screen(media_box: [f32; 4], image_rect: Rect, r: [f32; 4]) -> Rect {
+    Rect::from_two_pos(
+        pdf_to_screen(media_box, image_rect, r[0], r[1]),
+        pdf_to_screen(media_box, image_rect, r[2], r[3]),
+    )
+}
+
+/// Paint annotations over a rendered page.
+///
+/// Saved annotations are already part of the rendered image (poppler draws their
+/// appearance streams), so they only get an outline marker. Unsaved ones are painted fully.
+pub fn paint_annotations(painter: &Painter, annotations: &[Annotation], media_box: [f32; 4], image_rect: Rect) {
+    for annotation in annotations {
+        let rect = rect_to_screen(media_box, image_rect, annotation.rect);
+        let color = annotation.color;
+        let outline = Stroke::new(1.0, color.gamma_multiply(0.8));
+
+        match annotation.kind {
+            AnnotationKind::Highlight
+            | AnnotationKind::Underline
+            | AnnotationKind::StrikeOut
+            | AnnotationKind::Squiggly => {
+                let quads: Vec<Rect> = if annotation.quad_points.is_empty() {
+                    vec![rect]
+                } else {
+                    annotation.quad_points.iter()
+                        .map(|q| {
+                            let xs = [q[0], q[2], q[4], q[6]];
+                            let ys = [q[1], q[3], q[5], q[7]];
+                            let min_x = xs.iter().cloned().fold(f32::MAX, f32::min);
+                            let max_x = xs.iter().cloned().fold(f32::MIN, f32::max);
+                            let min_y = ys.iter().cloned().fold(f32::MAX, f32::min);
+                            let max_y = ys.iter().cloned().fold(f32::MIN, f32::max);
+                            rect_to_screen(media_box, image_rect, [min_x, min_y, max_x, max_y])
+                        })
+                        .collect()
+                };
+
+                for quad in quads {
+                    match annotation.kind {
+                        AnnotationKind::Highlight => {
+                            if annotation.unsaved {
+                                painter.rect_filled(quad, Rounding::ZERO, color.gamma_multiply(0.35));
+                            } else {
+                                painter.rect_stroke(quad, Rounding::ZERO, outline);
+                            }
+                        },
+                        AnnotationKind::StrikeOut => {
+                            painter.line_segment(
+                                [Pos2::new(quad.min.x, quad.center().y), Pos2::new(quad.max.x, quad.center().y)],
+                                Stroke::new(1.5, color),
+                            );
+                        },
+                        _ => {
+                            painter.line_segment(
+                                [Pos2::new(quad.min.x, quad.max.y), Pos2::new(quad.max.x, quad.max.y)],
+                                Stroke::new(1.5, color),
+                            );
+                        },
+                    }
+                }
+            },
+            AnnotationKind::Note => {
+                let icon = Rect::from_min_size(rect.min, Vec2::splat(16.0));
+                painter.rect_filled(icon, Rounding::same(2.0), color);
+                painter.rect_stroke(icon, Rounding::same(2.0), Stroke::new(1.0, Color32::from_rgb(120, 90, 0)));
+                painter.line_segment(
+                    [icon.min + Vec2::new(4.0, 6.0), icon.min + Vec2::new(12.0, 6.0)],
+                    Stroke::new(1.0, Color32::from_rgb(120, 90, 0)),
+                );
+                painter.line_segment(
+                    [icon.min + Vec2::new(4.0, 10.0), icon.min + Vec2::new(12.0, 10.0)],
+                    Stroke::new(1.0, Color32::from_rgb(120, 90, 0)),
+                );
+            },
+            AnnotationKind::Ink => {
+                let stroke = Stroke::new(2.0, color);
+                for points in &annotation.ink {
+                    for pair in points.windows(2) {
+                        painter.line_segment([
+                            pdf_to_screen(media_box, image_rect, pair[0].0, pair[0].1),
+                            pdf_to_screen(media_box, image_rect, pair[1].0, pair[1].1),
+                        ], stroke);
+                    }
+                }
+            },
+            AnnotationKind::Link => {
+                painter.rect_stroke(rect, Rounding::ZERO, Stroke::new(1.0, color.gamma_multiply(0.5)));
+            },
+            AnnotationKind::FreeText | AnnotationKind::Other => {
+                painter.rect_stroke(rect, Rounding::ZERO, outline);
+            },
+        }
+    }
+}
+
+/// Build the annotation dictionary written into the PDF
+fn annotation_to_dict(annotation: &Annotation, page_id: ObjectId) -> Dictionary {
+    let mut dict = Dictionary::new();
+    dict.set("Type", Object::Name(b"Annot".to_vec()));
+    dict.set("Subtype", Object::Name(annotation.kind.subtype().as_bytes().to_vec()));
+    dict.set("Rect", Object::Array(annotation.rect.iter().map(|&v| Object::Real(v)).collect()));
+    dict.set("P", Object::Reference(page_id));
+    // Print flag so the annotation shows up when printing
+    dict.set("F", Object::Integer(4));
+    dict.set("C", Object::Array(vec![
+        Object::Real(annotation.color.r() as f32 / 255.0),
+        Object::Real(annotation.color.g() as f32 / 255.0),
+        Object::Real(annotation.color.b() as f32 / 255.0),
+    ]));
+    dict.set("T", encode_pdf_string(&annotation.author));
+    dict.set("M", encode_pdf_string(&chrono::Local::now().format("D:%Y%m%d%H%M%S").to_string()));
+    if !annotation.contents.is_empty() {
+        dict.set("Contents", encode_pdf_string(&annotation.contents));
+    }
+
+    match annotation.kind {
+        AnnotationKind::Highlight
+        | AnnotationKind::Underline
+        | AnnotationKind::StrikeOut
+        | AnnotationKind::Squiggly => {
+            let quads: Vec<Object> = annotation.quad_points.iter()
+                .flat_map(|q| q.iter().map(|&v| Object::Real(v)))
+                .collect();
+            dict.set("QuadPoints", Object::Array(quads));
+            dict.set("CA", Object::Real(0.4));
+        },
+        AnnotationKind::Note => {
+            dict.set("Name", Object::Name(b"Comment".to_vec()));
+            dict.set("Open", Object::Boolean(false));
+        },
+        AnnotationKind::Ink => {
+            let strokes: Vec<Object> = annotation.ink.iter()
+                .map(|stroke| Object::Array(stroke.iter()
+                    .flat_map(|&(x, y)| [Object::Real(x), Object::Real(y)])
+                    .collect()))
+                .collect();
+            dict.set("InkList", Object::Array(strokes));
+            let mut border = Dictionary::new();
+            border.set("W", Object::Integer(2));
+            dict.set("BS", Object::Dictionary(border));
+        },
+        _ => {},
+    }
+
+    dict
+}
+
+/// Append annotations to a PDF as an incremental update.
+///
+/// The original bytes are kept untouched; the new annotation objects, the modified
+/// page dictionaries and a new cross-reference section are appended at the end of the file.
+pub fn save_annotations(path: &Path, annotations: &[Annotation]) -> Result<usize, AnnotationError> {
+    if annotations.is_empty() {
+        return Ok(0);
+    }
+
+    let mut incremental = IncrementalDocument::load(path)?;
+    if incremental.get_prev_documents().is_encrypted() {
+        return Err(AnnotationError::OtherError(
+            "Cannot add annotations to an encrypted PDF".to_string()
+        ));
+    }
+    let pages = incremental.get_prev_documents().get_pages();
+
+    for annotation in annotations {
+        let page_id = *pages.get(&(annotation.page as u32 + 1))
+            .ok_or_else(|| AnnotationError::OtherError(
+                format!("Page {} does not exist", annotation.page + 1)
+            ))?;
+
+        let annot_id = incremental.new_document.add_object(annotation_to_dict(annotation, page_id));
+        incremental.opt_clone_object_to_new_document(page_id)?;
+
+        let existing = incremental.new_document
+            .get_dictionary(page_id)?
+            .get(b"Annots")
+            .ok()
+            .cloned();
+
+        match existing {
+            Some(Object::Reference(array_id)) => {
+                incremental.opt_clone_object_to_new_document(array_id)?;
+                incremental.new_document
+                    .get_object_mut(array_id)
+                    .and_then(Object::as_array_mut)?
+                    .push(Object::Reference(annot_id));
+            },
+            Some(Object::Array(mut array)) => {
+                array.push(Object::Reference(annot_id));
+                incremental.new_document.get_dictionary_mut(page_id)?.set("Annots", Object::Array(array));
+            },
+            _ => {
+                incremental.new_document
+                    .get_dictionary_mut(page_id)?
+                    .set("Annots", Object::Array(vec![Object::Reference(annot_id)]));
+            },
+        }
+    }
+
+    // Write next to the original and swap in, so a failed write never truncates the PDF
+    let temp_path = path.with_extension("pdf.pdfscan-tmp");
+    incremental.save(&temp_path)?;
+    std::fs::rename(&temp_path, path)?;
+
+    Ok(annotations.len())
+}
+
+/// Find the topmost link annotation at a point in PDF user space
+pub fn link_at(annotations: &[Annotation], x: f32, y: f32) -> Option<&LinkTarget> {
+    annotations.iter()
+        .rev()
+        .filter(|a| a.contains(x, y))
+        .find_map(|a| a.link.as_ref())
+}
+
+/// Annotation authoring tool selected in the viewer toolbar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnnotationTool {
+    Browse,
+    Highlight,
+    Note,
+    Ink,
+}
+
+/// A sticky note waiting for its text to be entered
+pub struct PendingNote {
+    pub page: usize,
+    pub x: f32,
+    pub y: f32,
+    pub text: String,
+}
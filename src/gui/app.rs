@@ -5,9 +5,12 @@ use egui::{Context, Ui, ViewportCommand};
 use rfd::FileDialog;
 use dirs;
 
-use super::pdf_viewer::PdfViewer;
+use super::jobs::{JobKind, JobManager};
+use super::tabs::DocumentTabs;
 use super::search_panel::SearchPanel;
 use super::analysis_panel::AnalysisPanel;
+use super::dedupe_panel::DedupePanel;
+use super::batch_dialog::BatchDialog;
 
 /// The main application state
 pub struct PdfScanApp {
@@ -16,8 +19,8 @@ pub struct PdfScanApp {
     show_sidebar: bool,
     sidebar_width: f32,
     
-    // PDF viewer
-    pdf_viewer: PdfViewer,
+    // Open documents, one PDF viewer per tab
+    tabs: DocumentTabs,
     
     // Search functionality
     search_panel: SearchPanel,
@@ -25,6 +28,15 @@ pub struct PdfScanApp {
     // Analysis functionality
     analysis_panel: AnalysisPanel,
     
+    // Duplicate detection
+    dedupe_panel: DedupePanel,
+    
+    // Tools → Batch Process
+    batch_dialog: BatchDialog,
+    
+    // Background work shared by all components
+    jobs: JobManager,
+    
     // Global state
     recent_files: Vec<PathBuf>,
     theme: Theme,
@@ -35,6 +47,7 @@ enum Tab {
     Viewer,
     Search,
     Analysis,
+    Duplicates,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -50,15 +63,20 @@ impl PdfScanApp {
         
         // Load recent files if available
         let recent_files = load_recent_files().unwrap_or_default();
+        
+        let jobs = JobManager::new(&cc.egui_ctx);
 
         // Create the app
         Self {
             current_tab: Tab::Viewer,
             show_sidebar: true,
             sidebar_width: 250.0,
-            pdf_viewer: PdfViewer::new(),
-            search_panel: SearchPanel::new(),
-            analysis_panel: AnalysisPanel::new(),
+            tabs: DocumentTabs::restore(jobs.clone()),
+            search_panel: SearchPanel::new(jobs.clone()),
+            analysis_panel: AnalysisPanel::new(jobs.clone()),
+            dedupe_panel: DedupePanel::new(jobs.clone()),
+            batch_dialog: BatchDialog::new(jobs.clone()),
+            jobs,
             recent_files,
             theme: Theme::Dark,
         }
@@ -71,7 +89,8 @@ impl PdfScanApp {
                 if ui.button("Open PDF...").clicked() {
                     let file_path = open_file_dialog();
                     if let Some(path) = file_path {
-                        self.pdf_viewer.load_pdf(&path);
+                        self.tabs.open_in_new_tab(&path);
+                        self.current_tab = Tab::Viewer;
                         add_to_recent_files(&mut self.recent_files, path);
                         ui.close_menu();
                     }
@@ -88,7 +107,8 @@ impl PdfScanApp {
                 ui.menu_button("Recent Files", |ui| {
                     for path in &self.recent_files {
                         if ui.button(path.file_name().unwrap_or_default().to_string_lossy().to_string()).clicked() {
-                            self.pdf_viewer.load_pdf(path);
+                            self.tabs.open_in_new_tab(path);
+                            self.current_tab = Tab::Viewer;
                             ui.close_menu();
                         }
                     }
@@ -108,6 +128,10 @@ impl PdfScanApp {
                     ui.close_menu();
                 }
                 
+                if ui.checkbox(&mut self.tabs.open_results_in_new_tab, "Open Results in New Tab").clicked() {
+                    ui.close_menu();
+                }
+                
                 if ui.radio_value(&mut self.theme, Theme::Light, "Light Theme").clicked() {
                     apply_theme(ctx, self.theme);
                     ui.close_menu();
@@ -120,7 +144,7 @@ impl PdfScanApp {
             
             ui.menu_button("Tools", |ui| {
                 if ui.button("Extract Text...").clicked() {
-                    if let Some(current_pdf) = self.pdf_viewer.current_pdf() {
+                    if let Some(current_pdf) = self.tabs.active().current_pdf() {
                         self.extract_text_dialog(current_pdf.clone());
                     } else {
                         // Show a message that no PDF is open
@@ -129,7 +153,7 @@ impl PdfScanApp {
                 }
                 
                 if ui.button("Batch Process...").clicked() {
-                    // Implement batch processing dialog
+                    self.batch_dialog.open();
                     ui.close_menu();
                 }
             });
@@ -153,21 +177,16 @@ impl PdfScanApp {
             .add_filter("Text Files", &["txt"])
             .save_file() 
         {
-            // Extract text in a background thread
-            std::thread::spawn(move || {
+            let title = format!("Extract text from {}", pdf_path.file_name().unwrap_or_default().to_string_lossy());
+            
+            // Extract text in the background; the job list reports how it went
+            self.jobs.spawn(JobKind::Extraction, title, move |_| {
                 // Convert the PathBuf to a vector of strings as required by the extract module
                 let input_path = vec![pdf_path.to_string_lossy().to_string()];
                 let output_file = save_path.to_string_lossy().to_string();
                 
-                // Use the extract module to save the text
-                match crate::extract::run(&output_file, &input_path) {
-                    Ok(_) => {
-                        println!("Successfully extracted text to {}", output_file);
-                    },
-                    Err(e) => {
-                        eprintln!("Error extracting text: {}", e);
-                    }
-                }
+                crate::extract::run(&output_file, &input_path)
+                    .map_err(|e| format!("Error extracting text: {}", e))
             });
         }
     }
@@ -180,6 +199,11 @@ impl eframe::App for PdfScanApp {
             // Draw the menu bar
             self.menu_bar(ui, ctx);
         });
+        
+        // Background jobs in the status bar
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            self.jobs.show_status_bar(ui);
+        });
 
         // Draw the sidebar if enabled
         if self.show_sidebar {
@@ -204,6 +228,9 @@ impl eframe::App for PdfScanApp {
                         if ui.selectable_label(self.current_tab == Tab::Analysis, "📊 Analysis").clicked() {
                             self.current_tab = Tab::Analysis;
                         }
+                        if ui.selectable_label(self.current_tab == Tab::Duplicates, "📑 Duplicates").clicked() {
+                            self.current_tab = Tab::Duplicates;
+                        }
                     });
                     
                     ui.separator();
@@ -211,16 +238,20 @@ impl eframe::App for PdfScanApp {
                     // Display different sidebar content based on the selected tab
                     match self.current_tab {
                         Tab::Viewer => {
-                            // Show document outline if available
-                            self.pdf_viewer.show_outline(ui);
+                            // Show page thumbnails or the document outline
+                            self.tabs.show_sidebar(ui);
                         },
                         Tab::Search => {
                             // Show search options
-                            self.search_panel.show_options(ui, &self.pdf_viewer);
+                            self.search_panel.show_options(ui, self.tabs.active());
                         },
                         Tab::Analysis => {
                             // Show analysis options
-                            self.analysis_panel.show_options(ui, &self.pdf_viewer);
+                            self.analysis_panel.show_options(ui, self.tabs.active());
+                        },
+                        Tab::Duplicates => {
+                            // Show duplicate search options
+                            self.dedupe_panel.show_options(ui, self.tabs.active());
                         },
                     }
                     
@@ -234,16 +265,28 @@ impl eframe::App for PdfScanApp {
             // Draw the main content based on the selected tab
             match self.current_tab {
                 Tab::Viewer => {
-                    self.pdf_viewer.show(ui, ctx);
+                    self.tabs.show_tab_bar(ui);
+                    ui.separator();
+                    self.tabs.show(ui, ctx);
                 },
                 Tab::Search => {
-                    self.search_panel.show(ui, ctx, &mut self.pdf_viewer);
+                    self.search_panel.show(ui, ctx, &mut self.tabs);
                 },
                 Tab::Analysis => {
-                    self.analysis_panel.show(ui, ctx, &mut self.pdf_viewer);
+                    self.analysis_panel.show(ui, ctx, &mut self.tabs);
+                },
+                Tab::Duplicates => {
+                    self.dedupe_panel.show(ui, ctx, &mut self.tabs);
                 },
             }
         });
+        
+        self.batch_dialog.show(ctx);
+        self.jobs.show_toasts(ctx);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.tabs.remember_reading_positions();
     }
 }
 
@@ -0,0 +1,719 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use egui::{Color32, Context, RichText, TextEdit, Ui};
+use rayon::prelude::*;
+
+use crate::cache::{document_metadata, INFO_KEYS};
+use crate::report::{csv_field, json_string, ReportFormat};
+use crate::saved_searches::{load_saved_searches, SavedSearch};
+use crate::search::{find_matches, SearchMode, SearchOptions};
+use crate::trends::parse_pdf_date;
+
+use super::cache::{extract_record, load_record_from_cache, record_cache_path, save_record_to_cache};
+use super::jobs::{JobContext, JobHandle, JobKind, JobManager};
+use super::utils::truncate_string;
+
+/// File format of extracted text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextFormat {
+    Text,
+    Markdown,
+    /// An object with the file's path and text
+    Json,
+}
+
+impl TextFormat {
+    const ALL: [TextFormat; 3] = [TextFormat::Text, TextFormat::Markdown, TextFormat::Json];
+
+    fn name(&self) -> &'static str {
+        match self {
+            TextFormat::Text => "Plain text",
+            TextFormat::Markdown => "Markdown",
+            TextFormat::Json => "JSON",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            TextFormat::Text => "txt",
+            TextFormat::Markdown => "md",
+            TextFormat::Json => "json",
+        }
+    }
+
+    /// Contents of the output file for the text of a PDF
+    fn render(&self, path: &Path, text: &str) -> String {
+        match self {
+            TextFormat::Text => text.to_string(),
+            TextFormat::Markdown => format!(
+                "# {}\n\n{}\n",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                text.trim_end()
+            ),
+            TextFormat::Json => format!(
+                "{{\"path\": {}, \"text\": {}}}\n",
+                json_string(&path.display().to_string()),
+                json_string(text)
+            ),
+        }
+    }
+}
+
+/// File format of exported metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetadataFormat {
+    Csv,
+    Json,
+}
+
+impl MetadataFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            MetadataFormat::Csv => "CSV",
+            MetadataFormat::Json => "JSON",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            MetadataFormat::Csv => "csv",
+            MetadataFormat::Json => "json",
+        }
+    }
+}
+
+/// What a batch run does; copied into the job when it starts
+#[derive(Clone)]
+struct BatchSettings {
+    input_folders: Vec<PathBuf>,
+    output_folder: PathBuf,
+    extract: Option<TextFormat>,
+    search: Option<(String, SearchOptions)>,
+    metadata: Option<MetadataFormat>,
+    analysis: Option<(Vec<String>, ReportFormat)>,
+    /// Fill the text cache that the Search tab reads, in the cache of each PDF's own directory
+    index: bool,
+}
+
+/// How one operation went for one file
+struct LogEntry {
+    path: PathBuf,
+    operation: &'static str,
+    outcome: Result<String, String>,
+}
+
+/// Document information and page count of a PDF
+struct PdfMetadata {
+    /// Values of `INFO_KEYS`, empty where missing
+    values: Vec<String>,
+    pages: usize,
+}
+
+/// What the corpus-wide outputs need from each file
+struct FileResult {
+    path: PathBuf,
+    matches: Option<usize>,
+    metadata: Option<PdfMetadata>,
+}
+
+/// Tools → Batch Process: runs operations over every PDF in a set of folders
+pub struct BatchDialog {
+    open: bool,
+    input_folders: Vec<PathBuf>,
+    output_folder: Option<PathBuf>,
+
+    extract: bool,
+    text_format: TextFormat,
+    search: bool,
+    search_phrase: String,
+    search_options: SearchOptions,
+    saved_searches: Vec<SavedSearch>,
+    metadata: bool,
+    metadata_format: MetadataFormat,
+    analysis: bool,
+    keywords: String,
+    report_format: ReportFormat,
+    index: bool,
+
+    job: Option<JobHandle<String>>,
+    // Filled in by the job as files are processed
+    log: Arc<Mutex<Vec<LogEntry>>>,
+    summary: Option<Result<String, String>>,
+    jobs: JobManager,
+}
+
+impl BatchDialog {
+    pub fn new(jobs: JobManager) -> Self {
+        Self {
+            open: false,
+            input_folders: Vec::new(),
+            output_folder: None,
+            extract: true,
+            text_format: TextFormat::Text,
+            search: false,
+            search_phrase: String::new(),
+            search_options: SearchOptions::default(),
+            saved_searches: Vec::new(),
+            metadata: false,
+            metadata_format: MetadataFormat::Csv,
+            analysis: false,
+            keywords: String::new(),
+            report_format: ReportFormat::Html,
+            index: false,
+            job: None,
+            log: Arc::new(Mutex::new(Vec::new())),
+            summary: None,
+            jobs,
+        }
+    }
+
+    /// Open the dialog, picking up searches saved since it was last open
+    pub fn open(&mut self) {
+        self.open = true;
+        self.saved_searches = load_saved_searches().unwrap_or_default();
+    }
+
+    /// Draw the dialog if it is open
+    pub fn show(&mut self, ctx: &Context) {
+        self.poll();
+
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Batch Processing")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                self.show_contents(ui);
+            });
+        self.open = open;
+    }
+
+    /// Pick up the result of a finished run
+    fn poll(&mut self) {
+        if let Some(result) = self.job.as_ref().and_then(|job| job.poll()) {
+            self.job = None;
+            self.summary = Some(result);
+        }
+    }
+
+    fn show_contents(&mut self, ui: &mut Ui) {
+        let running = self.job.is_some();
+
+        ui.add_enabled_ui(!running, |ui| {
+            ui.strong("Input Folders");
+            let mut remove = None;
+            for (i, folder) in self.input_folders.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.small_button("✖").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.label(truncate_string(&folder.display().to_string(), 70))
+                        .on_hover_text(folder.display().to_string());
+                });
+            }
+            if let Some(i) = remove {
+                self.input_folders.remove(i);
+            }
+            if ui.button("Add Folder...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    if !self.input_folders.contains(&folder) {
+                        self.input_folders.push(folder);
+                    }
+                }
+            }
+
+            ui.separator();
+            ui.strong("Operations");
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.extract, "Extract text as");
+                egui::ComboBox::from_id_source("batch_text_format")
+                    .selected_text(self.text_format.name())
+                    .show_ui(ui, |ui| {
+                        for format in TextFormat::ALL {
+                            ui.selectable_value(&mut self.text_format, format, format.name());
+                        }
+                    });
+            });
+
+            let mut chosen = None;
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.search, "Search for");
+                ui.add(TextEdit::singleline(&mut self.search_phrase).desired_width(200.0));
+                ui.add_enabled_ui(!self.saved_searches.is_empty(), |ui| {
+                    egui::ComboBox::from_id_source("batch_saved_search")
+                        .selected_text("Saved search...")
+                        .show_ui(ui, |ui| {
+                            for search in &self.saved_searches {
+                                if ui.selectable_label(false, &search.name).on_hover_text(search.describe()).clicked() {
+                                    chosen = Some(search.clone());
+                                }
+                            }
+                        });
+                });
+            });
+            if let Some(search) = chosen {
+                self.search = true;
+                self.search_phrase = search.query;
+                self.search_options = search.options;
+            }
+            ui.horizontal(|ui| {
+                ui.add_space(24.0);
+                ui.checkbox(&mut self.search_options.case_sensitive, "Case sensitive");
+                let mut whole_words = self.search_options.mode == SearchMode::WholeWord;
+                if ui.checkbox(&mut whole_words, "Whole words only").changed() {
+                    self.search_options.mode = if whole_words { SearchMode::WholeWord } else { SearchMode::Substring };
+                }
+                if !self.search_options.filter.is_empty() {
+                    ui.label(format!("in files matching \"{}\"", self.search_options.filter));
+                    if ui.small_button("✖").clicked() {
+                        self.search_options.filter.clear();
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.metadata, "Export metadata as");
+                egui::ComboBox::from_id_source("batch_metadata_format")
+                    .selected_text(self.metadata_format.name())
+                    .show_ui(ui, |ui| {
+                        for format in [MetadataFormat::Csv, MetadataFormat::Json] {
+                            ui.selectable_value(&mut self.metadata_format, format, format.name());
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.analysis, "Analysis report for");
+                ui.add(TextEdit::singleline(&mut self.keywords)
+                    .hint_text("keywords, comma separated")
+                    .desired_width(200.0));
+                egui::ComboBox::from_id_source("batch_report_format")
+                    .selected_text(self.report_format.name())
+                    .show_ui(ui, |ui| {
+                        for format in ReportFormat::ALL {
+                            ui.selectable_value(&mut self.report_format, format, format.name());
+                        }
+                    });
+            });
+
+            ui.checkbox(&mut self.index, "Build search index")
+                .on_hover_text("Cache the text of every PDF so searches start immediately");
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.strong("Output Folder:");
+                match &self.output_folder {
+                    Some(folder) => {
+                        ui.label(truncate_string(&folder.display().to_string(), 60))
+                            .on_hover_text(folder.display().to_string());
+                    },
+                    None => {
+                        ui.label(RichText::new("not chosen").weak());
+                    },
+                }
+                if ui.button("Choose...").clicked() {
+                    if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                        self.output_folder = Some(folder);
+                    }
+                }
+            });
+        });
+
+        ui.separator();
+
+        if let Some(job) = &self.job {
+            let progress = job.progress();
+            ui.horizontal(|ui| {
+                let bar = match progress.fraction() {
+                    Some(fraction) => egui::ProgressBar::new(fraction)
+                        .text(format!("{}/{}", progress.done, progress.total)),
+                    None => egui::ProgressBar::new(0.0).animate(true),
+                };
+                ui.add(bar.desired_width(300.0));
+                if job.is_cancelled() {
+                    ui.label("Cancelling...");
+                } else if ui.button("Cancel").clicked() {
+                    job.cancel();
+                }
+            });
+            if !progress.message.is_empty() {
+                ui.label(RichText::new(truncate_string(&progress.message, 80)).small().weak());
+            }
+        } else {
+            let settings = self.settings();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(settings.is_ok(), egui::Button::new("▶ Run")).clicked() {
+                    if let Ok(settings) = &settings {
+                        self.run(settings.clone());
+                    }
+                }
+                if let Err(reason) = &settings {
+                    ui.label(RichText::new(*reason).weak());
+                }
+            });
+        }
+
+        match &self.summary {
+            Some(Ok(summary)) => {
+                ui.label(summary);
+            },
+            Some(Err(e)) => {
+                ui.label(RichText::new(e).color(Color32::RED));
+            },
+            None => {},
+        }
+
+        self.show_log(ui);
+    }
+
+    /// Per-file results, newest run only
+    fn show_log(&self, ui: &mut Ui) {
+        let log = self.log.lock().unwrap();
+        if log.is_empty() {
+            return;
+        }
+
+        let failed = log.iter().filter(|entry| entry.outcome.is_err()).count();
+        ui.label(format!("{} results, {} failed", log.len(), failed));
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        egui::ScrollArea::vertical()
+            .max_height(260.0)
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, log.len(), |ui, rows| {
+                for entry in &log[rows] {
+                    ui.horizontal(|ui| {
+                        match &entry.outcome {
+                            Ok(_) => ui.label(RichText::new("✔").color(Color32::GREEN)),
+                            Err(_) => ui.label(RichText::new("⚠").color(Color32::RED)),
+                        };
+                        ui.label(entry.operation);
+                        let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+                        ui.label(truncate_string(&name, 40)).on_hover_text(entry.path.display().to_string());
+                        match &entry.outcome {
+                            Ok(message) => ui.label(RichText::new(message).weak()),
+                            Err(e) => ui.label(RichText::new(e).color(Color32::RED)),
+                        };
+                    });
+                }
+            });
+    }
+
+    /// The settings of a run, or why it can't start
+    fn settings(&self) -> Result<BatchSettings, &'static str> {
+        if self.input_folders.is_empty() {
+            return Err("Add at least one input folder");
+        }
+        if !(self.extract || self.search || self.metadata || self.analysis || self.index) {
+            return Err("Choose at least one operation");
+        }
+        if self.search && self.search_phrase.trim().is_empty() {
+            return Err("Enter a search phrase");
+        }
+
+        let keywords: Vec<String> = self.keywords
+            .split(',')
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        if self.analysis && keywords.is_empty() {
+            return Err("Enter keywords for the analysis report");
+        }
+
+        let Some(output_folder) = self.output_folder.clone() else {
+            return Err("Choose an output folder");
+        };
+
+        Ok(BatchSettings {
+            input_folders: self.input_folders.clone(),
+            output_folder,
+            extract: self.extract.then_some(self.text_format),
+            search: self.search.then(|| (self.search_phrase.trim().to_string(), self.search_options.clone())),
+            metadata: self.metadata.then_some(self.metadata_format),
+            analysis: self.analysis.then_some((keywords, self.report_format)),
+            index: self.index,
+        })
+    }
+
+    /// Start a run in the background
+    fn run(&mut self, settings: BatchSettings) {
+        self.log.lock().unwrap().clear();
+        self.summary = None;
+
+        let log = self.log.clone();
+        self.job = Some(self.jobs.spawn(JobKind::Batch, "Batch process", move |job| {
+            run_batch(&settings, job, &log)
+        }));
+    }
+}
+
+/// Run the batch operations on every PDF in the input folders, returning a summary
+fn run_batch(settings: &BatchSettings, job: &JobContext, log: &Mutex<Vec<LogEntry>>) -> Result<String, String> {
+    let inputs: Vec<String> = settings.input_folders
+        .iter()
+        .map(|folder| folder.to_string_lossy().to_string())
+        .collect();
+    let pdfs = crate::stats::collect_pdf_paths(&inputs).map_err(|e| e.to_string())?;
+    if pdfs.is_empty() {
+        return Err("No PDF files found in the selected folders".to_string());
+    }
+
+    let output = &settings.output_folder;
+    fs::create_dir_all(output).map_err(|e| format!("Cannot create {}: {}", output.display(), e))?;
+
+    let done = AtomicUsize::new(0);
+    let mut results: Vec<FileResult> = pdfs.par_iter()
+        .filter_map(|path| {
+            if job.is_cancelled() {
+                return None;
+            }
+
+            let result = process_file(path, settings, log);
+            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+            job.progress(done, pdfs.len(), path.display().to_string());
+            Some(result)
+        })
+        .collect();
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let record = |path: PathBuf, operation: &'static str, outcome: Result<String, String>| {
+        log.lock().unwrap().push(LogEntry { path, operation, outcome });
+    };
+
+    if settings.search.is_some() {
+        let path = output.join("search_results.txt");
+        record(path.clone(), "Search", write_search_results(&path, settings, &results));
+    }
+
+    if let Some(format) = settings.metadata {
+        let path = output.join(format!("metadata.{}", format.extension()));
+        record(path.clone(), "Metadata", write_metadata(&path, format, &results));
+    }
+
+    if let Some((keywords, format)) = &settings.analysis {
+        if !job.is_cancelled() {
+            let path = output.join(format!("analysis.{}", format.extension()));
+            let report = crate::stats::analyze_with_progress(
+                &inputs,
+                keywords,
+                &crate::config::get().analysis_options(),
+                &|progress| {
+                    job.progress(progress.done, progress.total, format!("Analyzing {}", progress.path.display()));
+                },
+                job.cancel_flag(),
+            );
+            let outcome = report
+                .map_err(|e| e.to_string())
+                .and_then(|report| write_file(&path, &crate::report::render(&report, *format)));
+            record(path, "Analysis", outcome);
+        }
+    }
+
+    let mut log = log.lock().unwrap();
+    log.sort_by(|a, b| a.path.cmp(&b.path).then(a.operation.cmp(b.operation)));
+    let failed = log.iter().filter(|entry| entry.outcome.is_err()).count();
+
+    let log_path = output.join("batch_log.txt");
+    let lines: String = log.iter()
+        .map(|entry| match &entry.outcome {
+            Ok(message) => format!("OK    {:<9} {}: {}\n", entry.operation, entry.path.display(), message),
+            Err(e) => format!("FAIL  {:<9} {}: {}\n", entry.operation, entry.path.display(), e),
+        })
+        .collect();
+    fs::write(&log_path, lines).map_err(|e| format!("Cannot write {}: {}", log_path.display(), e))?;
+
+    Ok(format!(
+        "Processed {} of {} files, {} of {} results failed. The log is in {}",
+        results.len(),
+        pdfs.len(),
+        failed,
+        log.len(),
+        log_path.display()
+    ))
+}
+
+/// Run the per-file operations on one PDF
+fn process_file(path: &Path, settings: &BatchSettings, log: &Mutex<Vec<LogEntry>>) -> FileResult {
+    let record = |operation: &'static str, outcome: Result<String, String>| {
+        log.lock().unwrap().push(LogEntry { path: path.to_path_buf(), operation, outcome });
+    };
+    let mut result = FileResult { path: path.to_path_buf(), matches: None, metadata: None };
+
+    // The input folder the PDF was found in, to mirror its layout in the output
+    let root = settings.input_folders
+        .iter()
+        .find(|folder| path.starts_with(folder))
+        .map(PathBuf::as_path)
+        .or_else(|| path.parent())
+        .unwrap_or_else(|| Path::new("."));
+
+    if settings.extract.is_some() || settings.search.is_some() || settings.index {
+        let cache_path = record_cache_path(path);
+        let cached = load_record_from_cache(path, &cache_path);
+        let extracted = match &cached {
+            Some(cache_record) => Ok(cache_record.clone()),
+            None => extract_record(path),
+        };
+
+        match extracted {
+            Ok(cache_record) => {
+                let text = cache_record.text();
+                if settings.index {
+                    let outcome = if cached.is_some() {
+                        Ok("Already indexed".to_string())
+                    } else {
+                        save_record_to_cache(&cache_path, &cache_record)
+                            .map(|_| format!("Cached in {}", cache_path.display()))
+                            .map_err(|e| e.to_string())
+                    };
+                    record("Index", outcome);
+                }
+
+                if let Some(format) = settings.extract {
+                    let relative = path.strip_prefix(root)
+                        .unwrap_or_else(|_| Path::new(path.file_name().unwrap_or_default()));
+                    let target = settings.output_folder
+                        .join("text")
+                        .join(relative)
+                        .with_extension(format.extension());
+                    record("Extract", write_file(&target, &format.render(path, &text)));
+                }
+
+                if let Some((phrase, options)) = settings.search.as_ref().filter(|(_, options)| options.accepts(path)) {
+                    let matches = find_matches(&text, phrase, options).len();
+                    result.matches = Some(matches);
+                    let message = match matches {
+                        0 => "No matches".to_string(),
+                        1 => "1 match".to_string(),
+                        n => format!("{} matches", n),
+                    };
+                    record("Search", Ok(message));
+                }
+            },
+            Err(e) => {
+                let operations = [
+                    (settings.index, "Index"),
+                    (settings.extract.is_some(), "Extract"),
+                    (settings.search.is_some(), "Search"),
+                ];
+                for (enabled, operation) in operations {
+                    if enabled {
+                        record(operation, Err(e.clone()));
+                    }
+                }
+            },
+        }
+    }
+
+    if settings.metadata.is_some() {
+        match read_metadata(path) {
+            Ok(metadata) => {
+                record("Metadata", Ok(format!("{} pages", metadata.pages)));
+                result.metadata = Some(metadata);
+            },
+            Err(e) => record("Metadata", Err(e)),
+        }
+    }
+
+    result
+}
+
+/// Document information and page count of a PDF; dates are shown as `YYYY-MM-DD`
+fn read_metadata(path: &Path) -> Result<PdfMetadata, String> {
+    let document = lopdf::Document::load(path).map_err(|e| format!("Cannot read PDF: {}", e))?;
+    let metadata = document_metadata(&document);
+
+    let values = INFO_KEYS.iter()
+        .map(|key| {
+            let Some((_, value)) = metadata.iter().find(|(k, _)| k == key) else {
+                return String::new();
+            };
+
+            match parse_pdf_date(value) {
+                Some((year, month, day)) if key.ends_with("Date") => format!("{:04}-{:02}-{:02}", year, month, day),
+                _ => value.clone(),
+            }
+        })
+        .collect();
+
+    Ok(PdfMetadata { values, pages: document.get_pages().len() })
+}
+
+/// Write the number of matches in each file, files with matches first
+fn write_search_results(path: &Path, settings: &BatchSettings, results: &[FileResult]) -> Result<String, String> {
+    let Some((phrase, _)) = &settings.search else {
+        return Ok(String::new());
+    };
+
+    let mut matching: Vec<(&Path, usize)> = results.iter()
+        .filter_map(|result| Some((result.path.as_path(), result.matches?)))
+        .filter(|(_, matches)| *matches > 0)
+        .collect();
+    matching.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let mut text = format!("Search results for \"{}\"\n\n", phrase);
+    for (file, matches) in &matching {
+        text.push_str(&format!("{}\t{}\n", matches, file.display()));
+    }
+
+    write_file(path, &text)?;
+    Ok(format!("{} of {} files match", matching.len(), results.len()))
+}
+
+/// Write the metadata of every file that could be read
+fn write_metadata(path: &Path, format: MetadataFormat, results: &[FileResult]) -> Result<String, String> {
+    let rows: Vec<(&Path, &PdfMetadata)> = results.iter()
+        .filter_map(|result| Some((result.path.as_path(), result.metadata.as_ref()?)))
+        .collect();
+
+    let contents = match format {
+        MetadataFormat::Csv => {
+            let mut header = vec!["path".to_string(), "pages".to_string()];
+            header.extend(INFO_KEYS.iter().map(|key| key.to_lowercase()));
+            let mut csv = header.join(",");
+            csv.push('\n');
+            for (file, metadata) in &rows {
+                let mut row = vec![csv_field(&file.display().to_string()), metadata.pages.to_string()];
+                row.extend(metadata.values.iter().map(|value| csv_field(value)));
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            csv
+        },
+        MetadataFormat::Json => {
+            let objects: Vec<String> = rows.iter()
+                .map(|(file, metadata)| {
+                    let mut fields = vec![
+                        format!("\"path\": {}", json_string(&file.display().to_string())),
+                        format!("\"pages\": {}", metadata.pages),
+                    ];
+                    for (key, value) in INFO_KEYS.iter().zip(&metadata.values) {
+                        fields.push(format!("{}: {}", json_string(&key.to_lowercase()), json_string(value)));
+                    }
+                    format!("  {{{}}}", fields.join(", "))
+                })
+                .collect();
+            format!("[\n{}\n]\n", objects.join(",\n"))
+        },
+    };
+
+    write_file(path, &contents)?;
+    Ok(format!("{} files", rows.len()))
+}
+
+/// Write a file, creating its folder
+fn write_file(path: &Path, contents: &str) -> Result<String, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    Ok(format!("Wrote {}", path.display()))
+}
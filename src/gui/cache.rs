@@ -0,0 +1,45 @@
+use std::path::{Path, PathBuf};
+
+// The text cache is shared with the command line
+pub use crate::cache::{
+    caching_enabled, extract_record, get_cache_directory, load_or_extract_record,
+    load_record_from_cache, load_text_from_cache, record_cache_path, save_record_to_cache,
+};
+
+/// Get the cached thumbnail path for a page (0-based) rendered at `dpi`
+pub fn get_thumbnail_path(pdf_path: &Path, page_num: usize, dpi: u32) -> PathBuf {
+    // Next to the text, in the cache directory of the PDF's own directory
+    let pdf_dir = pdf_path.parent().unwrap_or_else(|| Path::new("."));
+    let pdf_name = pdf_path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    get_cache_directory(pdf_dir)
+        .join(crate::cache::THUMBNAIL_DIR)
+        .join(format!("{}_p{}_{}dpi.png", pdf_name, page_num + 1, dpi))
+}
+
+/// Load a cached thumbnail unless the PDF changed after it was written
+pub fn load_thumbnail_from_cache(pdf_path: &Path, thumb_path: &Path) -> Option<Vec<u8>> {
+    if !caching_enabled() {
+        return None;
+    }
+    let pdf_modified = std::fs::metadata(pdf_path).and_then(|m| m.modified()).ok()?;
+    let thumb_modified = std::fs::metadata(thumb_path).and_then(|m| m.modified()).ok()?;
+    if thumb_modified < pdf_modified {
+        return None;
+    }
+
+    std::fs::read(thumb_path).ok().filter(|data| !data.is_empty())
+}
+
+/// Save a rendered thumbnail PNG to the cache
+pub fn save_thumbnail_to_cache(thumb_path: &Path, png_data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    if !caching_enabled() {
+        return Ok(());
+    }
+    if let Some(parent) = thumb_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(thumb_path, png_data)?;
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, RichText, TextStyle, Ui};
+
+use crate::similarity::{DedupeOptions, DedupeReport, DiffLine, SimilarityMethod};
+
+use super::cache::{load_text_from_cache, record_cache_path};
+use super::jobs::{JobHandle, JobKind, JobManager};
+use super::pdf_viewer::PdfViewer;
+use super::tabs::DocumentTabs;
+
+/// Two compared documents and the line diff of their text
+type TextDiff = (PathBuf, PathBuf, Vec<DiffLine>);
+
+/// Duplicate detection panel component
+pub struct DedupePanel {
+    input_paths: Vec<PathBuf>,
+    method: SimilarityMethod,
+    threshold: f32,
+    report: Option<DedupeReport>,
+    report_job: Option<JobHandle<DedupeReport>>,
+    /// Documents picked for comparison, at most two
+    compare: Vec<PathBuf>,
+    diff: Option<TextDiff>,
+    diff_job: Option<JobHandle<TextDiff>>,
+    error_message: Option<String>,
+    jobs: JobManager,
+}
+
+impl DedupePanel {
+    pub fn new(jobs: JobManager) -> Self {
+        Self {
+            input_paths: Vec::new(),
+            method: SimilarityMethod::default(),
+            threshold: DedupeOptions::default().threshold as f32,
+            report: None,
+            report_job: None,
+            compare: Vec::new(),
+            diff: None,
+            diff_job: None,
+            error_message: None,
+            jobs,
+        }
+    }
+
+    /// Show duplicate search options in the sidebar
+    pub fn show_options(&mut self, ui: &mut Ui, pdf_viewer: &PdfViewer) {
+        ui.heading("Duplicate Search");
+
+        ui.label("Input Sources:");
+
+        if let Some(current_pdf) = pdf_viewer.current_pdf() {
+            if ui.button("Add Current Document").clicked() && !self.input_paths.contains(current_pdf) {
+                self.input_paths.push(current_pdf.clone());
+            }
+        }
+
+        if ui.button("Add Directory...").clicked() {
+            if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                if !self.input_paths.contains(&path) {
+                    self.input_paths.push(path);
+                }
+            }
+        }
+
+        if !self.input_paths.is_empty() {
+            ui.group(|ui| {
+                ui.label(RichText::new("Selected Sources:").strong());
+
+                let mut remove = None;
+                for (i, path) in self.input_paths.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let name = if path.is_dir() {
+                            format!("Directory: {}", path.to_string_lossy())
+                        } else {
+                            format!("File: {}", path.file_name().unwrap_or_default().to_string_lossy())
+                        };
+
+                        ui.label(&name);
+
+                        if ui.small_button("✖").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    self.input_paths.remove(i);
+                }
+            });
+        }
+
+        ui.add_space(10.0);
+
+        ui.label("Similarity Method:");
+        egui::ComboBox::from_id_source("dedupe_method")
+            .selected_text(self.method.name())
+            .show_ui(ui, |ui| {
+                for method in SimilarityMethod::ALL {
+                    ui.selectable_value(&mut self.method, method, method.name());
+                }
+            });
+
+        ui.label("Similarity Threshold:");
+        ui.add(egui::Slider::new(&mut self.threshold, 0.5..=1.0).text("threshold"))
+            .on_hover_text("Documents at least this similar are near-duplicates");
+
+        ui.add_space(15.0);
+
+        let button_text = if self.report_job.is_some() { "Searching..." } else { "Find Duplicates" };
+        if ui.add_enabled(self.report_job.is_none() && !self.input_paths.is_empty(), egui::Button::new(button_text)).clicked() {
+            self.find_duplicates();
+        }
+    }
+
+    /// Fingerprint the selected sources in a background thread
+    fn find_duplicates(&mut self) {
+        self.error_message = None;
+
+        let input_paths: Vec<String> = self.input_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let options = DedupeOptions {
+            method: self.method,
+            threshold: self.threshold as f64,
+            ..Default::default()
+        };
+
+        self.report_job = Some(self.jobs.spawn(JobKind::Dedupe, "Find duplicates", move |job| {
+            crate::similarity::dedupe(&input_paths, &options, job.cancel_flag())
+                .map_err(|e| format!("Error finding duplicates: {}", e))
+        }));
+    }
+
+    /// Diff the text of the two documents picked for comparison in a background thread
+    fn compare_documents(&mut self) {
+        let (old, new) = match self.compare.as_slice() {
+            [old, new] => (old.clone(), new.clone()),
+            _ => return,
+        };
+
+        self.error_message = None;
+        let title = format!("Compare {} and {}",
+            old.file_name().unwrap_or_default().to_string_lossy(),
+            new.file_name().unwrap_or_default().to_string_lossy());
+
+        self.diff_job = Some(self.jobs.spawn(JobKind::Diff, title, move |_| {
+            load_text(&old).and_then(|old_text| {
+                load_text(&new).map(|new_text| {
+                    let diff = crate::similarity::diff_lines(&old_text, &new_text);
+                    (old, new, diff)
+                })
+            })
+        }));
+    }
+
+    /// Pick up the results of finished background work
+    fn poll(&mut self) {
+        let cancelled = self.report_job.as_ref().is_some_and(|job| job.is_cancelled());
+        if let Some(result) = self.report_job.as_ref().and_then(|job| job.poll()) {
+            match result {
+                Ok(report) => {
+                    self.report = Some(report);
+                    self.compare.clear();
+                    self.diff = None;
+                },
+                Err(e) if !cancelled => self.error_message = Some(e),
+                Err(_) => {},
+            }
+            self.report_job = None;
+        }
+
+        if let Some(result) = self.diff_job.as_ref().and_then(|job| job.poll()) {
+            match result {
+                Ok(diff) => self.diff = Some(diff),
+                Err(e) => self.error_message = Some(e),
+            }
+            self.diff_job = None;
+        }
+    }
+
+    /// Show the duplicate clusters and the diff in the main content area
+    pub fn show(&mut self, ui: &mut Ui, _ctx: &Context, tabs: &mut DocumentTabs) {
+        self.poll();
+
+        ui.heading("Duplicates");
+
+        let mut clear_error = false;
+        if let Some(error) = &self.error_message {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("⚠ Error:").color(Color32::RED).strong());
+                ui.label(error);
+                clear_error = ui.button("×").clicked();
+            });
+            ui.separator();
+        }
+        if clear_error {
+            self.error_message = None;
+        }
+
+        let Some(report) = &self.report else {
+            if self.report_job.is_some() {
+                ui.label("Fingerprinting documents...");
+            } else {
+                ui.label("Add sources in the sidebar and click Find Duplicates.");
+            }
+            return;
+        };
+
+        ui.label(format!(
+            "{} documents, {} clusters of duplicates ({} similarity ≥ {:.2})",
+            report.total_documents,
+            report.clusters.len(),
+            report.method,
+            report.threshold
+        ));
+        if report.without_text > 0 {
+            ui.label(RichText::new(format!(
+                "{} documents have no extractable text and were compared by their bytes only",
+                report.without_text
+            )).italics());
+        }
+
+        let mut toggle = None;
+        let mut open = None;
+
+        egui::ScrollArea::vertical()
+            .id_source("dedupe_clusters")
+            .max_height(ui.available_height() * if self.diff.is_some() { 0.4 } else { 0.9 })
+            .show(ui, |ui| {
+                for (i, cluster) in report.clusters.iter().enumerate() {
+                    let title = format!(
+                        "Cluster {} – {} documents{}",
+                        i + 1,
+                        cluster.members.len(),
+                        if cluster.is_exact() { " (identical files)" } else { "" }
+                    );
+                    egui::CollapsingHeader::new(title)
+                        .id_source(("dedupe_cluster", i))
+                        .default_open(i < 10)
+                        .show(ui, |ui| {
+                            for (j, member) in cluster.members.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let mut selected = self.compare.contains(&member.path);
+                                    if ui.checkbox(&mut selected, "").on_hover_text("Compare").changed() {
+                                        toggle = Some(member.path.clone());
+                                    }
+
+                                    let name = member.path.file_name().unwrap_or_default().to_string_lossy();
+                                    ui.label(RichText::new(name).strong())
+                                        .on_hover_text(member.path.to_string_lossy());
+
+                                    let note = if j == 0 {
+                                        "reference".to_string()
+                                    } else if member.exact {
+                                        "identical bytes".to_string()
+                                    } else {
+                                        format!("similarity {:.2}", member.similarity)
+                                    };
+                                    ui.label(RichText::new(note).weak());
+
+                                    if ui.small_button("Open").clicked() {
+                                        open = Some(member.path.clone());
+                                    }
+                                });
+                            }
+                        });
+                }
+            });
+
+        if let Some(path) = toggle {
+            if let Some(index) = self.compare.iter().position(|p| *p == path) {
+                self.compare.remove(index);
+            } else {
+                // Keep the two most recently picked documents
+                self.compare.push(path);
+                if self.compare.len() > 2 {
+                    self.compare.remove(0);
+                }
+            }
+        }
+        if let Some(path) = open {
+            tabs.open_result(&path);
+        }
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            let button_text = if self.diff_job.is_some() { "Comparing..." } else { "Compare Selected" };
+            if ui.add_enabled(self.compare.len() == 2 && self.diff_job.is_none(), egui::Button::new(button_text))
+                .on_hover_text("Tick two documents to show the differences in their text")
+                .clicked()
+            {
+                self.compare_documents();
+            }
+            if self.diff.is_some() && ui.button("Close Diff").clicked() {
+                self.diff = None;
+            }
+        });
+
+        if let Some((old, new, diff)) = &self.diff {
+            show_diff(ui, old, new, diff);
+        }
+    }
+}
+
+/// Draw a unified line diff
+fn show_diff(ui: &mut Ui, old: &Path, new: &Path, diff: &[DiffLine]) {
+    let changed = diff.iter().filter(|line| !matches!(line, DiffLine::Same(_))).count();
+    ui.label(RichText::new(format!(
+        "− {}   + {}   ({} changed lines)",
+        old.file_name().unwrap_or_default().to_string_lossy(),
+        new.file_name().unwrap_or_default().to_string_lossy(),
+        changed
+    )).strong());
+
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .id_source("dedupe_diff")
+        .auto_shrink([false, false])
+        .show_rows(ui, row_height, diff.len(), |ui, rows| {
+            for line in &diff[rows] {
+                let text = match line {
+                    DiffLine::Same(text) => RichText::new(format!("  {}", text)),
+                    DiffLine::Removed(text) => RichText::new(format!("- {}", text))
+                        .color(Color32::from_rgb(230, 90, 90)),
+                    DiffLine::Added(text) => RichText::new(format!("+ {}", text))
+                        .color(Color32::from_rgb(90, 190, 90)),
+                };
+                ui.label(text.monospace());
+            }
+        });
+}
+
+/// Text of a document, from the text cache if it has been extracted before
+fn load_text(path: &Path) -> Result<String, String> {
+    match load_text_from_cache(path, &record_cache_path(path)) {
+        Some(text) => Ok(text),
+        None => crate::similarity::document_text(path).map_err(|e| e.to_string()),
+    }
+}
@@ -0,0 +1,396 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use egui::{Align2, Color32, Context, Id, Order, RichText, Ui};
+
+use super::utils::truncate_string;
+
+/// How long a notification stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Number of finished jobs kept in the job list
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// What a background job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    Extraction,
+    DirectoryLoading,
+    DocumentLoading,
+    Rendering,
+    Analysis,
+    Discovery,
+    Clustering,
+    Trends,
+    Dedupe,
+    Diff,
+    Batch,
+}
+
+impl JobKind {
+    /// Icon shown next to the job in the job list
+    pub fn icon(&self) -> &'static str {
+        match self {
+            JobKind::Extraction => "📝",
+            JobKind::DirectoryLoading => "📁",
+            JobKind::DocumentLoading | JobKind::Rendering => "📄",
+            JobKind::Analysis | JobKind::Trends => "📊",
+            JobKind::Discovery | JobKind::Clustering => "🔎",
+            JobKind::Dedupe | JobKind::Diff => "📑",
+            JobKind::Batch => "⚙",
+        }
+    }
+
+    /// Whether the end of the job is announced with a notification. Routine viewer
+    /// work isn't, and isn't kept in the job list once done.
+    pub fn notifies(&self) -> bool {
+        !matches!(self, JobKind::DocumentLoading | JobKind::Rendering)
+    }
+
+    /// Whether the work checks for cancellation, so the job list offers to cancel it.
+    /// Single-document work can't stop part way and runs to the end.
+    pub fn cancellable(&self) -> bool {
+        !matches!(self, JobKind::Extraction | JobKind::DocumentLoading | JobKind::Rendering | JobKind::Diff)
+    }
+}
+
+/// Where a job is in its life
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobState {
+    Running,
+    Finished,
+    Failed(String),
+    /// Cancelled; whatever the job had done by then is still its result
+    Cancelled,
+}
+
+/// Progress last reported by a job
+#[derive(Debug, Clone, Default)]
+pub struct JobProgress {
+    pub done: usize,
+    /// Zero while the amount of work is unknown
+    pub total: usize,
+    /// What the job is working on
+    pub message: String,
+}
+
+impl JobProgress {
+    /// Share of the work done, if the total is known
+    pub fn fraction(&self) -> Option<f32> {
+        (self.total > 0).then(|| self.done as f32 / self.total as f32)
+    }
+}
+
+/// State shared between a job's thread, its handle and the job list
+#[derive(Default)]
+struct JobShared {
+    progress: Mutex<JobProgress>,
+    cancel: AtomicBool,
+}
+
+/// Given to the work of a job to report progress and check for cancellation
+#[derive(Clone)]
+pub struct JobContext {
+    shared: Arc<JobShared>,
+    ctx: Context,
+}
+
+impl JobContext {
+    /// Whether the job should stop; it then returns what it has so far
+    pub fn is_cancelled(&self) -> bool {
+        self.shared.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Cancellation flag, for library functions that check it themselves
+    pub fn cancel_flag(&self) -> &AtomicBool {
+        &self.shared.cancel
+    }
+
+    /// Report how much of the work is done
+    pub fn progress(&self, done: usize, total: usize, message: impl Into<String>) {
+        *self.shared.progress.lock().unwrap() = JobProgress { done, total, message: message.into() };
+        self.ctx.request_repaint();
+    }
+}
+
+/// The starting side's end of a job: its result, progress and cancellation
+pub struct JobHandle<T> {
+    shared: Arc<JobShared>,
+    result: Receiver<Result<T, String>>,
+}
+
+impl<T> JobHandle<T> {
+    /// The result once the job has ended
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("The job ended without a result".to_string())),
+        }
+    }
+
+    pub fn progress(&self) -> JobProgress {
+        self.shared.progress.lock().unwrap().clone()
+    }
+
+    /// Ask the job to stop
+    pub fn cancel(&self) {
+        self.shared.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.shared.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// A job in the job list
+struct Job {
+    id: u64,
+    kind: JobKind,
+    title: String,
+    state: JobState,
+    shared: Arc<JobShared>,
+}
+
+/// A notification about a job that ended
+struct Toast {
+    text: String,
+    failed: bool,
+    shown_at: Instant,
+}
+
+#[derive(Default)]
+struct Jobs {
+    jobs: Vec<Job>,
+    toasts: Vec<Toast>,
+    next_id: u64,
+}
+
+/// Runs background work in threads and keeps the list of jobs for the status bar.
+/// Clones share the same list.
+#[derive(Clone)]
+pub struct JobManager {
+    jobs: Arc<Mutex<Jobs>>,
+    ctx: Context,
+}
+
+impl JobManager {
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(Jobs::default())),
+            ctx: ctx.clone(),
+        }
+    }
+
+    /// The egui context the jobs repaint, for other background work that needs to
+    pub fn context(&self) -> Context {
+        self.ctx.clone()
+    }
+
+    /// Run `work` in a background thread; its result arrives through the returned handle
+    pub fn spawn<T, F>(&self, kind: JobKind, title: impl Into<String>, work: F) -> JobHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&JobContext) -> Result<T, String> + Send + 'static,
+    {
+        let shared = Arc::new(JobShared::default());
+        let id = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let id = jobs.next_id;
+            jobs.next_id += 1;
+            jobs.jobs.push(Job { id, kind, title: title.into(), state: JobState::Running, shared: shared.clone() });
+            id
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let job = JobContext { shared: shared.clone(), ctx: self.ctx.clone() };
+        let manager = self.clone();
+
+        std::thread::spawn(move || {
+            // A panicking job fails rather than staying in the list as running
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| work(&job)))
+                .unwrap_or_else(|_| Err("The job crashed".to_string()));
+            // Work that stops early on cancellation may report it as an error
+            let state = match &result {
+                _ if job.is_cancelled() => JobState::Cancelled,
+                Err(e) => JobState::Failed(e.clone()),
+                Ok(_) => JobState::Finished,
+            };
+
+            // The handle may have been dropped; the job list still records the end
+            let _ = sender.send(result);
+            manager.finish(id, state);
+        });
+
+        JobHandle { shared, result: receiver }
+    }
+
+    /// Record the end of a job and announce it
+    fn finish(&self, id: u64, state: JobState) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(index) = jobs.jobs.iter().position(|job| job.id == id) else {
+            return;
+        };
+
+        let kind = jobs.jobs[index].kind;
+        if !kind.notifies() {
+            jobs.jobs.remove(index);
+            return;
+        }
+
+        let title = jobs.jobs[index].title.clone();
+        let (text, failed) = match &state {
+            JobState::Failed(e) => (format!("{} failed: {}", title, e), true),
+            JobState::Cancelled => (format!("{} cancelled", title), false),
+            _ => (format!("{} finished", title), false),
+        };
+        jobs.toasts.push(Toast { text, failed, shown_at: Instant::now() });
+        jobs.jobs[index].state = state;
+
+        // Forget the oldest finished jobs
+        let finished = jobs.jobs.iter().filter(|job| job.state != JobState::Running).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        jobs.jobs.retain(|job| {
+            let keep = excess == 0 || job.state == JobState::Running;
+            if !keep {
+                excess -= 1;
+            }
+            keep
+        });
+
+        self.ctx.request_repaint();
+    }
+
+    /// Show the running jobs in the status bar, with a menu listing every job
+    pub fn show_status_bar(&self, ui: &mut Ui) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let running: Vec<&Job> = jobs.jobs.iter().filter(|job| job.state == JobState::Running).collect();
+
+        let summary = match running.as_slice() {
+            [] => "No background jobs".to_string(),
+            [job] => {
+                let progress = job.shared.progress.lock().unwrap();
+                match progress.fraction() {
+                    Some(fraction) => format!("{} ({:.0}%)", job.title, fraction * 100.0),
+                    None => job.title.clone(),
+                }
+            },
+            running => format!("{} jobs running", running.len()),
+        };
+        let busy = !running.is_empty();
+
+        let mut clear_finished = false;
+        ui.horizontal(|ui| {
+            if busy {
+                ui.spinner();
+            }
+
+            ui.menu_button(summary, |ui| {
+                if jobs.jobs.is_empty() {
+                    ui.label("No jobs yet");
+                }
+
+                for job in jobs.jobs.iter().rev() {
+                    show_job(ui, job);
+                }
+
+                if jobs.jobs.iter().any(|job| job.state != JobState::Running) {
+                    ui.separator();
+                    clear_finished = ui.button("Clear Finished").clicked();
+                }
+            });
+        });
+
+        if clear_finished {
+            jobs.jobs.retain(|job| job.state == JobState::Running);
+        }
+    }
+
+    /// Show notifications about ended jobs in the bottom-right corner
+    pub fn show_toasts(&self, ctx: &Context) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.toasts.retain(|toast| toast.shown_at.elapsed() < TOAST_DURATION);
+        if jobs.toasts.is_empty() {
+            return;
+        }
+
+        let mut dismiss = None;
+        egui::Area::new(Id::new("job_toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, [-12.0, -36.0])
+            .order(Order::Foreground)
+            .show(ctx, |ui| {
+                for (i, toast) in jobs.toasts.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if toast.failed {
+                                ui.label(RichText::new("⚠").color(Color32::RED));
+                            } else {
+                                ui.label(RichText::new("✔").color(Color32::GREEN));
+                            }
+                            ui.label(truncate_string(&toast.text, 80)).on_hover_text(&toast.text);
+                            if ui.small_button("×").clicked() {
+                                dismiss = Some(i);
+                            }
+                        });
+                    });
+                }
+            });
+
+        if let Some(i) = dismiss {
+            jobs.toasts.remove(i);
+        }
+
+        // Repaint when the oldest toast expires
+        if let Some(oldest) = jobs.toasts.first() {
+            ctx.request_repaint_after(TOAST_DURATION.saturating_sub(oldest.shown_at.elapsed()));
+        }
+    }
+}
+
+/// One row of the job list
+fn show_job(ui: &mut Ui, job: &Job) {
+    ui.horizontal(|ui| {
+        ui.label(job.kind.icon());
+        ui.label(&job.title);
+
+        match &job.state {
+            JobState::Running => {
+                let progress = job.shared.progress.lock().unwrap().clone();
+                match progress.fraction() {
+                    Some(fraction) => {
+                        ui.add(egui::ProgressBar::new(fraction)
+                            .desired_width(120.0)
+                            .text(format!("{}/{}", progress.done, progress.total)));
+                    },
+                    None => {
+                        ui.spinner();
+                    },
+                }
+
+                if job.kind.cancellable() {
+                    if job.shared.cancel.load(Ordering::Relaxed) {
+                        ui.label("Cancelling...");
+                    } else if ui.small_button("Cancel").clicked() {
+                        job.shared.cancel.store(true, Ordering::Relaxed);
+                    }
+                }
+            },
+            JobState::Finished => {
+                ui.label(RichText::new("Done").weak());
+            },
+            JobState::Failed(e) => {
+                ui.label(RichText::new("Failed").color(Color32::RED)).on_hover_text(e);
+            },
+            JobState::Cancelled => {
+                ui.label(RichText::new("Cancelled").weak());
+            },
+        }
+    });
+
+    let progress = job.shared.progress.lock().unwrap();
+    if job.state == JobState::Running && !progress.message.is_empty() {
+        ui.label(RichText::new(truncate_string(&progress.message, 60)).small().weak());
+    }
+}
@@ -0,0 +1,224 @@
+use egui::{Align2, FontId, Sense, Stroke, Ui, Vec2};
+
+use crate::stats::AnalysisReport;
+
+use super::utils::truncate_string;
+
+/// Layout steps run per frame while the graph settles
+const STEPS_PER_FRAME: usize = 5;
+
+/// The layout stops moving below this temperature
+const MIN_TEMPERATURE: f32 = 0.002;
+
+/// Force-directed graph of keywords linked by correlations above the threshold
+pub struct KeywordGraph {
+    /// Keywords and edges the layout was computed for
+    keywords: Vec<String>,
+    edges: Vec<(usize, usize, f64)>,
+    /// Node positions in the unit square
+    positions: Vec<Vec2>,
+    /// Largest step a node may take; cools down as the layout settles
+    temperature: f32,
+    /// Node being dragged with the mouse
+    dragging: Option<usize>,
+}
+
+impl KeywordGraph {
+    pub fn new() -> Self {
+        Self {
+            keywords: Vec::new(),
+            edges: Vec::new(),
+            positions: Vec::new(),
+            temperature: 0.0,
+            dragging: None,
+        }
+    }
+
+    /// Draw the graph of a report, advancing the layout while it settles
+    pub fn show(&mut self, ui: &mut Ui, report: &AnalysisReport) {
+        let edges = edges(report);
+        if self.keywords != report.keywords {
+            self.reset(&report.keywords);
+        }
+        if self.edges != edges {
+            self.edges = edges;
+            self.temperature = 0.1;
+        }
+
+        if self.edges.is_empty() {
+            ui.label("No keyword pairs are correlated above the threshold");
+            return;
+        }
+
+        for _ in 0..STEPS_PER_FRAME {
+            self.step();
+        }
+
+        let size = Vec2::new(ui.available_width().max(200.0), 400.0);
+        let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+        let area = response.rect.shrink(40.0);
+        let to_screen = |p: Vec2| area.min + p * area.size();
+
+        // Node sizes by total occurrences
+        let totals: Vec<usize> = report.keywords
+            .iter()
+            .map(|keyword| report.documents.iter().map(|doc| doc.count(keyword)).sum())
+            .collect();
+        let max_total = totals.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let radius = |i: usize| 6.0 + 10.0 * (totals[i] as f32 / max_total).sqrt();
+
+        let hovered = response.hover_pos().and_then(|pointer| {
+            (0..self.positions.len()).find(|&i| to_screen(self.positions[i]).distance(pointer) <= radius(i) + 3.0)
+        });
+
+        // Dragging a node pins it under the pointer and lets its neighbours follow
+        if response.drag_started() {
+            self.dragging = hovered;
+        }
+        if let (Some(i), Some(pointer)) = (self.dragging, response.interact_pointer_pos()) {
+            if response.dragged() {
+                let p = (pointer - area.min) / area.size();
+                self.positions[i] = Vec2::new(p.x.clamp(0.0, 1.0), p.y.clamp(0.0, 1.0));
+                self.temperature = self.temperature.max(0.02);
+            }
+        }
+        if response.drag_released() {
+            self.dragging = None;
+        }
+
+        let visuals = ui.visuals();
+        let text_color = visuals.text_color();
+        let edge_color = visuals.selection.bg_fill;
+        let max_weight = self.edges.iter().map(|e| e.2).fold(0.0, f64::max).max(f64::EPSILON);
+        let font = FontId::proportional(12.0);
+
+        for &(i, j, weight) in &self.edges {
+            let strength = (weight / max_weight) as f32;
+            let highlighted = hovered == Some(i) || hovered == Some(j);
+            let color = if highlighted { text_color } else { edge_color.gamma_multiply(0.3 + 0.7 * strength) };
+            let (a, b) = (to_screen(self.positions[i]), to_screen(self.positions[j]));
+            painter.line_segment([a, b], Stroke::new(1.0 + 4.0 * strength, color));
+
+            if highlighted || self.edges.len() <= 20 {
+                painter.text(a.lerp(b, 0.5), Align2::CENTER_CENTER, format!("{:.2}", weight), font.clone(), visuals.weak_text_color());
+            }
+        }
+
+        for (i, keyword) in report.keywords.iter().enumerate() {
+            let center = to_screen(self.positions[i]);
+            let connected = self.edges.iter().any(|&(a, b, _)| a == i || b == i);
+            let fill = if hovered == Some(i) {
+                visuals.selection.stroke.color
+            } else if connected {
+                visuals.selection.bg_fill
+            } else {
+                visuals.widgets.inactive.bg_fill
+            };
+            painter.circle(center, radius(i), fill, Stroke::new(1.0, text_color));
+            painter.text(
+                center + Vec2::new(0.0, radius(i) + 2.0),
+                Align2::CENTER_TOP,
+                truncate_string(keyword, 24),
+                font.clone(),
+                text_color,
+            );
+        }
+
+        if let Some(i) = hovered {
+            let documents = report.documents.iter().filter(|doc| doc.count(&report.keywords[i]) > 0).count();
+            let mut links: Vec<(usize, f64)> = self.edges
+                .iter()
+                .filter_map(|&(a, b, weight)| match (a == i, b == i) {
+                    (true, _) => Some((b, weight)),
+                    (_, true) => Some((a, weight)),
+                    _ => None,
+                })
+                .collect();
+            links.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            response.on_hover_ui_at_pointer(|ui| {
+                ui.label(egui::RichText::new(&report.keywords[i]).strong());
+                ui.label(format!("{} occurrences in {} documents", totals[i], documents));
+                for (other, weight) in links.iter().take(10) {
+                    ui.label(format!("{} {:.3}", report.keywords[*other], weight));
+                }
+            });
+        }
+
+        if self.temperature > MIN_TEMPERATURE || self.dragging.is_some() {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Start a new layout with the nodes evenly spaced on a circle
+    fn reset(&mut self, keywords: &[String]) {
+        let n = keywords.len().max(1) as f32;
+        self.keywords = keywords.to_vec();
+        self.positions = (0..keywords.len())
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / n;
+                Vec2::new(0.5 + 0.4 * angle.cos(), 0.5 + 0.4 * angle.sin())
+            })
+            .collect();
+        self.edges.clear();
+        self.temperature = 0.1;
+        self.dragging = None;
+    }
+
+    /// One Fruchterman-Reingold iteration: all nodes repel, linked nodes attract
+    /// in proportion to their correlation
+    fn step(&mut self) {
+        if self.temperature <= MIN_TEMPERATURE && self.dragging.is_none() {
+            return;
+        }
+
+        let n = self.positions.len();
+        let k = (1.0 / n.max(1) as f32).sqrt();
+        let mut displacement = vec![Vec2::ZERO; n];
+
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let delta = self.positions[i] - self.positions[j];
+                let distance = delta.length().max(0.01);
+                let force = delta / distance * (k * k / distance);
+                displacement[i] += force;
+                displacement[j] -= force;
+            }
+        }
+
+        let max_weight = self.edges.iter().map(|e| e.2).fold(0.0, f64::max).max(f64::EPSILON);
+        for &(i, j, weight) in &self.edges {
+            let delta = self.positions[i] - self.positions[j];
+            let distance = delta.length().max(0.01);
+            let force = delta / distance * (distance * distance / k) * (weight / max_weight) as f32;
+            displacement[i] -= force;
+            displacement[j] += force;
+        }
+
+        for (i, (position, displacement)) in self.positions.iter_mut().zip(displacement).enumerate() {
+            if self.dragging == Some(i) {
+                continue;
+            }
+            let length = displacement.length();
+            if length > 0.0 {
+                *position += displacement / length * length.min(self.temperature);
+            }
+            *position = position.clamp(Vec2::ZERO, Vec2::splat(1.0));
+        }
+
+        self.temperature *= 0.95;
+    }
+}
+
+/// Keyword pairs correlated above the report's threshold
+fn edges(report: &AnalysisReport) -> Vec<(usize, usize, f64)> {
+    let mut edges = Vec::new();
+    for (i, row) in report.correlations.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate().skip(i + 1) {
+            if value > 0.0 && value >= report.threshold {
+                edges.push((i, j, value));
+            }
+        }
+    }
+    edges
+}
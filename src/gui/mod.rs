@@ -2,8 +2,18 @@ mod app;
 mod pdf_viewer;
 mod search_panel;
 mod analysis_panel;
+mod dedupe_panel;
+mod batch_dialog;
+mod jobs;
+mod keyword_graph;
+pub mod annotations;
+mod cache;
+mod navigation;
+mod tabs;
+mod trend_chart;
 mod theme;
 mod utils;
 
 pub use app::PdfScanApp;
+pub use jobs::JobManager;
 pub use pdf_viewer::PdfViewer; 
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+/// Maximum number of entries kept in each direction
+const MAX_HISTORY: usize = 100;
+
+/// Maximum number of documents whose reading position is remembered
+const MAX_READING_POSITIONS: usize = 200;
+
+/// Back/forward history of page jumps within a document
+#[derive(Default)]
+pub struct NavigationHistory {
+    back: Vec<usize>,
+    forward: Vec<usize>,
+}
+
+impl NavigationHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a jump away from `from`; clears the forward stack like a browser does
+    pub fn push(&mut self, from: usize) {
+        if self.back.last() != Some(&from) {
+            self.back.push(from);
+            if self.back.len() > MAX_HISTORY {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    /// Go back from `current`, returning the page to show
+    pub fn back(&mut self, current: usize) -> Option<usize> {
+        let page = self.back.pop()?;
+        self.forward.push(current);
+        Some(page)
+    }
+
+    /// Go forward from `current`, returning the page to show
+    pub fn forward(&mut self, current: usize) -> Option<usize> {
+        let page = self.forward.pop()?;
+        self.back.push(current);
+        Some(page)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Forget all history (e.g. when another document is loaded)
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+    }
+}
+
+/// Where the user left off in a document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadingPosition {
+    pub page: usize,
+    pub zoom: f32,
+    /// View mode: fit the page to the window height, or use `zoom`
+    pub auto_fit: bool,
+}
+
+/// File in the pdfscan config directory holding reading positions
+fn reading_positions_path() -> Option<PathBuf> {
+    crate::config::config_dir().map(|dir| dir.join("reading_positions.txt"))
+}
+
+/// Load all remembered reading positions, most recently used first.
+///
+/// Each line is `page<TAB>zoom<TAB>fit|zoom<TAB>path`.
+fn load_reading_positions() -> Result<Vec<(PathBuf, ReadingPosition)>> {
+    let path = match reading_positions_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(Vec::new()),
+    };
+    
+    let content = std::fs::read_to_string(path)?;
+    let positions = content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, '\t');
+            let page = parts.next()?.parse().ok()?;
+            let zoom = parts.next()?.parse().ok()?;
+            let auto_fit = parts.next()? == "fit";
+            let path = PathBuf::from(parts.next()?);
+            Some((path, ReadingPosition { page, zoom, auto_fit }))
+        })
+        .collect();
+    
+    Ok(positions)
+}
+
+/// Look up the reading position remembered for a document
+pub fn reading_position(pdf_path: &Path) -> Option<ReadingPosition> {
+    load_reading_positions()
+        .ok()?
+        .into_iter()
+        .find(|(path, _)| path == pdf_path)
+        .map(|(_, position)| position)
+}
+
+/// Remember the reading position for a document
+pub fn save_reading_position(pdf_path: &Path, position: ReadingPosition) -> Result<()> {
+    let path = match reading_positions_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    
+    let mut positions = load_reading_positions().unwrap_or_default();
+    positions.retain(|(p, _)| p != pdf_path);
+    positions.insert(0, (pdf_path.to_path_buf(), position));
+    positions.truncate(MAX_READING_POSITIONS);
+    
+    let content: String = positions
+        .iter()
+        .map(|(p, pos)| format!(
+            "{}\t{}\t{}\t{}",
+            pos.page,
+            pos.zoom,
+            if pos.auto_fit { "fit" } else { "zoom" },
+            p.to_string_lossy()
+        ))
+        .collect::<Vec<String>>()
+        .join("\n");
+    
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    
+    Ok(())
+}
@@ -1,13 +1,25 @@
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::process::Command;
 use std::io::Read;
 use std::fs;
-use egui::{Context, Ui, Vec2, RichText, Color32, TextureHandle};
+use egui::{Context, Ui, Vec2, RichText, Color32, TextureHandle, Pos2, Rect, Sense};
 use lopdf::Document;
 use image::{ImageBuffer, Rgba, DynamicImage};
 
+use super::annotations::{self, Annotation, AnnotationTool, DocumentAnnotations, LinkTarget, PendingNote};
+use super::navigation::{self, NavigationHistory, ReadingPosition};
+use super::cache;
+use crate::config;
+use super::jobs::{JobKind, JobManager};
+
+/// Maximum number of thumbnails rendered at the same time
+const MAX_THUMBNAIL_JOBS: usize = 3;
+
+/// Rendered RGBA pixels and size per page, waiting to be loaded as textures
+type RenderedImages = Arc<Mutex<HashMap<usize, (Vec<u8>, (u32, u32))>>>;
+
 /// PDF viewer component that displays PDFs with rendering support
 pub struct PdfViewer {
     current_pdf_path: Option<PathBuf>,
@@ -25,11 +37,46 @@ pub struct PdfViewer {
     auto_fit: bool, // Auto-fit to screen height
     rendering_pages: Arc<Mutex<Vec<usize>>>, // Pages currently being rendered
     use_poppler: bool, // Whether poppler is available
-    rendered_images: Arc<Mutex<HashMap<usize, (Vec<u8>, (u32, u32))>>>, // Rendered images waiting to be loaded as textures
+    rendered_images: RenderedImages, // Rendered images waiting to be loaded as textures
     search_query: String, // Search query for current document
     search_results: Vec<(usize, usize)>, // (page_num, position) for search matches
     current_match_index: Option<usize>, // Current match being viewed
     case_sensitive: bool, // Case sensitive search
+    annotations: DocumentAnnotations, // Annotations parsed from /Annots plus unsaved ones
+    show_annotations: bool, // Draw the annotation overlay
+    annotation_tool: AnnotationTool, // Active authoring tool
+    annotation_drag: Option<(Pos2, Pos2)>, // Start and current screen position of a highlight drag
+    ink_stroke: Vec<(f32, f32)>, // Ink stroke being drawn, in PDF coordinates
+    pending_note: Option<PendingNote>, // Note waiting for its text
+    annotation_status: Option<String>, // Result of the last save
+    history: NavigationHistory, // Back/forward stack of page jumps
+    pending_uri: Option<String>, // External link waiting for confirmation
+    restore_position: Option<ReadingPosition>, // Remembered position to apply once the page count is known
+    last_saved_position: Option<ReadingPosition>, // Last reading position written to the config dir
+    sidebar_view: SidebarView, // What the Viewer sidebar shows
+    thumbnail_textures: HashMap<usize, TextureHandle>, // Loaded page thumbnails
+    thumbnail_images: RenderedImages, // Rendered thumbnails waiting to be loaded as textures
+    thumbnail_jobs: Arc<Mutex<Vec<usize>>>, // Pages whose thumbnails are being rendered
+    failed_thumbnails: Arc<Mutex<HashSet<usize>>>, // Pages whose thumbnails could not be rendered
+    thumbnail_rows: std::ops::Range<usize>, // Thumbnails visible in the last frame
+    thumbnail_page: usize, // Current page when the thumbnail strip was last drawn
+    pending_jump: Option<(JumpTarget, String)>, // Jump and search term to apply once the document is loaded
+    jobs: JobManager, // Runs document loading and page rendering
+}
+
+/// Where a jump requested before the document finished loading should go
+enum JumpTarget {
+    /// Byte position within the document text
+    TextPosition(usize),
+    /// Page number (0-based)
+    Page(usize),
+}
+
+/// Content of the Viewer sidebar
+#[derive(PartialEq, Clone, Copy)]
+enum SidebarView {
+    Thumbnails,
+    Outline,
 }
 
 /// Page data
@@ -47,7 +94,7 @@ struct OutlineItem {
 }
 
 impl PdfViewer {
-    pub fn new() -> Self {
+    pub fn new(jobs: JobManager) -> Self {
         // Check if pdftocairo or pdftoppm is available
         let use_poppler = Command::new("pdftocairo")
             .arg("-v")
@@ -83,13 +130,43 @@ impl PdfViewer {
             search_results: Vec::new(),
             current_match_index: None,
             case_sensitive: false,
+            annotations: DocumentAnnotations::default(),
+            show_annotations: true,
+            annotation_tool: AnnotationTool::Browse,
+            annotation_drag: None,
+            ink_stroke: Vec::new(),
+            pending_note: None,
+            annotation_status: None,
+            history: NavigationHistory::new(),
+            pending_uri: None,
+            restore_position: None,
+            last_saved_position: None,
+            sidebar_view: SidebarView::Thumbnails,
+            thumbnail_textures: HashMap::new(),
+            thumbnail_images: Arc::new(Mutex::new(HashMap::new())),
+            thumbnail_jobs: Arc::new(Mutex::new(Vec::new())),
+            failed_thumbnails: Arc::new(Mutex::new(HashSet::new())),
+            thumbnail_rows: 0..0,
+            thumbnail_page: 0,
+            pending_jump: None,
+            jobs,
         }
     }
     
     /// Load a PDF file
     pub fn load_pdf(&mut self, path: &Path) {
+        // Remember where we were in the previous document
+        self.remember_reading_position();
+        
         self.loading = true;
         self.current_pdf_path = Some(path.to_path_buf());
+        self.restore_position = navigation::reading_position(path);
+        self.last_saved_position = None;
+        
+        // Fresh buffers so a slower load of the previous document can't overwrite this one
+        self.text_data = Arc::new(Mutex::new(String::new()));
+        self.document_loaded = Arc::new(Mutex::new(None));
+        self.pending_jump = None;
         
         // Create a clone for the async task
         let path_clone = path.to_path_buf();
@@ -101,11 +178,26 @@ impl PdfViewer {
         self.current_page = 0;
         self.total_pages = 0;
         self.pages.clear();
+        self.outline.clear();
         self.page_textures.clear();
         self.document_title = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        self.annotations = DocumentAnnotations::default();
+        self.annotation_drag = None;
+        self.ink_stroke.clear();
+        self.pending_note = None;
+        self.annotation_status = None;
+        self.history.clear();
+        self.pending_uri = None;
         
-        // Load the PDF in a separate thread
-        std::thread::spawn(move || {
+        // Fresh buffers so thumbnails still rendering for the previous document are dropped
+        self.thumbnail_textures.clear();
+        self.thumbnail_images = Arc::new(Mutex::new(HashMap::new()));
+        self.thumbnail_jobs = Arc::new(Mutex::new(Vec::new()));
+        self.failed_thumbnails = Arc::new(Mutex::new(HashSet::new()));
+        self.thumbnail_rows = 0..0;
+        
+        // Load the PDF in the background
+        self.jobs.spawn(JobKind::DocumentLoading, format!("Open {}", self.document_title), move |_| {
             // Load with lopdf for structure parsing
             let lopdf_result = Document::load(&path_clone);
             
@@ -128,14 +220,17 @@ impl PdfViewer {
             } else {
                 eprintln!("Error loading PDF with lopdf (will try pdfinfo for page count)");
             }
+            
+            Ok(())
         });
     }
     
     /// Process loaded document (should be called from the UI thread)
     fn process_loaded_document(&mut self, ctx: &Context) {
-        if self.loading {
+        // The lopdf document can arrive after loading has finished with a poppler page count
+        if self.loading || (self.document.is_none() && self.current_pdf_path.is_some()) {
             // First, try to get page count from poppler (most reliable)
-            if self.total_pages == 0 {
+            if self.loading && self.total_pages == 0 {
                 if let Some(pdf_path) = &self.current_pdf_path {
                     let page_count = get_pdf_page_count(pdf_path);
                     if page_count > 0 {
@@ -154,6 +249,8 @@ impl PdfViewer {
             if let Some(doc) = doc_option {
                 // Update state with the loaded document
                 self.document = Some(doc.clone());
+                self.annotations = annotations::load_annotations(&doc);
+                self.outline = load_outline(&doc);
                 
                 // Get page count from lopdf (use if we don't have one from poppler)
                 let lopdf_pages = doc.get_pages().len();
@@ -164,13 +261,16 @@ impl PdfViewer {
                     eprintln!("Page count mismatch: lopdf={}, using poppler={}", lopdf_pages, self.total_pages);
                 }
                 
-                // Render first page if poppler is available
+                // Continue where the user left off last time
+                self.apply_restored_position();
+                
+                // Render the page if poppler is available
                 if self.use_poppler && self.total_pages > 0 {
-                    self.render_page(0, ctx);
+                    self.render_page(self.current_page, ctx);
                 }
                 
-                // Load first page text
-                self.extract_page_text(0);
+                // Load the page text
+                self.extract_page_text(self.current_page);
             }
             
             // If we got here and still loading, check if we have at least text data
@@ -194,6 +294,7 @@ impl PdfViewer {
                 // Mark as not loading if we have some data
                 if self.total_pages > 0 || has_text {
                     self.loading = false;
+                    self.apply_restored_position();
                 }
             }
         }
@@ -237,170 +338,23 @@ impl PdfViewer {
         let rendered_images_clone = self.rendered_images.clone();
         let rendering_pages_clone = self.rendering_pages.clone();
         
-        // Render in background thread
-        std::thread::spawn(move || {
-            // Use pdftocairo for better PNG output (faster and more reliable)
-            // Lower DPI for faster rendering (can be increased for better quality)
-            let dpi = 120; // Balanced quality/speed
-            let temp_dir = std::env::temp_dir();
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            
-            // pdftocairo with -singlefile expects base name without extension, adds .png
-            let temp_base = temp_dir.join(format!("pdfscan_page_{}_{}", page_num_clone, timestamp));
-            let temp_file_png = temp_base.with_extension("png");
-            
-            // Try pdftocairo first (better for PNG)
-            let result = Command::new("pdftocairo")
-                .arg("-png")
-                .arg("-singlefile")
-                .arg("-r")
-                .arg(dpi.to_string())
-                .arg("-f")
-                .arg((page_num_clone + 1).to_string())
-                .arg("-l")
-                .arg((page_num_clone + 1).to_string())
-                .arg(&pdf_path)
-                .arg(&temp_base)  // Base name without extension
-                .output();
-            
-            let success = match result {
-                Ok(output) if output.status.success() => {
-                    // pdftocairo creates file with .png extension
-                    match fs::read(&temp_file_png) {
-                        Ok(png_data) if !png_data.is_empty() => {
-                            match image::load_from_memory(&png_data) {
-                                Ok(img) => {
-                                    let rgba = img.to_rgba8();
-                                    let width = rgba.width();
-                                    let height = rgba.height();
-                                    let pixels = rgba.into_raw();
-                                    
-                                    eprintln!("Successfully rendered page {} with pdftocairo: {}x{} pixels", 
-                                        page_num_clone, width, height);
-                                    
-                                    // Store rendered image for main thread to load as texture
-                                    let mut rendered = rendered_images_clone.lock().unwrap();
-                                    rendered.insert(page_num_clone, (pixels, (width, height)));
-                                    
-                                    // Request repaint to load texture
-                                    ctx_clone.request_repaint();
-                                    
-                                    // Clean up temp file
-                                    let _ = fs::remove_file(&temp_file_png);
-                                    true
-                                },
-                                Err(e) => {
-                                    eprintln!("Failed to parse PNG: {}", e);
-                                    false
-                                }
-                            }
-                        },
-                        Ok(_) => {
-                            eprintln!("PNG file is empty");
-                            false
-                        },
-                        Err(e) => {
-                            eprintln!("Failed to read temp PNG file {:?}: {}", temp_file_png, e);
-                            false
-                        }
-                    }
-                },
-                Ok(output) => {
-                    // pdftocairo failed, try pdftoppm as fallback
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    eprintln!("pdftocairo failed: {}", stderr);
-                    false
-                },
-                Err(e) => {
-                    // pdftocairo not available, try pdftoppm
-                    eprintln!("pdftocairo command failed: {}", e);
-                    false
-                }
-            };
-            
-            // Fallback to pdftoppm if pdftocairo failed
-            if !success {
-                // pdftoppm needs a base name (without extension) and adds page numbers like "base-1.png"
-                let temp_base_ppm = temp_dir.join(format!("pdfscan_ppm_{}_{}", page_num_clone, timestamp));
-                
-                let output = Command::new("pdftoppm")
-                    .arg("-png")
-                    .arg("-r")
-                    .arg(dpi.to_string())
-                    .arg("-f")
-                    .arg((page_num_clone + 1).to_string())
-                    .arg("-l")
-                    .arg((page_num_clone + 1).to_string())
-                    .arg(&pdf_path)
-                    .arg(&temp_base_ppm)  // Base name, pdftoppm will add "-1.png"
-                    .output();
+        // Render in the background
+        let title = format!("Render page {} of {}", page_num + 1, self.document_title);
+        self.jobs.spawn(JobKind::Rendering, title, move |_| {
+            if let Some(image) = render_page_png(&pdf_path, page_num_clone, config::get().viewer.dpi).and_then(|png| decode_png(&png)) {
+                // Store rendered image for main thread to load as texture
+                let mut rendered = rendered_images_clone.lock().unwrap();
+                rendered.insert(page_num_clone, image);
                 
-                match output {
-                    Ok(output) if output.status.success() => {
-                        // pdftoppm outputs files like "base-1.png", "base-2.png", etc.
-                        let actual_file = temp_base_ppm.parent().unwrap()
-                            .join(format!("{}-{}.png", 
-                                temp_base_ppm.file_name().unwrap().to_string_lossy(),
-                                page_num_clone + 1));
-                        
-                        eprintln!("Looking for pdftoppm output file: {:?}", actual_file);
-                        
-                        match fs::read(&actual_file) {
-                            Ok(png_data) if !png_data.is_empty() => {
-                                match image::load_from_memory(&png_data) {
-                                    Ok(img) => {
-                                        let rgba = img.to_rgba8();
-                                        let width = rgba.width();
-                                        let height = rgba.height();
-                                        let pixels = rgba.into_raw();
-                                        
-                                        eprintln!("Successfully rendered page {} with pdftoppm: {}x{} pixels", 
-                                            page_num_clone, width, height);
-                                        
-                                        let mut rendered = rendered_images_clone.lock().unwrap();
-                                        rendered.insert(page_num_clone, (pixels, (width, height)));
-                                        ctx_clone.request_repaint();
-                                        
-                                        // Clean up temp file
-                                        let _ = fs::remove_file(&actual_file);
-                                    },
-                                    Err(e) => {
-                                        eprintln!("Failed to parse PNG from pdftoppm: {}", e);
-                                    }
-                                }
-                            },
-                            Ok(_) => {
-                                eprintln!("pdftoppm output file is empty: {:?}", actual_file);
-                            },
-                            Err(e) => {
-                                eprintln!("Failed to read pdftoppm output file {:?}: {}", actual_file, e);
-                                // List what files actually exist
-                                if let Ok(entries) = fs::read_dir(temp_base_ppm.parent().unwrap()) {
-                                    eprintln!("Files in temp dir:");
-                                    for entry in entries.flatten() {
-                                        if entry.path().to_string_lossy().contains("pdfscan") {
-                                            eprintln!("  {:?}", entry.path());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    },
-                    Ok(output) => {
-                        eprintln!("pdftoppm failed: {}", String::from_utf8_lossy(&output.stderr));
-                    },
-                    Err(e) => {
-                        eprintln!("Failed to run pdftoppm: {}", e);
-                    }
-                }
+                // Request repaint to load texture
+                ctx_clone.request_repaint();
             }
             
             // Remove from rendering list
             let mut rendering = rendering_pages_clone.lock().unwrap();
             rendering.retain(|&x| x != page_num_clone);
+            
+            Ok(())
         });
     }
     
@@ -444,6 +398,72 @@ impl PdfViewer {
         }
     }
     
+    /// Start rendering a page thumbnail in the background if it isn't loaded yet
+    fn request_thumbnail(&mut self, page_num: usize, ctx: &Context) {
+        if !self.use_poppler
+            || self.thumbnail_textures.contains_key(&page_num)
+            || self.failed_thumbnails.lock().unwrap().contains(&page_num)
+        {
+            return;
+        }
+        
+        let pdf_path = match &self.current_pdf_path {
+            Some(p) => p.clone(),
+            None => return,
+        };
+        
+        {
+            let mut jobs = self.thumbnail_jobs.lock().unwrap();
+            if jobs.contains(&page_num) || jobs.len() >= MAX_THUMBNAIL_JOBS {
+                return;
+            }
+            jobs.push(page_num);
+        }
+        
+        let ctx_clone = ctx.clone();
+        let thumbnail_images = self.thumbnail_images.clone();
+        let thumbnail_jobs = self.thumbnail_jobs.clone();
+        let failed_thumbnails = self.failed_thumbnails.clone();
+        
+        std::thread::spawn(move || {
+            match load_or_render_thumbnail(&pdf_path, page_num) {
+                Some(image) => {
+                    thumbnail_images.lock().unwrap().insert(page_num, image);
+                },
+                // Not retried until the document is reloaded
+                None => {
+                    failed_thumbnails.lock().unwrap().insert(page_num);
+                },
+            }
+            
+            thumbnail_jobs.lock().unwrap().retain(|&x| x != page_num);
+            ctx_clone.request_repaint();
+        });
+    }
+    
+    /// Load rendered thumbnails as textures (called from main thread)
+    fn load_thumbnail_textures(&mut self, ctx: &Context) {
+        let rendered: Vec<_> = self.thumbnail_images
+            .lock()
+            .unwrap()
+            .drain()
+            .collect();
+        
+        for (page_num, (pixels, (width, height))) in rendered {
+            if pixels.len() != (width as usize * height as usize * 4) {
+                continue;
+            }
+            
+            let color_image = egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels);
+            let texture = ctx.load_texture(
+                format!("pdf_thumbnail_{}", page_num),
+                color_image,
+                egui::TextureOptions::LINEAR
+            );
+            self.thumbnail_textures.insert(page_num, texture);
+        }
+    }
+    
     /// Extract text from a specific page
     fn extract_page_text(&mut self, page_num: usize) {
         if self.pages.contains_key(&page_num) {
@@ -488,6 +508,11 @@ impl PdfViewer {
         });
     }
     
+    /// Get the document title (file name)
+    pub fn title(&self) -> &str {
+        &self.document_title
+    }
+    
     /// Get the current PDF path
     pub fn current_pdf(&self) -> Option<&PathBuf> {
         self.current_pdf_path.as_ref()
@@ -518,8 +543,61 @@ impl PdfViewer {
         }
     }
     
-    /// Jump to a specific page and optionally highlight a search term
+    /// Jump to a specific page and optionally highlight a search term.
+    ///
+    /// The page being left is recorded in the back/forward history.
     pub fn jump_to_page(&mut self, page_num: usize, search_term: Option<&str>, ctx: &Context) {
+        if let Some(term) = search_term {
+            if !term.is_empty() && term != self.search_query {
+                self.search_query = term.to_string();
+                self.find_matches();
+            }
+        }
+        
+        if page_num < self.total_pages && page_num != self.current_page {
+            self.history.push(self.current_page);
+        }
+        self.set_current_page(page_num, ctx);
+    }
+    
+    /// Jump to the page containing a byte position of the document text, once the text is available
+    pub fn jump_to_text_position(&mut self, position: usize, search_term: &str, ctx: &Context) {
+        self.pending_jump = Some((JumpTarget::TextPosition(position), search_term.to_string()));
+        self.apply_pending_jump(ctx);
+    }
+    
+    /// Jump to a page once the document has loaded
+    pub fn jump_to_page_when_loaded(&mut self, page_num: usize, search_term: &str, ctx: &Context) {
+        self.pending_jump = Some((JumpTarget::Page(page_num), search_term.to_string()));
+        self.apply_pending_jump(ctx);
+    }
+    
+    /// Perform a jump requested before the document finished loading
+    fn apply_pending_jump(&mut self, ctx: &Context) {
+        if self.pending_jump.is_none() || self.loading || self.total_pages == 0 {
+            return;
+        }
+        
+        let text_len = self.text_data.lock().unwrap().len();
+        if text_len == 0 {
+            return;
+        }
+        
+        if let Some((target, term)) = self.pending_jump.take() {
+            let page = match target {
+                JumpTarget::TextPosition(position) => {
+                    // Approximate the page from the position within the text
+                    let position_ratio = position as f32 / text_len as f32;
+                    (position_ratio * self.total_pages as f32).floor() as usize
+                },
+                JumpTarget::Page(page) => page,
+            };
+            self.jump_to_page(page.min(self.total_pages - 1), Some(&term), ctx);
+        }
+    }
+    
+    /// Show a page without touching the navigation history
+    fn set_current_page(&mut self, page_num: usize, ctx: &Context) {
         if page_num < self.total_pages {
             self.current_page = page_num;
             
@@ -538,10 +616,72 @@ impl PdfViewer {
         }
     }
     
+    /// Go back to the page shown before the last jump
+    pub fn go_back(&mut self, ctx: &Context) {
+        if let Some(page) = self.history.back(self.current_page) {
+            self.set_current_page(page, ctx);
+        }
+    }
+    
+    /// Redo a jump undone with `go_back`
+    pub fn go_forward(&mut self, ctx: &Context) {
+        if let Some(page) = self.history.forward(self.current_page) {
+            self.set_current_page(page, ctx);
+        }
+    }
+    
+    /// Apply the remembered reading position once the page count is known
+    fn apply_restored_position(&mut self) {
+        if let Some(position) = self.restore_position.take() {
+            if position.page < self.total_pages {
+                self.current_page = position.page;
+            }
+            self.zoom = position.zoom.clamp(0.1, 3.0);
+            self.auto_fit = position.auto_fit;
+        }
+        self.last_saved_position = self.current_reading_position();
+    }
+    
+    /// Current page, zoom and view mode
+    fn current_reading_position(&self) -> Option<ReadingPosition> {
+        if self.current_pdf_path.is_none() || self.total_pages == 0 {
+            return None;
+        }
+        
+        Some(ReadingPosition {
+            page: self.current_page,
+            zoom: self.zoom,
+            auto_fit: self.auto_fit,
+        })
+    }
+    
+    /// Persist the reading position if it changed since it was last saved
+    pub fn remember_reading_position(&mut self) {
+        // Don't overwrite the stored position before it has been applied
+        if self.restore_position.is_some() {
+            return;
+        }
+        
+        let position = match self.current_reading_position() {
+            Some(position) => position,
+            None => return,
+        };
+        
+        if self.last_saved_position != Some(position) {
+            if let Some(path) = &self.current_pdf_path {
+                if let Err(e) = navigation::save_reading_position(path, position) {
+                    eprintln!("Failed to save reading position: {}", e);
+                }
+            }
+            self.last_saved_position = Some(position);
+        }
+    }
+    
     /// Show the PDF viewer
     pub fn show(&mut self, ui: &mut Ui, ctx: &Context) {
         // Process any loaded document
         self.process_loaded_document(ctx);
+        self.apply_pending_jump(ctx);
         
         // Load any rendered textures
         self.load_rendered_textures(ctx);
@@ -551,7 +691,16 @@ impl PdfViewer {
             let input = ctx.input(|i| i.clone());
             let mut changed_page = false;
             
-            if input.key_pressed(egui::Key::ArrowLeft) {
+            // Alt+Left/Right and the mouse back/forward buttons walk the jump history
+            if (input.modifiers.alt && input.key_pressed(egui::Key::ArrowLeft))
+                || input.pointer.button_pressed(egui::PointerButton::Extra1)
+            {
+                self.go_back(ctx);
+            } else if (input.modifiers.alt && input.key_pressed(egui::Key::ArrowRight))
+                || input.pointer.button_pressed(egui::PointerButton::Extra2)
+            {
+                self.go_forward(ctx);
+            } else if input.key_pressed(egui::Key::ArrowLeft) {
                 // Previous page
                 if self.current_page > 0 {
                     self.current_page = self.current_page.saturating_sub(1);
@@ -621,6 +770,20 @@ impl PdfViewer {
                     
                     // Navigation controls
                     ui.horizontal(|ui| {
+                        // History navigation
+                        if ui.add_enabled(self.history.can_go_back(), egui::Button::new("⏴"))
+                            .on_hover_text("Back (Alt+←)")
+                            .clicked()
+                        {
+                            self.go_back(ctx);
+                        }
+                        if ui.add_enabled(self.history.can_go_forward(), egui::Button::new("⏵"))
+                            .on_hover_text("Forward (Alt+→)")
+                            .clicked()
+                        {
+                            self.go_forward(ctx);
+                        }
+                        
                         // Page navigation
                         if ui.add_enabled(self.current_page > 0, egui::Button::new("◀ Previous")).clicked() {
                             self.current_page = self.current_page.saturating_sub(1);
@@ -718,6 +881,9 @@ impl PdfViewer {
                             ui.label(RichText::new("📝 Text-only").small().weak());
                         }
                     });
+                    
+                    // Annotation tools
+                    self.annotation_toolbar(ui);
                 });
             
             // Main content area for the PDF
@@ -753,7 +919,7 @@ impl PdfViewer {
                                 .id_source("pdf_image")
                                 .show(ui, |ui| {
                                     // Try to show rendered page first
-                                    if let Some(texture) = self.page_textures.get(&self.current_page) {
+                                    if let Some(texture) = self.page_textures.get(&self.current_page).cloned() {
                                         // Show rendered page image
                                         let texture_size = texture.size_vec2();
                                         
@@ -769,11 +935,16 @@ impl PdfViewer {
                                                 texture_size * self.zoom
                                             };
                                             
-                                            ui.vertical_centered(|ui| {
-                                                let image = egui::Image::new(texture)
-                                                    .fit_to_exact_size(display_size);
-                                                ui.add(image);
-                                            });
+                                            let response = ui.vertical_centered(|ui| {
+                                                let image = egui::Image::new(&texture)
+                                                    .fit_to_exact_size(display_size)
+                                                    .sense(Sense::click_and_drag());
+                                                ui.add(image)
+                                            }).inner;
+                                            
+                                            // Annotation authoring and overlay
+                                            self.handle_annotation_input(&response);
+                                            self.paint_page_overlay(ui, &response);
                                         } else {
                                             ui.vertical_centered(|ui| {
                                                 ui.label("Invalid texture size");
@@ -878,10 +1049,146 @@ impl PdfViewer {
                 });
             }
         });
+        
+        // Text entry for a sticky note placed with the note tool
+        self.show_pending_note(ctx);
+        
+        // Confirmation before handing a link to the system
+        self.show_pending_uri(ctx);
+        
+        // Save the reading position when the page changes; zoom and view mode changes
+        // are saved with it, or when the tab is closed or the app exits
+        if self.last_saved_position.is_some_and(|position| position.page != self.current_page) {
+            self.remember_reading_position();
+        }
+    }
+    
+    /// Show the Viewer sidebar: page thumbnails or the outline and annotations
+    pub fn show_sidebar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.sidebar_view, SidebarView::Thumbnails, "🖼 Pages");
+            ui.selectable_value(&mut self.sidebar_view, SidebarView::Outline, "☰ Outline");
+        });
+        ui.separator();
+        
+        match self.sidebar_view {
+            SidebarView::Thumbnails => self.show_thumbnails(ui),
+            SidebarView::Outline => {
+                self.show_outline(ui);
+                self.show_annotation_list(ui);
+            },
+        }
+    }
+    
+    /// Show a strip of page thumbnails, rendering only the visible ones
+    fn show_thumbnails(&mut self, ui: &mut Ui) {
+        if self.total_pages == 0 {
+            ui.label(if self.loading { "Loading..." } else { "No document open" });
+            return;
+        }
+        
+        let ctx = ui.ctx().clone();
+        self.load_thumbnail_textures(&ctx);
+        
+        // Match counts per page for the current in-document search
+        let mut hits: HashMap<usize, usize> = HashMap::new();
+        for (page, _) in &self.search_results {
+            *hits.entry(*page).or_insert(0) += 1;
+        }
+        let failed = self.failed_thumbnails.lock().unwrap().clone();
+        
+        let thumb_height = 140.0;
+        let row_height = thumb_height + ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 2.0;
+        let mut jump_to = None;
+        let mut visible = 0..0;
+        
+        let mut scroll_area = egui::ScrollArea::vertical()
+            .id_source("page_thumbnails")
+            .auto_shrink([false, false]);
+        
+        // Keep the current page in view when it changes from elsewhere
+        if self.thumbnail_page != self.current_page && !self.thumbnail_rows.contains(&self.current_page) {
+            scroll_area = scroll_area.vertical_scroll_offset(self.current_page as f32 * row_height);
+        }
+        
+        scroll_area.show_rows(ui, row_height, self.total_pages, |ui, rows| {
+            visible = rows.clone();
+            for page in rows {
+                let is_current = page == self.current_page;
+                let thumb_width = (ui.available_width() - 8.0).max(40.0);
+                
+                ui.vertical_centered(|ui| {
+                    let response = match self.thumbnail_textures.get(&page) {
+                        Some(texture) => {
+                            let size = texture.size_vec2();
+                            let scale = (thumb_height / size.y).min(thumb_width / size.x);
+                            ui.add(egui::Image::new(texture).fit_to_exact_size(size * scale).sense(Sense::click()))
+                        },
+                        None => {
+                            let (rect, response) = ui.allocate_exact_size(
+                                Vec2::new(thumb_height * 0.77, thumb_height),
+                                Sense::click(),
+                            );
+                            ui.painter().rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+                            let (placeholder, size) = if failed.contains(&page) {
+                                ("No preview".to_string(), 11.0)
+                            } else {
+                                ((page + 1).to_string(), 18.0)
+                            };
+                            ui.painter().text(
+                                rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                placeholder,
+                                egui::FontId::proportional(size),
+                                ui.visuals().weak_text_color(),
+                            );
+                            response
+                        },
+                    };
+                    
+                    // Frame the current page and mark pages with search hits
+                    if is_current {
+                        ui.painter().rect_stroke(response.rect.expand(2.0), 2.0, egui::Stroke::new(2.0, ui.visuals().selection.bg_fill));
+                    }
+                    if let Some(count) = hits.get(&page) {
+                        let badge = Rect::from_min_size(response.rect.right_top() + Vec2::new(-22.0, 2.0), Vec2::new(20.0, 14.0));
+                        ui.painter().rect_filled(badge, 3.0, Color32::from_rgb(255, 165, 0));
+                        ui.painter().text(
+                            badge.center(),
+                            egui::Align2::CENTER_CENTER,
+                            count.to_string(),
+                            egui::FontId::proportional(10.0),
+                            Color32::BLACK,
+                        );
+                    }
+                    
+                    let label = if is_current {
+                        RichText::new(format!("Page {}", page + 1)).strong()
+                    } else {
+                        RichText::new(format!("Page {}", page + 1))
+                    };
+                    let label_response = ui.selectable_label(is_current, label);
+                    
+                    if response.clicked() || label_response.clicked() {
+                        jump_to = Some(page);
+                    }
+                });
+            }
+        });
+        
+        for page in visible.clone() {
+            self.request_thumbnail(page, &ctx);
+        }
+        self.thumbnail_rows = visible;
+        self.thumbnail_page = self.current_page;
+        
+        if let Some(page) = jump_to {
+            self.jump_to_page(page, None, &ctx);
+        }
     }
     
     /// Show the document outline in the sidebar
-    pub fn show_outline(&self, ui: &mut Ui) {
+    fn show_outline(&mut self, ui: &mut Ui) {
         if self.outline.is_empty() {
             ui.label("No outline available");
             return;
@@ -889,13 +1196,344 @@ impl PdfViewer {
         
         ui.heading("Document Outline");
         
+        let mut jump_to = None;
         for item in &self.outline {
-            self.show_outline_item(ui, item);
+            self.show_outline_item(ui, item, &mut jump_to);
+        }
+        
+        if let Some(page) = jump_to {
+            let ctx = ui.ctx().clone();
+            self.jump_to_page(page, None, &ctx);
+        }
+    }
+    
+    /// Show the list of annotations in the sidebar
+    fn show_annotation_list(&mut self, ui: &mut Ui) {
+        if self.annotations.is_empty() {
+            return;
+        }
+        
+        ui.separator();
+        ui.heading(format!("Annotations ({})", self.annotations.len()));
+        
+        let mut pages: Vec<usize> = self.annotations.by_page.keys().copied().collect();
+        pages.sort();
+        
+        let mut jump_to = None;
+        egui::ScrollArea::vertical()
+            .id_source("annotation_list")
+            .max_height(250.0)
+            .show(ui, |ui| {
+                for page in pages {
+                    for annotation in self.annotations.page(page) {
+                        let mut text = format!("p.{} {}", page + 1, annotation.kind.label());
+                        if !annotation.contents.is_empty() {
+                            text.push_str(": ");
+                            text.push_str(&super::utils::truncate_string(&annotation.contents, 40));
+                        }
+                        if annotation.unsaved {
+                            text.push_str(" *");
+                        }
+                        
+                        let label = RichText::new(text).color(annotation.color);
+                        if ui.selectable_label(page == self.current_page, label).clicked() {
+                            jump_to = Some(page);
+                        }
+                    }
+                }
+            });
+        
+        if let Some(page) = jump_to {
+            let ctx = ui.ctx().clone();
+            self.jump_to_page(page, None, &ctx);
+        }
+    }
+    
+    /// Draw the annotation toolbar row
+    fn annotation_toolbar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_annotations, "Annotations");
+            ui.separator();
+            
+            ui.selectable_value(&mut self.annotation_tool, AnnotationTool::Browse, "✋ Browse");
+            ui.selectable_value(&mut self.annotation_tool, AnnotationTool::Highlight, "🖍 Highlight");
+            ui.selectable_value(&mut self.annotation_tool, AnnotationTool::Note, "📝 Note");
+            ui.selectable_value(&mut self.annotation_tool, AnnotationTool::Ink, "✏ Ink");
+            
+            ui.separator();
+            
+            let unsaved = self.annotations.unsaved_count();
+            if ui.add_enabled(unsaved > 0, egui::Button::new(format!("💾 Save annotations ({})", unsaved)))
+                .on_hover_text("Append the new annotations to the PDF as an incremental update")
+                .clicked()
+            {
+                self.save_annotations();
+            }
+            
+            if ui.add_enabled(unsaved > 0, egui::Button::new("Discard")).clicked() {
+                self.annotations.discard_unsaved();
+                self.annotation_status = None;
+            }
+            
+            if let Some(status) = &self.annotation_status {
+                ui.label(RichText::new(status).small().weak());
+            }
+        });
+    }
+    
+    /// Handle pointer input on the rendered page for the active annotation tool
+    fn handle_annotation_input(&mut self, response: &egui::Response) {
+        let media_box = self.annotations.media_box(self.current_page);
+        let image_rect = response.rect;
+        
+        match self.annotation_tool {
+            AnnotationTool::Browse => {
+                if response.clicked() {
+                    let target = response.interact_pointer_pos().and_then(|pos| {
+                        let (x, y) = annotations::screen_to_pdf(media_box, image_rect, pos);
+                        annotations::link_at(self.annotations.page(self.current_page), x, y).cloned()
+                    });
+                    
+                    match target {
+                        Some(LinkTarget::Page(page)) => {
+                            let ctx = response.ctx.clone();
+                            self.jump_to_page(page, None, &ctx);
+                        },
+                        Some(LinkTarget::Uri(uri)) if super::utils::is_openable_uri(&uri) => {
+                            self.pending_uri = Some(uri);
+                        },
+                        Some(LinkTarget::Uri(uri)) => {
+                            eprintln!("Not following link to {}: only http, https and mailto links are opened", uri);
+                        },
+                        None => {},
+                    }
+                }
+            },
+            AnnotationTool::Highlight => {
+                if response.drag_started() {
+                    if let Some(pos) = response.interact_pointer_pos() {
+                        self.annotation_drag = Some((pos, pos));
+                    }
+                }
+                if response.dragged() {
+                    if let (Some((start, _)), Some(pos)) = (self.annotation_drag, response.interact_pointer_pos()) {
+                        self.annotation_drag = Some((start, pos));
+                    }
+                }
+                if response.drag_released() {
+                    if let Some((start, end)) = self.annotation_drag.take() {
+                        let (x1, y1) = annotations::screen_to_pdf(media_box, image_rect, start);
+                        let (x2, y2) = annotations::screen_to_pdf(media_box, image_rect, end);
+                        
+                        // Ignore accidental clicks
+                        if (x2 - x1).abs() > 2.0 && (y2 - y1).abs() > 2.0 {
+                            self.annotations.add(Annotation::new_highlight(
+                                self.current_page,
+                                [x1, y1, x2, y2],
+                                Color32::from_rgb(255, 230, 0),
+                            ));
+                        }
+                    }
+                }
+            },
+            AnnotationTool::Note => {
+                if response.clicked() && self.pending_note.is_none() {
+                    if let Some(pos) = response.interact_pointer_pos() {
+                        let (x, y) = annotations::screen_to_pdf(media_box, image_rect, pos);
+                        self.pending_note = Some(PendingNote {
+                            page: self.current_page,
+                            x,
+                            y,
+                            text: String::new(),
+                        });
+                    }
+                }
+            },
+            AnnotationTool::Ink => {
+                if response.drag_started() {
+                    self.ink_stroke.clear();
+                }
+                if response.dragged() {
+                    if let Some(pos) = response.interact_pointer_pos() {
+                        self.ink_stroke.push(annotations::screen_to_pdf(media_box, image_rect, pos));
+                    }
+                }
+                if response.drag_released() {
+                    let stroke = std::mem::take(&mut self.ink_stroke);
+                    if stroke.len() > 1 {
+                        self.annotations.add(Annotation::new_ink(
+                            self.current_page,
+                            stroke,
+                            Color32::from_rgb(220, 40, 40),
+                        ));
+                    }
+                }
+            },
+        }
+    }
+    
+    /// Paint annotations, in-progress strokes and hover details over the rendered page
+    fn paint_page_overlay(&self, ui: &Ui, response: &egui::Response) {
+        let image_rect = response.rect;
+        let media_box = self.annotations.media_box(self.current_page);
+        let painter = ui.painter_at(image_rect);
+        
+        if self.show_annotations {
+            annotations::paint_annotations(&painter, self.annotations.page(self.current_page), media_box, image_rect);
+        }
+        
+        // Highlight being dragged out
+        if let Some((start, current)) = self.annotation_drag {
+            painter.rect_filled(
+                Rect::from_two_pos(start, current),
+                egui::Rounding::ZERO,
+                Color32::from_rgba_unmultiplied(255, 230, 0, 90),
+            );
+        }
+        
+        // Ink stroke being drawn
+        for pair in self.ink_stroke.windows(2) {
+            painter.line_segment([
+                annotations::pdf_to_screen(media_box, image_rect, pair[0].0, pair[0].1),
+                annotations::pdf_to_screen(media_box, image_rect, pair[1].0, pair[1].1),
+            ], egui::Stroke::new(2.0, Color32::from_rgb(220, 40, 40)));
+        }
+        
+        // Show annotation contents on hover
+        if self.show_annotations {
+            if let Some(pos) = response.hover_pos() {
+                let (x, y) = annotations::screen_to_pdf(media_box, image_rect, pos);
+                
+                if self.annotation_tool == AnnotationTool::Browse {
+                    if let Some(target) = annotations::link_at(self.annotations.page(self.current_page), x, y) {
+                        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                        let text = match target {
+                            LinkTarget::Page(page) => format!("Go to page {}", page + 1),
+                            LinkTarget::Uri(uri) => uri.clone(),
+                        };
+                        response.clone().on_hover_text(text);
+                        return;
+                    }
+                }
+                
+                if let Some(annotation) = self.annotations.page(self.current_page)
+                    .iter()
+                    .rev()
+                    .find(|a| a.contains(x, y) && !a.contents.is_empty())
+                {
+                    let mut text = annotation.kind.label().to_string();
+                    if !annotation.author.is_empty() {
+                        text.push_str(&format!(" — {}", annotation.author));
+                    }
+                    text.push('\n');
+                    text.push_str(&annotation.contents);
+                    response.clone().on_hover_text(text);
+                }
+            }
+        }
+    }
+    
+    /// Show the text entry window for a pending sticky note
+    fn show_pending_note(&mut self, ctx: &Context) {
+        let mut add = false;
+        let mut cancel = false;
+        
+        if let Some(note) = &mut self.pending_note {
+            egui::Window::new("Add Note")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("Note on page {}", note.page + 1));
+                    ui.add(egui::TextEdit::multiline(&mut note.text)
+                        .hint_text("Type your note...")
+                        .desired_rows(4));
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!note.text.trim().is_empty(), egui::Button::new("Add")).clicked() {
+                            add = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+        }
+        
+        if add {
+            if let Some(note) = self.pending_note.take() {
+                self.annotations.add(Annotation::new_note(note.page, note.x, note.y, note.text));
+            }
+        } else if cancel {
+            self.pending_note = None;
+        }
+    }
+    
+    /// Ask for confirmation before opening an external link
+    fn show_pending_uri(&mut self, ctx: &Context) {
+        let mut open = false;
+        let mut cancel = false;
+        
+        if let Some(uri) = &self.pending_uri {
+            egui::Window::new("Open Link")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label("This document links to an external resource:");
+                    ui.label(RichText::new(uri).monospace());
+                    ui.add_space(5.0);
+                    ui.label("Open it with the system's default application?");
+                    ui.horizontal(|ui| {
+                        if ui.button("Open").clicked() {
+                            open = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+        }
+        
+        if open {
+            if let Some(uri) = self.pending_uri.take() {
+                if let Err(e) = super::utils::open_uri(&uri) {
+                    eprintln!("Failed to open {}: {}", uri, e);
+                }
+            }
+        } else if cancel {
+            self.pending_uri = None;
+        }
+    }
+    
+    /// Save annotations authored in this session into the PDF
+    fn save_annotations(&mut self) {
+        let path = match &self.current_pdf_path {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        
+        let unsaved = self.annotations.unsaved();
+        match annotations::save_annotations(&path, &unsaved) {
+            Ok(count) => {
+                for annotation in self.annotations.by_page.values_mut().flatten() {
+                    annotation.unsaved = false;
+                }
+                
+                // Re-render so the saved annotations come from the renderer
+                self.page_textures.clear();
+                self.thumbnail_textures.clear();
+                self.failed_thumbnails.lock().unwrap().clear();
+                self.annotation_status = Some(format!("Saved {} annotation(s)", count));
+            },
+            Err(e) => {
+                eprintln!("Error saving annotations to {}: {}", path.display(), e);
+                self.annotation_status = Some(format!("Failed to save annotations: {}", e));
+            }
         }
     }
     
     /// Recursively show an outline item and its children
-    fn show_outline_item(&self, ui: &mut Ui, item: &OutlineItem) {
+    fn show_outline_item(&self, ui: &mut Ui, item: &OutlineItem, jump_to: &mut Option<usize>) {
         ui.horizontal(|ui| {
             // Indent based on level
             ui.add_space(item.level as f32 * 10.0);
@@ -908,15 +1546,13 @@ impl PdfViewer {
             };
             
             if ui.link(text).clicked() {
-                // In a real implementation, this would scroll to the page
-                // For now, we just set it as the current page
-                // self.current_page = item.page;
+                *jump_to = Some(item.page);
             }
         });
         
         // Show children
         for child in &item.children {
-            self.show_outline_item(ui, child);
+            self.show_outline_item(ui, child, jump_to);
         }
     }
 
@@ -980,8 +1616,21 @@ impl PdfViewer {
         }
     }
     
-    /// Search in the current document
+    /// Search in the current document and jump to the first match
     fn search_in_document(&mut self, ctx: &Context) {
+        self.find_matches();
+        
+        // Jump to first match if any found
+        if !self.search_results.is_empty() {
+            let first_page = self.search_results[0].0;
+            self.current_match_index = Some(0);
+            self.jump_to_page(first_page, None, ctx);
+            ctx.request_repaint();
+        }
+    }
+    
+    /// Find all matches of the search query in the document text
+    fn find_matches(&mut self) {
         if self.search_query.trim().is_empty() {
             self.search_results.clear();
             self.current_match_index = None;
@@ -1056,20 +1705,6 @@ impl PdfViewer {
                 start_char_idx += 1;
             }
         }
-        
-        // Jump to first match if any found
-        if !self.search_results.is_empty() {
-            let first_page = self.search_results[0].0;
-            let search_term = self.search_query.clone();
-            self.current_match_index = Some(0);
-            // Update current page and render
-            self.current_page = first_page;
-            if self.use_poppler {
-                self.render_page(first_page, ctx);
-            }
-            self.extract_page_text(first_page);
-            ctx.request_repaint();
-        }
     }
     
     /// Jump to next search match
@@ -1078,21 +1713,12 @@ impl PdfViewer {
             let next_idx = (current_idx + 1) % self.search_results.len();
             self.current_match_index = Some(next_idx);
             let (page_num, _) = self.search_results[next_idx];
-            // Jump to the page
-            self.current_page = page_num;
-            if self.use_poppler {
-                self.render_page(page_num, ctx);
-            }
-            self.extract_page_text(page_num);
+            self.jump_to_page(page_num, None, ctx);
             ctx.request_repaint();
         } else if !self.search_results.is_empty() {
             self.current_match_index = Some(0);
             let (page_num, _) = self.search_results[0];
-            self.current_page = page_num;
-            if self.use_poppler {
-                self.render_page(page_num, ctx);
-            }
-            self.extract_page_text(page_num);
+            self.jump_to_page(page_num, None, ctx);
             ctx.request_repaint();
         }
     }
@@ -1107,28 +1733,90 @@ impl PdfViewer {
             };
             self.current_match_index = Some(prev_idx);
             let (page_num, _) = self.search_results[prev_idx];
-            // Jump to the page
-            self.current_page = page_num;
-            if self.use_poppler {
-                self.render_page(page_num, ctx);
-            }
-            self.extract_page_text(page_num);
+            self.jump_to_page(page_num, None, ctx);
             ctx.request_repaint();
         } else if !self.search_results.is_empty() {
             let last_idx = self.search_results.len().saturating_sub(1);
             self.current_match_index = Some(last_idx);
             let (page_num, _) = self.search_results[last_idx];
-            self.current_page = page_num;
-            if self.use_poppler {
-                self.render_page(page_num, ctx);
-            }
-            self.extract_page_text(page_num);
+            self.jump_to_page(page_num, None, ctx);
             ctx.request_repaint();
         }
     }
     
 }
 
+/// Read the document outline (bookmarks) from the catalog's /Outlines tree
+fn load_outline(doc: &Document) -> Vec<OutlineItem> {
+    let page_indices = annotations::page_index_map(doc);
+    
+    let first = doc.catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Outlines").ok())
+        .and_then(|o| annotations::resolve(doc, o).as_dict().ok())
+        .and_then(|outlines| outlines.get(b"First").ok())
+        .and_then(|o| o.as_reference().ok());
+    
+    match first {
+        Some(first) => load_outline_level(doc, first, 0, &page_indices),
+        None => Vec::new(),
+    }
+}
+
+/// Read one level of outline items, following /Next siblings and /First children
+fn load_outline_level(
+    doc: &Document,
+    first: lopdf::ObjectId,
+    level: usize,
+    page_indices: &HashMap<lopdf::ObjectId, usize>,
+) -> Vec<OutlineItem> {
+    let mut items = Vec::new();
+    let mut visited = std::collections::HashSet::new();
+    let mut next = Some(first);
+    
+    // Guard against malformed files with cyclic or absurdly deep outlines
+    if level > 16 {
+        return items;
+    }
+    
+    while let Some(id) = next {
+        if !visited.insert(id) {
+            break;
+        }
+        let item = match doc.get_dictionary(id) {
+            Ok(item) => item,
+            Err(_) => break,
+        };
+        
+        let dest = item.get(b"Dest").ok().or_else(|| {
+            item.get(b"A")
+                .ok()
+                .and_then(|o| annotations::resolve(doc, o).as_dict().ok())
+                .filter(|action| action.get(b"S").and_then(|s| s.as_name()).ok() == Some(&b"GoTo"[..]))
+                .and_then(|action| action.get(b"D").ok())
+        });
+        let page = dest
+            .and_then(|d| annotations::resolve_destination(doc, d, page_indices))
+            .unwrap_or(0);
+        
+        let children = item.get(b"First")
+            .and_then(|o| o.as_reference())
+            .map(|child| load_outline_level(doc, child, level + 1, page_indices))
+            .unwrap_or_default();
+        
+        items.push(OutlineItem {
+            title: annotations::text_entry(doc, item, b"Title"),
+            page,
+            level,
+            children,
+        });
+        
+        next = item.get(b"Next").and_then(|o| o.as_reference()).ok();
+    }
+    
+    items
+}
+
 /// Get PDF page count using pdfinfo (most reliable)
 fn get_pdf_page_count(path: &Path) -> usize {
     if let Ok(info_output) = Command::new("pdfinfo")
@@ -1171,4 +1859,130 @@ fn extract_text_from_pdf(path: &Path) -> Result<String, Box<dyn std::error::Erro
             Ok(String::new()) // Return empty string rather than crashing
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Render one page (0-based) to PNG bytes with pdftocairo, falling back to pdftoppm
+fn render_page_png(pdf_path: &Path, page_num: usize, dpi: u32) -> Option<Vec<u8>> {
+    let temp_dir = std::env::temp_dir();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    
+    // pdftocairo with -singlefile expects base name without extension, adds .png
+    let temp_base = temp_dir.join(format!("pdfscan_page_{}_{}_{}", page_num, dpi, timestamp));
+    let temp_file_png = temp_base.with_extension("png");
+    
+    // Try pdftocairo first (better for PNG)
+    let result = Command::new("pdftocairo")
+        .arg("-png")
+        .arg("-singlefile")
+        .arg("-r")
+        .arg(dpi.to_string())
+        .arg("-f")
+        .arg((page_num + 1).to_string())
+        .arg("-l")
+        .arg((page_num + 1).to_string())
+        .arg(pdf_path)
+        .arg(&temp_base)  // Base name without extension
+        .output();
+    
+    match result {
+        Ok(output) if output.status.success() => {
+            let png_data = fs::read(&temp_file_png);
+            let _ = fs::remove_file(&temp_file_png);
+            match png_data {
+                Ok(png_data) if !png_data.is_empty() => return Some(png_data),
+                Ok(_) => eprintln!("PNG file is empty"),
+                Err(e) => eprintln!("Failed to read temp PNG file {:?}: {}", temp_file_png, e),
+            }
+        },
+        Ok(output) => {
+            eprintln!("pdftocairo failed: {}", String::from_utf8_lossy(&output.stderr));
+        },
+        Err(e) => {
+            eprintln!("pdftocairo command failed: {}", e);
+        }
+    }
+    
+    // Fallback to pdftoppm, which needs a base name and adds page numbers like "base-1.png"
+    let temp_base_ppm = temp_dir.join(format!("pdfscan_ppm_{}_{}_{}", page_num, dpi, timestamp));
+    
+    let output = Command::new("pdftoppm")
+        .arg("-png")
+        .arg("-r")
+        .arg(dpi.to_string())
+        .arg("-f")
+        .arg((page_num + 1).to_string())
+        .arg("-l")
+        .arg((page_num + 1).to_string())
+        .arg(pdf_path)
+        .arg(&temp_base_ppm)
+        .output();
+    
+    match output {
+        Ok(output) if output.status.success() => {
+            // The page number may be zero-padded depending on the page count
+            let prefix = format!("{}-", temp_base_ppm.file_name().unwrap().to_string_lossy());
+            let actual_file = fs::read_dir(&temp_dir).ok()?
+                .flatten()
+                .map(|entry| entry.path())
+                .find(|path| path.file_name()
+                    .map(|name| name.to_string_lossy().starts_with(&prefix))
+                    .unwrap_or(false))?;
+            
+            let png_data = fs::read(&actual_file);
+            let _ = fs::remove_file(&actual_file);
+            match png_data {
+                Ok(png_data) if !png_data.is_empty() => Some(png_data),
+                Ok(_) => {
+                    eprintln!("pdftoppm output file is empty: {:?}", actual_file);
+                    None
+                },
+                Err(e) => {
+                    eprintln!("Failed to read pdftoppm output file {:?}: {}", actual_file, e);
+                    None
+                }
+            }
+        },
+        Ok(output) => {
+            eprintln!("pdftoppm failed: {}", String::from_utf8_lossy(&output.stderr));
+            None
+        },
+        Err(e) => {
+            eprintln!("Failed to run pdftoppm: {}", e);
+            None
+        }
+    }
+}
+
+/// Decode PNG bytes into RGBA pixels and their size
+fn decode_png(png_data: &[u8]) -> Option<(Vec<u8>, (u32, u32))> {
+    match image::load_from_memory(png_data) {
+        Ok(img) => {
+            let rgba = img.to_rgba8();
+            let size = (rgba.width(), rgba.height());
+            Some((rgba.into_raw(), size))
+        },
+        Err(e) => {
+            eprintln!("Failed to parse PNG: {}", e);
+            None
+        }
+    }
+}
+
+/// Load a page thumbnail from the on-disk cache, rendering and caching it if needed
+fn load_or_render_thumbnail(pdf_path: &Path, page_num: usize) -> Option<(Vec<u8>, (u32, u32))> {
+    let thumb_path = cache::get_thumbnail_path(pdf_path, page_num, config::get().viewer.thumbnail_dpi);
+    if let Some(png_data) = cache::load_thumbnail_from_cache(pdf_path, &thumb_path) {
+        if let Some(image) = decode_png(&png_data) {
+            return Some(image);
+        }
+    }
+    
+    let png_data = render_page_png(pdf_path, page_num, config::get().viewer.thumbnail_dpi)?;
+    if let Err(e) = cache::save_thumbnail_to_cache(&thumb_path, &png_data) {
+        eprintln!("Failed to cache thumbnail {:?}: {}", thumb_path, e);
+    }
+    decode_png(&png_data)
+}
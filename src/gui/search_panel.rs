@@ -1,10 +1,19 @@
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::collections::HashMap;
+use std::time::Duration;
 
 use egui::{Context, Ui, RichText, Color32, TextEdit, Key};
+use rayon::prelude::*;
 
+use crate::saved_searches::{self, SavedSearch, SearchScope};
+use crate::search::{find_matches, SearchMode, SearchOptions};
+use crate::watch::{Change, DirectoryWatcher, DEBOUNCE};
+
+use super::jobs::{JobHandle, JobKind, JobManager};
 use super::pdf_viewer::PdfViewer;
+use super::tabs::DocumentTabs;
+use super::cache::{extract_record, load_or_extract_record, record_cache_path, save_record_to_cache};
 
 /// Search panel component
 pub struct SearchPanel {
@@ -12,15 +21,22 @@ pub struct SearchPanel {
     search_results: Vec<SearchResult>,
     search_paths: Vec<PathBuf>,
     case_sensitive: bool,
+    search_mode: SearchMode,
     search_scope: SearchScope,
     directory_path: Option<PathBuf>,
     is_searching: bool,
     create_zip: bool,
     loaded_pdfs: Vec<PathBuf>,
     pdf_cache: HashMap<PathBuf, String>,
-    is_loading_directory: bool,
-    directory_loading_progress: Option<(usize, usize)>,
+    directory_job: Option<JobHandle<HashMap<PathBuf, String>>>, // Extracts the text of the loaded PDFs
+    watcher: Option<DirectoryWatcher>, // Follows PDFs added to, changed in or removed from the directory
+    refresh_jobs: Vec<JobHandle<HashMap<PathBuf, String>>>, // Extract the text of changed PDFs
     directory_filter: String,
+    history: Vec<String>, // Most recent query first
+    saved_searches: Vec<SavedSearch>,
+    new_search_name: String,
+    search_when_loaded: bool, // Run the search once the directory has loaded
+    jobs: JobManager,
 }
 
 /// Search result
@@ -37,28 +53,29 @@ struct MatchResult {
     position: usize,
 }
 
-#[derive(PartialEq)]
-enum SearchScope {
-    CurrentDocument,
-    Directory,
-}
-
 impl SearchPanel {
-    pub fn new() -> Self {
+    pub fn new(jobs: JobManager) -> Self {
         Self {
             search_query: String::new(),
             search_results: Vec::new(),
             search_paths: Vec::new(),
             case_sensitive: false,
+            search_mode: SearchMode::Substring,
             search_scope: SearchScope::CurrentDocument,
             directory_path: None,
             is_searching: false,
             create_zip: false,
             loaded_pdfs: Vec::new(),
             pdf_cache: HashMap::new(),
-            is_loading_directory: false,
-            directory_loading_progress: None,
+            directory_job: None,
+            watcher: None,
+            refresh_jobs: Vec::new(),
             directory_filter: String::new(),
+            history: saved_searches::load_history().unwrap_or_default(),
+            saved_searches: saved_searches::load_saved_searches().unwrap_or_default(),
+            new_search_name: String::new(),
+            search_when_loaded: false,
+            jobs,
         }
     }
     
@@ -68,16 +85,23 @@ impl SearchPanel {
         
         // Search query
         ui.label("Search for:");
-        let text_edit = TextEdit::singleline(&mut self.search_query)
-            .hint_text("Enter search text...")
-            .desired_width(ui.available_width());
-        
-        ui.add(text_edit);
+        ui.horizontal(|ui| {
+            let text_edit = TextEdit::singleline(&mut self.search_query)
+                .hint_text("Enter search text...")
+                .desired_width(ui.available_width() - 30.0);
+            
+            ui.add(text_edit);
+            self.history_menu(ui);
+        });
         
         ui.add_space(5.0);
         
         // Search options
         ui.checkbox(&mut self.case_sensitive, "Case sensitive");
+        let mut whole_words = self.search_mode == SearchMode::WholeWord;
+        if ui.checkbox(&mut whole_words, "Whole words only").changed() {
+            self.search_mode = if whole_words { SearchMode::WholeWord } else { SearchMode::Substring };
+        }
         
         ui.add_space(10.0);
         
@@ -125,12 +149,14 @@ impl SearchPanel {
                 ui.label(RichText::new("No directory selected").italics());
             }
             
-            if self.is_loading_directory {
-                if let Some((current, total)) = self.directory_loading_progress {
-                    ui.label(format!("Loading PDFs: {}/{}", current, total));
-                } else {
-                    ui.label("Loading PDFs...");
-                }
+            if let Some(job) = &self.directory_job {
+                let progress = job.progress();
+                ui.horizontal(|ui| {
+                    ui.label(format!("Loading PDFs: {}/{}", progress.done, progress.total));
+                    if ui.small_button("Cancel").clicked() {
+                        job.cancel();
+                    }
+                });
             }
             
             ui.checkbox(&mut self.create_zip, "Create ZIP with results");
@@ -151,6 +177,138 @@ impl SearchPanel {
         {
             self.perform_search(pdf_viewer);
         }
+        
+        ui.add_space(15.0);
+        self.show_saved_searches(ui, pdf_viewer);
+    }
+    
+    /// Button listing recent queries; picking one puts it in the search box
+    fn history_menu(&mut self, ui: &mut Ui) {
+        ui.add_enabled_ui(!self.history.is_empty(), |ui| {
+            ui.menu_button("🕘", |ui| {
+                for query in &self.history {
+                    if ui.button(super::utils::truncate_string(query, 50)).clicked() {
+                        self.search_query = query.clone();
+                        ui.close_menu();
+                    }
+                }
+                
+                ui.separator();
+                if ui.button("Clear History").clicked() {
+                    self.history.clear();
+                    if let Err(e) = saved_searches::save_history(&self.history) {
+                        eprintln!("Failed to save search history: {}", e);
+                    }
+                    ui.close_menu();
+                }
+            }).response.on_hover_text("Search history");
+        });
+    }
+    
+    /// List of saved searches, with a field to save the current one
+    fn show_saved_searches(&mut self, ui: &mut Ui, pdf_viewer: &PdfViewer) {
+        egui::CollapsingHeader::new("Saved Searches")
+            .default_open(true)
+            .show(ui, |ui| {
+                let mut run = None;
+                let mut delete = None;
+                
+                for (i, search) in self.saved_searches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                            delete = Some(i);
+                        }
+                        if ui.button(&search.name).on_hover_text(search.describe()).clicked() {
+                            run = Some(i);
+                        }
+                    });
+                }
+                
+                if self.saved_searches.is_empty() {
+                    ui.label(RichText::new("No saved searches").italics());
+                }
+                
+                if let Some(i) = run {
+                    let search = self.saved_searches[i].clone();
+                    self.run_saved_search(&search, pdf_viewer);
+                }
+                
+                if let Some(i) = delete {
+                    self.saved_searches.remove(i);
+                    self.save_saved_searches();
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(&mut self.new_search_name)
+                        .hint_text("Name")
+                        .desired_width(ui.available_width() - 60.0));
+                    
+                    let can_save = !self.new_search_name.trim().is_empty() && !self.search_query.trim().is_empty();
+                    if ui.add_enabled(can_save, egui::Button::new("Save"))
+                        .on_hover_text("Save the current query and options; a search with the same name is replaced")
+                        .clicked()
+                    {
+                        self.save_current_search();
+                    }
+                });
+            });
+    }
+    
+    /// Options of the search as currently set up
+    fn search_options(&self) -> SearchOptions {
+        SearchOptions {
+            mode: self.search_mode,
+            case_sensitive: self.case_sensitive,
+            filter: self.directory_filter.clone(),
+        }
+    }
+    
+    /// Save the current query and options under the entered name
+    fn save_current_search(&mut self) {
+        let search = SavedSearch {
+            name: self.new_search_name.trim().to_string(),
+            query: self.search_query.trim().to_string(),
+            scope: self.search_scope,
+            directory: self.directory_path.clone(),
+            options: self.search_options(),
+        };
+        
+        match self.saved_searches.iter_mut().find(|saved| saved.name == search.name) {
+            Some(saved) => *saved = search,
+            None => self.saved_searches.push(search),
+        }
+        self.new_search_name.clear();
+        self.save_saved_searches();
+    }
+    
+    fn save_saved_searches(&self) {
+        if let Err(e) = saved_searches::save_saved_searches(&self.saved_searches) {
+            eprintln!("Failed to save saved searches: {}", e);
+        }
+    }
+    
+    /// Restore a saved search and run it, after loading its directory if needed
+    fn run_saved_search(&mut self, search: &SavedSearch, pdf_viewer: &PdfViewer) {
+        self.search_query = search.query.clone();
+        self.search_mode = search.options.mode;
+        self.case_sensitive = search.options.case_sensitive;
+        self.directory_filter = search.options.filter.clone();
+        self.search_scope = search.scope;
+        
+        if search.scope == SearchScope::Directory {
+            if let Some(directory) = &search.directory {
+                if self.directory_path.as_ref() != Some(directory) {
+                    self.load_directory(directory);
+                }
+            }
+            
+            if self.directory_job.is_some() {
+                self.search_when_loaded = true;
+                return;
+            }
+        }
+        
+        self.perform_search(pdf_viewer);
     }
     
     /// Load directory (public method)
@@ -161,10 +319,12 @@ impl SearchPanel {
     
     /// Load all PDFs from a directory
     fn load_directory_pdfs(&mut self, dir_path: &PathBuf) {
-        self.is_loading_directory = true;
+        // Drop the text of a directory still loading
+        if let Some(job) = self.directory_job.take() {
+            job.cancel();
+        }
         self.loaded_pdfs.clear();
         self.pdf_cache.clear();
-        self.directory_loading_progress = Some((0, 0));
         
         // First, quickly scan for PDF files synchronously
         let mut pdfs = Vec::new();
@@ -180,123 +340,132 @@ impl SearchPanel {
         }
         
         self.loaded_pdfs = pdfs.clone();
-        self.directory_loading_progress = Some((0, self.loaded_pdfs.len()));
-        
-        // Now extract text in background
-        if !pdfs.is_empty() {
-            let pdf_cache_arc = Arc::new(Mutex::new(HashMap::<PathBuf, String>::new()));
-            let pdf_cache_clone = pdf_cache_arc.clone();
-            let progress_arc = Arc::new(Mutex::new(None::<(usize, usize)>));
-            let progress_clone = progress_arc.clone();
-            let is_loading_arc = Arc::new(Mutex::new(true));
-            let is_loading_clone = is_loading_arc.clone();
-            
-            // Create cache directory
-            let cache_dir = get_cache_directory(&dir_path);
-            let cache_dir_clone = cache_dir.clone();
-            
-            // Load PDFs with thread pool (1 thread per 20 files)
-            const FILES_PER_THREAD: usize = 20;
-            let num_threads = (pdfs.len() + FILES_PER_THREAD - 1) / FILES_PER_THREAD;
-            
-            let pdfs_arc = Arc::new(pdfs);
-            let mut handles = Vec::new();
+        
+        // Keep the list current while the directory is open
+        let ctx = self.jobs.context();
+        self.watcher = match DirectoryWatcher::new(std::slice::from_ref(dir_path), move || ctx.request_repaint()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                eprintln!("Cannot watch {} for changes: {}", dir_path.display(), e);
+                None
+            },
+        };
+        
+        if pdfs.is_empty() {
+            return;
+        }
+        
+        // Now extract text in the background, using and filling the text cache
+        let title = format!("Load {}", dir_path.file_name().unwrap_or_default().to_string_lossy());
+        
+        self.directory_job = Some(self.jobs.spawn(JobKind::DirectoryLoading, title, move |job| {
+            let done = AtomicUsize::new(0);
             
-            for thread_idx in 0..num_threads {
-                let pdfs_clone = pdfs_arc.clone();
-                let cache_dir_thread = cache_dir_clone.clone();
-                let progress_clone_thread = progress_clone.clone();
-                let pdf_cache_clone_thread = pdf_cache_clone.clone();
-                let is_loading_clone_thread = is_loading_clone.clone();
-                
-                let handle = std::thread::spawn(move || {
-                    let start_idx = thread_idx * FILES_PER_THREAD;
-                    let end_idx = (start_idx + FILES_PER_THREAD).min(pdfs_clone.len());
-                    
-                    let mut local_cache = HashMap::new();
+            let texts: HashMap<PathBuf, String> = pdfs.par_iter()
+                .filter_map(|pdf_path| {
+                    if job.is_cancelled() {
+                        return None;
+                    }
                     
-                    // Process files assigned to this thread
-                    for idx in start_idx..end_idx {
-                        let pdf_path = &pdfs_clone[idx];
-                        
-                        // Check cache first
-                        let cache_path = get_cache_path(pdf_path, &cache_dir_thread);
-                        let text = if let Some(cached_text) = load_text_from_cache(&cache_path) {
-                            // Use cached text
-                            cached_text
-                        } else {
-                            // Extract text and save to cache
-                            match extract_text_from_pdf_safe(pdf_path) {
-                                Ok(extracted_text) => {
-                                    // Save to cache (ignore errors)
-                                    let _ = save_text_to_cache(&cache_path, &extracted_text);
-                                    extracted_text
-                                },
-                                Err(e) => {
-                                    eprintln!("Failed to extract text from {}: {}", pdf_path.display(), e);
-                                    String::new()
-                                }
-                            }
-                        };
-                        
-                        if !text.is_empty() {
-                            local_cache.insert(pdf_path.clone(), text);
+                    // Check cache first
+                    let text = match load_or_extract_record(pdf_path, &record_cache_path(pdf_path)) {
+                        Ok(record) => record.text(),
+                        Err(e) => {
+                            eprintln!("Failed to extract text from {}: {}", pdf_path.display(), e);
+                            String::new()
                         }
-                        
-                        // Update progress
-                        {
-                            let mut prog = progress_clone_thread.lock().unwrap();
-                            *prog = Some((idx + 1, pdfs_clone.len()));
-                        }
-                    }
+                    };
                     
-                    // Merge local cache into shared cache
-                    {
-                        let mut cache_guard = pdf_cache_clone_thread.lock().unwrap();
-                        cache_guard.extend(local_cache);
-                    }
-                });
-                
-                handles.push(handle);
-            }
-            
-            // Spawn a thread to wait for all workers and mark completion
-            std::thread::spawn(move || {
-                for handle in handles {
-                    let _ = handle.join();
-                }
-                
-                // Mark loading as complete
-                {
-                    let mut loading = is_loading_clone.lock().unwrap();
-                    *loading = false;
-                }
-            });
+                    let name = pdf_path.file_name().unwrap_or_default().to_string_lossy();
+                    job.progress(done.fetch_add(1, Ordering::Relaxed) + 1, pdfs.len(), name);
+                    
+                    (!text.is_empty()).then(|| (pdf_path.clone(), text))
+                })
+                .collect();
             
-            // Store Arc reference for later access (we'll need to check completion)
-            // For now, we'll extract text lazily during search
-        } else {
-            self.is_loading_directory = false;
+            Ok(texts)
+        }));
+    }
+    
+    /// Take in the text of the loaded directory once it has been extracted
+    fn update_directory_loading(&mut self) {
+        let Some(result) = self.directory_job.as_ref().and_then(|job| job.poll()) else {
+            return;
+        };
+        
+        // A failure has already been reported by the job list
+        if let Ok(texts) = result {
+            self.pdf_cache.extend(texts);
         }
+        self.directory_job = None;
     }
     
-    /// Check for loaded directory PDFs and update state
-    fn update_directory_loading(&mut self, ctx: &Context) {
-        if self.is_loading_directory {
-            // In a real implementation, we'd use channels or async
-            // For now, we'll mark as complete after a short delay
-            // The background thread will update the cache as it processes files
-            ctx.request_repaint();
-            
-            // Check if we should mark loading as complete
-            // (In production, check thread completion status)
-            if let Some((current, total)) = self.directory_loading_progress {
-                if current >= total && total > 0 {
-                    self.is_loading_directory = false;
-                    self.directory_loading_progress = None;
-                }
+    /// Follow PDFs added, changed or removed in the loaded directory: update the
+    /// list and re-extract the text of new and changed files into the cache
+    fn update_watched_directory(&mut self, ctx: &Context) {
+        self.refresh_jobs.retain(|job| match job.poll() {
+            Some(Ok(texts)) => {
+                self.pdf_cache.extend(texts);
+                false
+            },
+            Some(Err(_)) => false,
+            None => true,
+        });
+        
+        let (Some(watcher), Some(dir_path)) = (&mut self.watcher, &self.directory_path) else {
+            return;
+        };
+        let changes = match watcher.changes(Duration::ZERO) {
+            Ok((changes, _)) => changes,
+            Err(e) => {
+                eprintln!("Stopped watching {}: {}", dir_path.display(), e);
+                self.watcher = None;
+                return;
+            },
+        };
+        
+        // Changes are reported once files stop changing
+        if watcher.has_pending() {
+            ctx.request_repaint_after(DEBOUNCE);
+        }
+        
+        let mut updated = Vec::new();
+        for change in changes {
+            match change {
+                Change::Updated(path) => {
+                    if !self.loaded_pdfs.contains(&path) {
+                        self.loaded_pdfs.push(path.clone());
+                    }
+                    updated.push(path);
+                },
+                Change::Removed(path) => {
+                    let _ = std::fs::remove_file(record_cache_path(&path));
+                    self.loaded_pdfs.retain(|pdf| *pdf != path);
+                    self.pdf_cache.remove(&path);
+                },
             }
         }
+        
+        if updated.is_empty() {
+            return;
+        }
+        
+        let title = format!("Reindex {} changed PDF(s)", updated.len());
+        self.refresh_jobs.push(self.jobs.spawn(JobKind::DocumentLoading, title, move |_| {
+            let texts = updated.into_iter()
+                .filter_map(|pdf_path| match extract_record(&pdf_path) {
+                    Ok(record) => {
+                        let _ = save_record_to_cache(&record_cache_path(&pdf_path), &record);
+                        Some((pdf_path, record.text()))
+                    },
+                    Err(e) => {
+                        eprintln!("Failed to extract text from {}: {}", pdf_path.display(), e);
+                        None
+                    },
+                })
+                .collect();
+            Ok(texts)
+        }));
     }
     
     /// Perform a search operation
@@ -310,6 +479,12 @@ impl SearchPanel {
         self.is_searching = true;
         self.search_results.clear();
         
+        saved_searches::add_to_history(&mut self.history, &self.search_query);
+        if let Err(e) = saved_searches::save_history(&self.history) {
+            eprintln!("Failed to save search history: {}", e);
+        }
+        let options = self.search_options();
+        
         // Search in current document
         if self.search_scope == SearchScope::CurrentDocument {
             if let Some(pdf_path) = pdf_viewer.current_pdf() {
@@ -332,9 +507,10 @@ impl SearchPanel {
         else if self.search_scope == SearchScope::Directory {
             if let Some(_dir_path) = &self.directory_path {
                 // Use loaded PDFs and cache for searching
-                let search_query = self.search_query.clone();
-                let case_sensitive = self.case_sensitive;
-                let loaded_pdfs = self.loaded_pdfs.clone();
+                let loaded_pdfs: Vec<PathBuf> = self.loaded_pdfs.iter()
+                    .filter(|path| options.accepts(path))
+                    .cloned()
+                    .collect();
                 let pdf_cache = self.pdf_cache.clone();
                 
                 // Search through loaded PDFs
@@ -347,28 +523,9 @@ impl SearchPanel {
                         cached_text.clone()
                     } else {
                         // Try to load from cache file first
-                        let cache_dir = if let Some(dir) = &self.directory_path {
-                            get_cache_directory(dir)
-                        } else {
-                            pdf_path.parent().unwrap_or_else(|| Path::new(".")).to_path_buf()
-                        };
-                        let cache_path = get_cache_path(pdf_path, &cache_dir);
-                        
-                        let extracted_text = if let Some(cached_text) = load_text_from_cache(&cache_path) {
-                            // Use cached text
-                            cached_text
-                        } else {
-                            // Extract text safely and save to cache
-                            match extract_text_from_pdf_safe(pdf_path) {
-                                Ok(text) => {
-                                    // Save to cache (ignore errors)
-                                    let _ = save_text_to_cache(&cache_path, &text);
-                                    text
-                                },
-                                Err(_) => {
-                                    continue; // Skip this PDF if extraction fails
-                                }
-                            }
+                        let extracted_text = match load_or_extract_record(pdf_path, &record_cache_path(pdf_path)) {
+                            Ok(record) => record.text(),
+                            Err(_) => continue, // Skip this PDF if extraction fails
                         };
                         
                         // Cache in memory for this session
@@ -376,34 +533,19 @@ impl SearchPanel {
                         extracted_text
                     };
                     
-                    // Search in text
-                    let search_text = if case_sensitive {
-                        text.clone()
-                    } else {
-                        text.to_lowercase()
-                    };
-                    
-                    let query_lower = if case_sensitive {
-                        search_query.clone()
-                    } else {
-                        search_query.to_lowercase()
-                    };
-                    
-                    let matches = search_text.matches(&query_lower).count();
+                    // Search in text, with the context of each match
+                    let match_results = self.search_in_text(&text);
                     
-                    if matches > 0 {
+                    if !match_results.is_empty() {
                         let file_name = pdf_path.file_name()
                             .unwrap_or_default()
                             .to_string_lossy()
                             .to_string();
                         
-                        // Find match positions for context
-                        let match_results = self.search_in_text(&text);
-                        
                         results.push(SearchResult {
                             file_path: pdf_path.clone(),
                             file_name,
-                            match_count: matches,
+                            match_count: match_results.len(),
                             matches: match_results,
                         });
                     }
@@ -446,109 +588,41 @@ impl SearchPanel {
     /// Search for matches in text
     /// This method safely handles UTF-8 characters and prevents crashes
     fn search_in_text(&self, text: &str) -> Vec<MatchResult> {
-        let mut matches = Vec::new();
-        
-        // Return early if query is empty
-        if self.search_query.is_empty() {
-            return matches;
-        }
-        
-        let query = if self.case_sensitive {
-            self.search_query.clone()
-        } else {
-            self.search_query.to_lowercase()
-        };
-        
-        // If query is empty after processing, return
-        if query.is_empty() {
-            return matches;
-        }
-        
-        let search_text = if self.case_sensitive {
-            text.to_string()
-        } else {
-            text.to_lowercase()
-        };
+        let query_len = self.search_query.chars().count();
         
         // Use char_indices to handle UTF-8 properly
         let text_chars: Vec<(usize, char)> = text.char_indices().collect();
-        let search_chars: Vec<char> = search_text.chars().collect();
-        let query_chars: Vec<char> = query.chars().collect();
+        let byte_at = |char_idx: usize| text_chars.get(char_idx).map_or(text.len(), |&(byte, _)| byte);
         
-        if query_chars.is_empty() || search_chars.len() < query_chars.len() {
-            return matches;
-        }
-        
-        // Find all occurrences using character matching
-        let mut start_char_idx = 0;
-        while start_char_idx <= search_chars.len().saturating_sub(query_chars.len()) {
-            // Check if we have a match starting at start_char_idx
-            let mut matched = true;
-            for (i, &qc) in query_chars.iter().enumerate() {
-                if start_char_idx + i >= search_chars.len() || search_chars[start_char_idx + i] != qc {
-                    matched = false;
-                    break;
-                }
-            }
-            
-            if matched {
-                // Found a match at start_char_idx
-                // Get the byte position in the original text
-                let byte_pos = if start_char_idx < text_chars.len() {
-                    text_chars[start_char_idx].0
-                } else {
-                    text.len()
-                };
-                
+        find_matches(text, &self.search_query, &self.search_options())
+            .into_iter()
+            .map(|start_char_idx| {
                 // Extract context (40 chars before and after)
-                let context_before = 40;
-                let context_after = 40;
+                let context_before = crate::config::get().search.context_chars;
+                let context_after = context_before;
                 
                 let context_start_char = start_char_idx.saturating_sub(context_before);
-                let context_end_char = (start_char_idx + query_chars.len() + context_after)
-                    .min(text_chars.len());
-                
-                // Get byte positions for context
-                let context_start_byte = if context_start_char < text_chars.len() {
-                    text_chars[context_start_char].0
-                } else {
-                    0
-                };
+                let context_end_char = (start_char_idx + query_len + context_after).min(text_chars.len());
                 
-                let context_end_byte = if context_end_char < text_chars.len() {
-                    text_chars[context_end_char].0
-                } else {
-                    text.len()
-                };
-                
-                // Safely extract context
-                let context = if context_start_byte < context_end_byte && context_end_byte <= text.len() {
-                    text.get(context_start_byte..context_end_byte)
+                MatchResult {
+                    text: text.get(byte_at(context_start_char)..byte_at(context_end_char))
                         .unwrap_or("")
-                        .to_string()
-                } else {
-                    String::new()
-                };
-                
-                matches.push(MatchResult {
-                    text: context,
-                    position: byte_pos,
-                });
-                
-                // Move past this match
-                start_char_idx += query_chars.len();
-            } else {
-                start_char_idx += 1;
-            }
-        }
-        
-        matches
+                        .to_string(),
+                    position: byte_at(start_char_idx),
+                }
+            })
+            .collect()
     }
     
     /// Show the search panel in the main content area
-    pub fn show(&mut self, ui: &mut Ui, ctx: &Context, pdf_viewer: &mut PdfViewer) {
+    pub fn show(&mut self, ui: &mut Ui, ctx: &Context, tabs: &mut DocumentTabs) {
         // Update directory loading status
-        self.update_directory_loading(ctx);
+        self.update_directory_loading();
+        self.update_watched_directory(ctx);
+        if self.search_when_loaded && self.directory_job.is_none() {
+            self.search_when_loaded = false;
+            self.perform_search(tabs.active());
+        }
         ui.vertical(|ui| {
             // Top search bar
             ui.horizontal(|ui| {
@@ -578,16 +652,17 @@ impl SearchPanel {
                             .desired_width(ui.available_width() - 20.0)
                             .show(ui);
                         
+                        self.history_menu(ui);
                         ui.checkbox(&mut self.case_sensitive, "Case sensitive");
                         
                         let button_enabled = !self.search_query.is_empty() && 
-                            ((self.search_scope == SearchScope::CurrentDocument && pdf_viewer.current_pdf().is_some()) || 
+                            ((self.search_scope == SearchScope::CurrentDocument && tabs.active().current_pdf().is_some()) || 
                             (self.search_scope == SearchScope::Directory && self.directory_path.is_some()));
                         
                         // Check for Enter key press to trigger search
                         if button_enabled && !self.is_searching && 
                            (text_edit_response.response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter))) {
-                            self.perform_search(pdf_viewer);
+                            self.perform_search(tabs.active());
                         }
                     });
                     
@@ -612,17 +687,17 @@ impl SearchPanel {
             egui::CentralPanel::default().show_inside(ui, |ui| {
                 if self.search_results.is_empty() && self.search_scope == SearchScope::Directory && !self.loaded_pdfs.is_empty() {
                     // Show directory PDFs list
-                    self.show_directory_pdfs(ui, pdf_viewer);
+                    self.show_directory_pdfs(ui, tabs);
                 } else {
                     // Show search results
-                    self.show_results(ui, pdf_viewer, ctx);
+                    self.show_results(ui, tabs, ctx);
                 }
             });
         });
     }
     
     /// Show directory PDFs list
-    fn show_directory_pdfs(&mut self, ui: &mut Ui, pdf_viewer: &mut PdfViewer) {
+    fn show_directory_pdfs(&mut self, ui: &mut Ui, tabs: &mut DocumentTabs) {
         ui.horizontal(|ui| {
             ui.heading(format!("PDF Files in Directory ({} files)", self.loaded_pdfs.len()));
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -683,7 +758,7 @@ impl SearchPanel {
                         ui.label(format!("{}. ", idx + 1));
                         
                         if ui.button("📄 Open").clicked() {
-                            pdf_viewer.load_pdf(pdf_path);
+                            tabs.open_result(pdf_path);
                         }
                         
                         // Limit filename length
@@ -717,7 +792,7 @@ impl SearchPanel {
     }
     
     /// Show the search results
-    fn show_results(&mut self, ui: &mut Ui, pdf_viewer: &mut PdfViewer, ctx: &Context) {
+    fn show_results(&mut self, ui: &mut Ui, tabs: &mut DocumentTabs, ctx: &Context) {
         // Store search query in memory for highlighting
         if !self.search_query.is_empty() {
             ui.memory_mut(|mem| mem.data.insert_temp("search_query".into(), self.search_query.clone()));
@@ -778,7 +853,7 @@ impl SearchPanel {
                             .show(ui, |ui| {
                                 ui.horizontal(|ui| {
                                     if ui.button("Open PDF").clicked() {
-                                        pdf_viewer.load_pdf(&result.file_path);
+                                        tabs.open_result(&result.file_path);
                                     }
                                     // Truncate long paths to prevent rendering issues
                                     let path_str = result.file_path.to_string_lossy();
@@ -809,18 +884,9 @@ impl SearchPanel {
                                         ui.label(format!("{}. {}", i + 1, display_text));
                                         
                                         if ui.button("Jump to match").clicked() {
-                                            // Calculate the approximate page number based on position
-                                            let text = pdf_viewer.text();
-                                            if !text.is_empty() {
-                                                let position_ratio = m.position as f32 / text.len() as f32;
-                                                let page = (position_ratio * pdf_viewer.total_pages() as f32).floor() as usize;
-                                                
-                                                // Jump to the calculated page with search term highlighting
-                                                pdf_viewer.jump_to_page(page, Some(&self.search_query), ctx);
-                                            } else {
-                                                // Just load the PDF if we can't calculate the page
-                                                pdf_viewer.load_pdf(&result.file_path);
-                                            }
+                                            // Open the result and jump to the page of the match once its text is loaded
+                                            tabs.open_result(&result.file_path)
+                                                .jump_to_text_position(m.position, &self.search_query, ctx);
                                         }
                                     });
                                 }
@@ -866,75 +932,7 @@ fn search_files_in_directory(dir: &PathBuf, search_phrase: &str) -> Result<Vec<P
 /// Check if a PDF file contains the search phrase (safely)
 fn search_phrase_in_pdf(file_path: &Path, search_phrase: &str) -> Result<bool, Box<dyn std::error::Error>> {
     // Try cache first
-    let cache_dir = file_path.parent().unwrap_or_else(|| Path::new("."));
-    let cache_path = get_cache_path(file_path, cache_dir);
-    
-    let text = if let Some(cached_text) = load_text_from_cache(&cache_path) {
-        cached_text
-    } else {
-        // Extract text safely
-        match extract_text_from_pdf_safe(file_path) {
-            Ok(extracted_text) => {
-                // Save to cache
-                let _ = save_text_to_cache(&cache_path, &extracted_text);
-                extracted_text
-            },
-            Err(e) => {
-                return Err(e); // Return error if extraction fails
-            }
-        }
-    };
+    let text = load_or_extract_record(file_path, &record_cache_path(file_path))?.text();
     
     Ok(text.contains(search_phrase))
 }
-
-/// Extract text from a PDF file safely, handling panics
-fn extract_text_from_pdf_safe(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
-    let bytes = std::fs::read(path)?;
-    
-    // Wrap in panic handler to prevent crashes from malformed PDFs
-    let text = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        pdf_extract::extract_text_from_mem(&bytes)
-    }));
-    
-    match text {
-        Ok(Ok(text)) => Ok(text),
-        Ok(Err(e)) => {
-            eprintln!("Error extracting text from {}: {}", path.display(), e);
-            Ok(String::new()) // Return empty string on error, don't fail completely
-        },
-        Err(_) => {
-            // Panic occurred (likely from type1-encoding-parser with malformed fonts)
-            eprintln!("Warning: PDF text extraction panicked for {} (malformed PDF?)", path.display());
-            Ok(String::new()) // Return empty string rather than crashing
-        }
-    }
-}
-
-/// Get cache file path for a PDF
-fn get_cache_path(pdf_path: &Path, cache_dir: &Path) -> PathBuf {
-    let pdf_name = pdf_path.file_stem()
-        .unwrap_or_default()
-        .to_string_lossy();
-    cache_dir.join(format!("{}.txt", pdf_name))
-}
-
-/// Get or create .pdfscan cache directory
-fn get_cache_directory(pdf_dir: &Path) -> PathBuf {
-    pdf_dir.join(".pdfscan")
-}
-
-/// Load text from cache file if it exists
-fn load_text_from_cache(cache_path: &Path) -> Option<String> {
-    std::fs::read_to_string(cache_path).ok()
-}
-
-/// Save text to cache file
-fn save_text_to_cache(cache_path: &Path, text: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Create parent directory if it doesn't exist
-    if let Some(parent) = cache_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    std::fs::write(cache_path, text)?;
-    Ok(())
-}
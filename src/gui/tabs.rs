@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use egui::{Context, RichText, Ui};
+
+use super::jobs::JobManager;
+use super::pdf_viewer::PdfViewer;
+
+/// Open documents, each with its own viewer state
+pub struct DocumentTabs {
+    tabs: Vec<Tab>,
+    active: usize,
+    next_id: u64,
+    /// Open search/analysis results in a new tab instead of the current one
+    pub open_results_in_new_tab: bool,
+    jobs: JobManager,
+}
+
+/// One document tab
+struct Tab {
+    id: u64,
+    viewer: PdfViewer,
+}
+
+impl DocumentTabs {
+    pub fn new(jobs: JobManager) -> Self {
+        Self {
+            tabs: vec![Tab { id: 0, viewer: PdfViewer::new(jobs.clone()) }],
+            active: 0,
+            next_id: 1,
+            open_results_in_new_tab: true,
+            jobs,
+        }
+    }
+
+    /// Reopen the tabs that were open when the application last exited
+    pub fn restore(jobs: JobManager) -> Self {
+        let mut tabs = Self::new(jobs);
+
+        let (paths, active) = load_open_tabs().unwrap_or_default();
+        for path in paths.iter().filter(|path| path.exists()) {
+            tabs.open_in_new_tab(path);
+        }
+        // Select the saved tab by its document, as tabs of missing files were skipped
+        let active_tab = paths
+            .get(active)
+            .and_then(|path| tabs.tabs.iter().position(|tab| tab.viewer.current_pdf() == Some(path)));
+        if let Some(index) = active_tab {
+            tabs.active = index;
+        }
+
+        tabs
+    }
+
+    /// The viewer of the selected tab
+    pub fn active(&self) -> &PdfViewer {
+        &self.tabs[self.active].viewer
+    }
+
+    /// The viewer of the selected tab
+    pub fn active_mut(&mut self) -> &mut PdfViewer {
+        &mut self.tabs[self.active].viewer
+    }
+
+    /// Open a document in a new tab, or switch to the tab already showing it
+    pub fn open_in_new_tab(&mut self, path: &Path) -> &mut PdfViewer {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.viewer.current_pdf().map(|p| p.as_path()) == Some(path)) {
+            self.active = index;
+        } else if self.active().current_pdf().is_none() {
+            // Reuse the empty tab shown at startup
+            self.tabs[self.active].viewer.load_pdf(path);
+        } else {
+            let mut viewer = PdfViewer::new(self.jobs.clone());
+            viewer.load_pdf(path);
+            self.tabs.push(Tab { id: self.next_id, viewer });
+            self.next_id += 1;
+            self.active = self.tabs.len() - 1;
+        }
+
+        self.save();
+        self.active_mut()
+    }
+
+    /// Open a document in the selected tab
+    pub fn open_in_current_tab(&mut self, path: &Path) -> &mut PdfViewer {
+        if self.active().current_pdf().map(|p| p.as_path()) != Some(path) {
+            self.active_mut().load_pdf(path);
+            self.save();
+        }
+        self.active_mut()
+    }
+
+    /// Open a search or analysis result, honouring `open_results_in_new_tab`
+    pub fn open_result(&mut self, path: &Path) -> &mut PdfViewer {
+        if self.open_results_in_new_tab {
+            self.open_in_new_tab(path)
+        } else {
+            self.open_in_current_tab(path)
+        }
+    }
+
+    /// Close a tab; the last tab is emptied rather than removed
+    pub fn close(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+
+        self.tabs[index].viewer.remember_reading_position();
+        self.tabs.remove(index);
+
+        if self.tabs.is_empty() {
+            self.tabs.push(Tab { id: self.next_id, viewer: PdfViewer::new(self.jobs.clone()) });
+            self.next_id += 1;
+        }
+        if self.active > index || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+
+        self.save();
+    }
+
+    /// Persist the reading position of every open document
+    pub fn remember_reading_positions(&mut self) {
+        for tab in &mut self.tabs {
+            tab.viewer.remember_reading_position();
+        }
+    }
+
+    /// Draw the tab bar above the viewer
+    pub fn show_tab_bar(&mut self, ui: &mut Ui) {
+        let mut select = None;
+        let mut close = None;
+        let mut open = false;
+
+        egui::ScrollArea::horizontal()
+            .id_source("document_tabs")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, tab) in self.tabs.iter().enumerate() {
+                        let title = if tab.viewer.current_pdf().is_some() {
+                            super::utils::truncate_string(tab.viewer.title(), 30)
+                        } else {
+                            "New Tab".to_string()
+                        };
+
+                        let selected = index == self.active;
+                        let label = if selected { RichText::new(title).strong() } else { RichText::new(title) };
+                        let response = ui.selectable_label(selected, label);
+                        if response.clicked() {
+                            select = Some(index);
+                        }
+                        if response.middle_clicked() {
+                            close = Some(index);
+                        }
+                        if let Some(path) = tab.viewer.current_pdf() {
+                            response.on_hover_text(path.to_string_lossy());
+                        }
+
+                        if ui.small_button("×").on_hover_text("Close tab").clicked() {
+                            close = Some(index);
+                        }
+                        ui.separator();
+                    }
+
+                    if ui.small_button("+").on_hover_text("Open PDF in a new tab").clicked() {
+                        open = true;
+                    }
+                });
+            });
+
+        if let Some(index) = select {
+            if index != self.active {
+                self.active = index;
+                self.save();
+            }
+        }
+        if let Some(index) = close {
+            self.close(index);
+        }
+        if open {
+            if let Some(path) = super::app::open_file_dialog() {
+                self.open_in_new_tab(&path);
+            }
+        }
+    }
+
+    /// Draw the selected document
+    pub fn show(&mut self, ui: &mut Ui, ctx: &Context) {
+        // Keep widget state (scroll offsets etc.) separate per tab
+        let tab = &mut self.tabs[self.active];
+        ui.push_id(tab.id, |ui| {
+            tab.viewer.show(ui, ctx);
+        });
+    }
+
+    /// Draw the sidebar of the selected document
+    pub fn show_sidebar(&mut self, ui: &mut Ui) {
+        let tab = &mut self.tabs[self.active];
+        ui.push_id(tab.id, |ui| {
+            tab.viewer.show_sidebar(ui);
+        });
+    }
+
+    /// Persist the open tab set
+    fn save(&self) {
+        let paths: Vec<&PathBuf> = self.tabs
+            .iter()
+            .filter_map(|tab| tab.viewer.current_pdf())
+            .collect();
+
+        // Index of the selected tab among the tabs that have a document
+        let active = self.tabs[..self.active]
+            .iter()
+            .filter(|tab| tab.viewer.current_pdf().is_some())
+            .count();
+
+        if let Err(e) = save_open_tabs(&paths, active) {
+            eprintln!("Failed to save open tabs: {}", e);
+        }
+    }
+}
+
+/// File in the pdfscan config directory listing the open tabs
+fn open_tabs_path() -> Option<PathBuf> {
+    crate::config::config_dir().map(|dir| dir.join("open_tabs.txt"))
+}
+
+/// Load the open tab set and the selected index.
+///
+/// Each line is a document path; the selected one is prefixed with `*<TAB>`.
+fn load_open_tabs() -> Result<(Vec<PathBuf>, usize)> {
+    let path = match open_tabs_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok((Vec::new(), 0)),
+    };
+
+    let content = std::fs::read_to_string(path)?;
+    let mut paths = Vec::new();
+    let mut active = 0;
+    for line in content.lines().filter(|line| !line.is_empty()) {
+        match line.strip_prefix("*\t") {
+            Some(path) => {
+                active = paths.len();
+                paths.push(PathBuf::from(path));
+            },
+            None => paths.push(PathBuf::from(line)),
+        }
+    }
+
+    Ok((paths, active))
+}
+
+/// Save the open tab set
+fn save_open_tabs(paths: &[&PathBuf], active: usize) -> Result<()> {
+    let path = match open_tabs_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let content: String = paths
+        .iter()
+        .enumerate()
+        .map(|(index, p)| {
+            if index == active {
+                format!("*\t{}", p.to_string_lossy())
+            } else {
+                p.to_string_lossy().to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
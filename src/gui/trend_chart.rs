@@ -0,0 +1,153 @@
+use egui::{Align2, Color32, FontId, Pos2, Rect, RichText, Sense, Shape, Stroke, Ui, Vec2};
+use egui::epaint::TextShape;
+
+use crate::trends::TrendReport;
+
+use super::utils::truncate_string;
+
+/// Series colours, repeated when there are more keywords
+const PALETTE: [Color32; 10] = [
+    Color32::from_rgb(31, 119, 180),
+    Color32::from_rgb(255, 127, 14),
+    Color32::from_rgb(44, 160, 44),
+    Color32::from_rgb(214, 39, 40),
+    Color32::from_rgb(148, 103, 189),
+    Color32::from_rgb(140, 86, 75),
+    Color32::from_rgb(227, 119, 194),
+    Color32::from_rgb(127, 127, 127),
+    Color32::from_rgb(188, 189, 34),
+    Color32::from_rgb(23, 190, 207),
+];
+
+/// How the series are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartStyle {
+    Line,
+    Bar,
+}
+
+/// Colour of a keyword's series
+pub fn series_color(keyword: usize) -> Color32 {
+    PALETTE[keyword % PALETTE.len()]
+}
+
+/// Draw the keyword series of a trend report, one point or bar per group.
+/// `relative` plots occurrences per 1,000 words instead of counts.
+pub fn show_trend_chart(ui: &mut Ui, report: &TrendReport, style: ChartStyle, relative: bool) {
+    if report.groups.is_empty() {
+        ui.label("No documents to chart");
+        return;
+    }
+
+    // Legend
+    ui.horizontal_wrapped(|ui| {
+        for (k, keyword) in report.keywords.iter().enumerate() {
+            ui.label(RichText::new("■").color(series_color(k)));
+            ui.label(keyword);
+            ui.add_space(8.0);
+        }
+    });
+
+    let value = |g: usize, k: usize| {
+        let group = &report.groups[g];
+        if relative { group.frequency(k) } else { group.counts[k] as f64 }
+    };
+    let groups = report.groups.len();
+    let keywords = report.keywords.len();
+    let max = (0..groups)
+        .flat_map(|g| (0..keywords).map(move |k| (g, k)))
+        .map(|(g, k)| value(g, k))
+        .fold(0.0, f64::max)
+        .max(f64::EPSILON);
+
+    let size = Vec2::new(ui.available_width().max(300.0), 340.0);
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    let plot = Rect::from_min_max(
+        response.rect.min + Vec2::new(56.0, 10.0),
+        response.rect.max - Vec2::new(10.0, 70.0),
+    );
+    let slot = plot.width() / groups as f32;
+    let x = |g: usize| plot.left() + (g as f32 + 0.5) * slot;
+    let y = |v: f64| plot.bottom() - (v / max) as f32 * plot.height();
+
+    let visuals = ui.visuals();
+    let text_color = visuals.text_color();
+    let grid = Stroke::new(1.0, visuals.widgets.noninteractive.bg_stroke.color);
+    let font = FontId::proportional(11.0);
+
+    // Horizontal grid lines with value labels
+    for tick in 0..=4 {
+        let v = max * tick as f64 / 4.0;
+        painter.hline(plot.x_range(), y(v), grid);
+        let label = if relative || max < 10.0 { format!("{:.1}", v) } else { format!("{:.0}", v) };
+        painter.text(Pos2::new(plot.left() - 6.0, y(v)), Align2::RIGHT_CENTER, label, font.clone(), text_color);
+    }
+
+    // Group labels, turned upright when they don't fit side by side
+    for (g, group) in report.groups.iter().enumerate() {
+        let label = truncate_string(&group_label(report, &group.key), 16);
+        let galley = painter.layout_no_wrap(label, font.clone(), text_color);
+        if galley.size().x + 4.0 <= slot {
+            painter.galley(Pos2::new(x(g) - galley.size().x / 2.0, plot.bottom() + 4.0), galley, text_color);
+        } else {
+            let pos = Pos2::new(x(g) - galley.size().y / 2.0, plot.bottom() + 4.0 + galley.size().x);
+            painter.add(TextShape::new(pos, galley, text_color).with_angle(-std::f32::consts::FRAC_PI_2));
+        }
+    }
+
+    match style {
+        ChartStyle::Line => {
+            for k in 0..keywords {
+                let points: Vec<Pos2> = (0..groups).map(|g| Pos2::new(x(g), y(value(g, k)))).collect();
+                painter.add(Shape::line(points.clone(), Stroke::new(2.0, series_color(k))));
+                for point in points {
+                    painter.circle_filled(point, 3.0, series_color(k));
+                }
+            }
+        },
+        ChartStyle::Bar => {
+            let bar = slot * 0.8 / keywords as f32;
+            for g in 0..groups {
+                for k in 0..keywords {
+                    let left = x(g) - slot * 0.4 + k as f32 * bar;
+                    let rect = Rect::from_min_max(Pos2::new(left, y(value(g, k))), Pos2::new(left + bar, plot.bottom()));
+                    painter.rect_filled(rect, 0.0, series_color(k));
+                }
+            }
+        },
+    }
+
+    painter.hline(plot.x_range(), plot.bottom(), Stroke::new(1.0, text_color));
+    painter.vline(plot.left(), plot.y_range(), Stroke::new(1.0, text_color));
+
+    // Values of the group under the pointer
+    let Some(pointer) = response.hover_pos().filter(|p| plot.x_range().contains(p.x)) else {
+        return;
+    };
+    let g = (((pointer.x - plot.left()) / slot) as usize).min(groups - 1);
+    painter.vline(x(g), plot.y_range(), Stroke::new(1.0, visuals.weak_text_color()));
+
+    let group = &report.groups[g];
+    response.on_hover_ui_at_pointer(|ui| {
+        ui.label(RichText::new(&group.key).strong());
+        ui.label(format!("{} documents, {} words", group.documents, group.word_count));
+        for (k, keyword) in report.keywords.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("■").color(series_color(k)));
+                ui.label(format!("{}: {} ({:.2} per 1,000 words)", keyword, group.counts[k], group.frequency(k)));
+            });
+        }
+    });
+}
+
+/// Axis label of a group: folder names without their path, dates as they are
+fn group_label(report: &TrendReport, key: &str) -> String {
+    if report.group_by.is_date() {
+        key.to_string()
+    } else {
+        std::path::Path::new(key)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| key.to_string())
+    }
+}
@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::fs;
+use std::process::Command;
 use egui::{Button, Color32, Ui};
 
 /// Formats a file size for display
@@ -26,10 +27,11 @@ pub fn get_file_size(path: &Path) -> Option<u64> {
 
 /// Truncates a string to a maximum length
 pub fn truncate_string(s: &str, max_length: usize) -> String {
-    if s.len() <= max_length {
+    if s.chars().count() <= max_length {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_length - 3])
+        let truncated: String = s.chars().take(max_length.saturating_sub(3)).collect();
+        format!("{}...", truncated)
     }
 }
 
@@ -51,4 +53,35 @@ pub fn setup_search_button_style<'a>(_ui: &mut Ui, button: Button<'a>) -> Button
     button
         .fill(Color32::from_rgb(60, 120, 180))
         .stroke((1.0, Color32::from_rgb(40, 80, 120)))
-} 
\ No newline at end of file
+}
+
+/// URI schemes that links in documents may open
+const OPENABLE_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Whether a link URI uses a scheme that may be handed to the system
+pub fn is_openable_uri(uri: &str) -> bool {
+    uri.split_once(':')
+        .is_some_and(|(scheme, _)| OPENABLE_SCHEMES.iter().any(|allowed| allowed.eq_ignore_ascii_case(scheme)))
+}
+
+/// Open an http, https or mailto URI with the system's default application. The URI
+/// is passed as a single argument, never through a shell.
+pub fn open_uri(uri: &str) -> std::io::Result<()> {
+    if !is_openable_uri(uri) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "only http, https and mailto links can be opened",
+        ));
+    }
+    
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = Command::new("explorer.exe");
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Command::new("xdg-open");
+    
+    command.arg(uri).spawn()?;
+    Ok(())
+}
+
@@ -2,14 +2,37 @@ use eframe::{NativeOptions, run_native};
 use egui::ViewportBuilder;
 
 mod gui;
+mod cache;
+mod config;
+mod discover;
 mod extract;
+mod isolation;
+mod report;
+mod saved_searches;
 mod search;
+mod similarity;
 mod stats;
+mod stopwords;
+mod text;
+mod topics;
+mod trends;
+mod watch;
 
 fn main() -> Result<(), eframe::Error> {
+    // Isolated extraction runs this binary as its worker
+    if let Some(code) = isolation::worker_main() {
+        std::process::exit(code);
+    }
+    
     // Initialize logging
     env_logger::init();
     
+    // Read config.toml and .pdfscan.toml before any window opens
+    match config::load() {
+        Ok(settings) => config::init(settings),
+        Err(e) => eprintln!("Error: {}; using the default settings", e),
+    }
+    
     // Set up native options
     let options = NativeOptions {
         viewport: ViewportBuilder::default()
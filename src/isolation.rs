@@ -0,0 +1,229 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cache::{self, CacheRecord};
+
+/// First argument that makes either binary run as an extraction worker
+pub const WORKER_ARG: &str = "--extract-worker";
+
+/// How often a worker is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Exit code of a worker whose extraction failed normally
+const EXIT_FAILED: i32 = 2;
+
+/// Limits of isolated extraction workers
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsolationConfig {
+    /// Extract each PDF in its own worker process
+    pub enabled: bool,
+    /// Wall-clock time a worker gets before it is killed
+    pub timeout: Duration,
+    /// Address space limit of a worker in MB; Unix only
+    pub memory_limit_mb: Option<u64>,
+}
+
+impl Default for IsolationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: Duration::from_secs(60),
+            memory_limit_mb: Some(2048),
+        }
+    }
+}
+
+/// Why a PDF could not be extracted in a worker
+#[derive(Debug)]
+pub enum WorkerError {
+    SpawnError(io::Error),
+    Timeout(Duration),
+    /// Killed by a signal, usually a crash or running out of memory
+    Crashed(String),
+    /// The extractor reported an error
+    Failed(String),
+    /// The worker's output could not be read
+    OutputError(String),
+}
+
+impl fmt::Display for WorkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerError::SpawnError(e) => write!(f, "Cannot start extraction worker: {}", e),
+            WorkerError::Timeout(timeout) => write!(f, "Extraction timed out after {} s", timeout.as_secs_f64()),
+            WorkerError::Crashed(e) => write!(f, "Extraction worker crashed: {}", e),
+            WorkerError::Failed(e) => write!(f, "{}", e),
+            WorkerError::OutputError(e) => write!(f, "Invalid output from extraction worker: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WorkerError {}
+
+/// Extract a PDF in a worker process under the configured limits
+pub fn extract_isolated(path: &Path, config: &IsolationConfig) -> Result<CacheRecord, WorkerError> {
+    // The extractor prints diagnostics to stdout, so the record comes back in a file.
+    // It is created here, readable only by this user, and removed when dropped.
+    let mut output = tempfile::Builder::new()
+        .prefix("pdfscan-worker-")
+        .suffix(&format!(".{}", cache::RECORD_EXTENSION))
+        .tempfile()
+        .map_err(WorkerError::SpawnError)?;
+    run_worker(path, output.path(), config)?;
+
+    // Read through the handle created above, whatever the path names by now
+    let mut bytes = Vec::new();
+    output.as_file_mut().seek(SeekFrom::Start(0))
+        .and_then(|_| output.as_file_mut().read_to_end(&mut bytes))
+        .map_err(|e| WorkerError::OutputError(e.to_string()))?;
+    CacheRecord::decode(&bytes).map_err(|e| WorkerError::OutputError(e.to_string()))
+}
+
+fn run_worker(path: &Path, output: &Path, config: &IsolationConfig) -> Result<(), WorkerError> {
+    let executable = std::env::current_exe().map_err(WorkerError::SpawnError)?;
+    let mut command = Command::new(executable);
+    command.arg(WORKER_ARG)
+        .arg(path)
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if let Some(limit) = config.memory_limit_mb {
+        limit_memory(&mut command, limit);
+    }
+
+    let mut child = command.spawn().map_err(WorkerError::SpawnError)?;
+    // Read the pipe while waiting, so that a lot of output can't fill it and stall the worker
+    let stderr = read_in_background(child.stderr.take());
+
+    let status = wait_with_timeout(&mut child, config.timeout)?;
+    let stderr = String::from_utf8_lossy(&stderr.join().unwrap_or_default()).trim().to_string();
+
+    if status.success() {
+        return Ok(());
+    }
+    match status.code() {
+        Some(EXIT_FAILED) => Err(WorkerError::Failed(stderr)),
+        Some(code) => Err(WorkerError::Crashed(format!("exit code {}{}", code, detail(&stderr)))),
+        None => Err(WorkerError::Crashed(format!("{}{}", signal_description(&status, config), detail(&stderr)))),
+    }
+}
+
+fn detail(stderr: &str) -> String {
+    match stderr.lines().last() {
+        Some(line) => format!(" ({})", line),
+        None => String::new(),
+    }
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<std::process::ExitStatus, WorkerError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().map_err(WorkerError::SpawnError)? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(WorkerError::Timeout(timeout));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(unix)]
+fn limit_memory(command: &mut Command, limit_mb: u64) {
+    use std::os::unix::process::CommandExt;
+
+    let bytes = limit_mb.saturating_mul(1024 * 1024) as libc::rlim_t;
+    // Safety: only calls setrlimit, which is async-signal-safe, between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+            if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn limit_memory(_command: &mut Command, _limit_mb: u64) {}
+
+/// Open the record file created by the parent, without creating one or following a
+/// symbolic link put in its place
+#[cfg(unix)]
+fn open_output(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new().write(true).truncate(true).custom_flags(libc::O_NOFOLLOW).open(path)
+}
+
+#[cfg(not(unix))]
+fn open_output(path: &Path) -> io::Result<File> {
+    OpenOptions::new().write(true).truncate(true).open(path)
+}
+
+#[cfg(unix)]
+fn signal_description(status: &std::process::ExitStatus, config: &IsolationConfig) -> String {
+    use std::os::unix::process::ExitStatusExt;
+
+    match (status.signal(), config.memory_limit_mb) {
+        // Rust aborts when an allocation fails
+        (Some(libc::SIGABRT), Some(limit)) => format!("aborted, possibly over the {} MB memory limit", limit),
+        (Some(signal), _) => format!("signal {}", signal),
+        (None, _) => "unknown status".to_string(),
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_description(_status: &std::process::ExitStatus, _config: &IsolationConfig) -> String {
+    "unknown status".to_string()
+}
+
+/// Run as an extraction worker if the process was started as one, returning its exit
+/// code: the record of the PDF named after `WORKER_ARG` is written to the existing file
+/// named next
+pub fn worker_main() -> Option<i32> {
+    let mut args = std::env::args_os().skip(1);
+    if args.next()? != WORKER_ARG {
+        return None;
+    }
+    let (Some(path), Some(output)) = (args.next(), args.next()) else {
+        eprintln!("No PDF and record file given to the extraction worker");
+        return Some(EXIT_FAILED);
+    };
+
+    let record = match cache::extract_record_in_process(Path::new(&path)) {
+        Ok(record) => record,
+        Err(e) => {
+            eprintln!("{}", e);
+            return Some(EXIT_FAILED);
+        },
+    };
+
+    let written = record.encode().and_then(|bytes| open_output(Path::new(&output))?.write_all(&bytes));
+    match written {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Cannot write record: {}", e);
+            Some(EXIT_FAILED)
+        },
+    }
+}
@@ -1,5 +1,17 @@
 pub mod gui;
+pub mod cache;
+pub mod config;
+pub mod discover;
 pub mod extract;
+pub mod isolation;
+pub mod report;
+pub mod saved_searches;
 pub mod search;
+pub mod similarity;
 pub mod stats;
+pub mod stopwords;
+pub mod text;
+pub mod topics;
+pub mod trends;
+pub mod watch;
 
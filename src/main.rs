@@ -1,16 +1,49 @@
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::atomic::AtomicBool;
 
+mod cache;
+mod config;
+mod discover;
 mod extract;
+mod isolation;
+mod report;
+mod saved_searches;
 mod search;
+mod similarity;
 mod stats;
+mod stopwords;
+mod text;
+mod topics;
+mod trends;
+mod watch;
 
 #[derive(Parser)]
 #[command(author, version, about = "PDF text extraction and search tool")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    
+    /// Worker threads for parallel extraction (default: [performance] threads in the config, else all cores)
+    #[arg(long, global = true)]
+    threads: Option<usize>,
+    
+    /// Extract each PDF in a separate worker process, so a file that hangs or crashes is only recorded as failed
+    #[arg(long, global = true)]
+    isolate: bool,
+    
+    /// Seconds an isolated extraction may take before it is killed (default: from the config, else 60)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+    
+    /// Memory limit of an isolated extraction in MB, 0 for none; Unix only (default: from the config, else 2048)
+    #[arg(long, global = true)]
+    memory_limit: Option<u64>,
+    
+    /// Where extracted text is cached: in-tree, central or disabled (default: [cache] location in the config, else in-tree)
+    #[arg(long, global = true)]
+    cache: Option<cache::CacheLocation>,
 }
 
 #[derive(Subcommand)]
@@ -27,8 +60,8 @@ enum Commands {
     /// Search for text in PDF files
     Search {
         /// Text to search for
-        #[arg(short, long)]
-        search_phrase: String,
+        #[arg(short, long, required_unless_present = "saved")]
+        search_phrase: Option<String>,
         
         /// Directories to search in
         #[arg(short, long, required = false)]
@@ -37,12 +70,16 @@ enum Commands {
         /// Enable ZIP output of matching files
         #[arg(short, long)]
         zip: bool,
+        
+        /// Run a search saved in the GUI, in its directory unless --directories are given
+        #[arg(long, conflicts_with = "search_phrase")]
+        saved: Option<String>,
     },
 
     /// Analyze keyword correlations in PDF files
     Analyze {
-        /// Keywords to analyze
-        #[arg(short, long, required = true)]
+        /// Keywords to analyze; separate synonyms counted as one keyword with `|`
+        #[arg(short, long, required_unless_present_any = ["discover", "topics"])]
         keywords: Vec<String>,
         
         /// Input paths (directories or PDF files)
@@ -53,24 +90,350 @@ enum Commands {
         #[arg(short, long, default_value = "pdf_analysis_report.txt")]
         output_file: String,
         
-        /// Correlation threshold (0.0 to 1.0)
-        #[arg(short, long, default_value_t = 0.1)]
+        /// Report format: text, html, markdown, json or csv (default: from the output file extension, else text)
+        #[arg(short, long)]
+        format: Option<report::ReportFormat>,
+        
+        /// Correlation threshold, on the normalized scale of the chosen measure (default: from the config, else 0.1)
+        #[arg(short, long)]
+        threshold: Option<f64>,
+        
+        /// Correlation measure: cooccurrence, pearson, spearman, jaccard, dice, pmi, npmi or chi-square
+        /// (default: from the config, else pearson)
+        #[arg(short, long)]
+        measure: Option<stats::CorrelationMeasure>,
+        
+        /// Document ranking: correlation, tfidf or bm25 (default: from the config, else correlation)
+        #[arg(short, long)]
+        ranking: Option<stats::RankingMethod>,
+        
+        /// Co-occurrence window: document, sentence, paragraph, page or words:N (default: from the config, else document)
+        #[arg(short, long)]
+        window: Option<stats::CooccurrenceWindow>,
+        
+        /// Match keywords case-sensitively
+        #[arg(long)]
+        case_sensitive: bool,
+        
+        /// Match keywords by their Snowball stem in --language
+        #[arg(long)]
+        stem: bool,
+        
+        /// Discover keywords from the documents and add them to --keywords
+        #[arg(long)]
+        discover: bool,
+        
+        /// Discovery method: tfidf, rake or textrank
+        #[arg(long, default_value = "tfidf")]
+        discover_method: discover::DiscoveryMethod,
+        
+        /// Number of keywords to discover
+        #[arg(long, default_value_t = 10)]
+        discover_top: usize,
+        
+        /// Group the documents into topics by their TF-IDF vectors instead of analyzing keywords
+        #[arg(long, conflicts_with_all = ["keywords", "discover"])]
+        topics: bool,
+        
+        /// Number of topics to form
+        #[arg(long, default_value_t = 5)]
+        clusters: usize,
+        
+        /// Topic clustering method: kmeans or hierarchical
+        #[arg(long, default_value = "kmeans")]
+        clustering: topics::ClusteringMethod,
+        
+        /// Language for stemming and discovery and topic stopwords (default: from the config, else english)
+        #[arg(long)]
+        language: Option<stopwords::Language>,
+    },
+
+    /// Propose keywords and key phrases for PDF files
+    Discover {
+        /// Input paths (directories or PDF files)
+        #[arg(short, long, required = true)]
+        input_paths: Vec<String>,
+        
+        /// Output report file path (printed to stdout if omitted)
+        #[arg(short, long)]
+        output_file: Option<String>,
+        
+        /// Discovery method: tfidf, rake or textrank
+        #[arg(short, long, default_value = "tfidf")]
+        method: discover::DiscoveryMethod,
+        
+        /// Stopword language: english, german, french, spanish, italian, dutch or portuguese
+        /// (default: from the config, else english)
+        #[arg(short, long)]
+        language: Option<stopwords::Language>,
+        
+        /// Number of terms to propose, corpus-wide and per document
+        #[arg(short, long, default_value_t = 10)]
+        top: usize,
+        
+        /// Longest phrase to propose, in words
+        #[arg(long, default_value_t = 3)]
+        max_ngram: usize,
+    },
+
+    /// Find duplicate and near-duplicate PDF files
+    Dedupe {
+        /// Input paths (directories or PDF files)
+        #[arg(short, long, required = true)]
+        input_paths: Vec<String>,
+        
+        /// Output report file path (printed to stdout if omitted)
+        #[arg(short, long)]
+        output_file: Option<String>,
+        
+        /// Similarity method: minhash or simhash
+        #[arg(short, long, default_value = "minhash")]
+        method: similarity::SimilarityMethod,
+        
+        /// Minimum text similarity (0 to 1) of near-duplicates
+        #[arg(short, long, default_value_t = 0.8)]
         threshold: f64,
+        
+        /// Words per shingle for MinHash
+        #[arg(long, default_value_t = 5)]
+        shingle_size: usize,
+    },
+
+    /// Chart keyword frequencies over folders or document dates
+    Trends {
+        /// Keywords to count; separate synonyms counted as one keyword with `|`
+        #[arg(short, long, required = true)]
+        keywords: Vec<String>,
+        
+        /// Input paths (directories or PDF files)
+        #[arg(short, long, required = true)]
+        input_paths: Vec<String>,
+        
+        /// Output file path (printed to stdout if omitted)
+        #[arg(short, long)]
+        output_file: Option<String>,
+        
+        /// Group documents by folder, created (PDF creation date) or modified (file time)
+        #[arg(short, long, default_value = "folder")]
+        group_by: trends::GroupBy,
+        
+        /// Date period when grouping by date: day, month or year
+        #[arg(short, long, default_value = "month")]
+        period: trends::Period,
+        
+        /// Write CSV instead of a text report
+        #[arg(long)]
+        csv: bool,
+        
+        /// Match keywords case-sensitively
+        #[arg(long)]
+        case_sensitive: bool,
+        
+        /// Match keywords by their Snowball stem in --language
+        #[arg(long)]
+        stem: bool,
+        
+        /// Language for stemming (default: from the config, else english)
+        #[arg(long)]
+        language: Option<stopwords::Language>,
+    },
+
+    /// Index new and changed PDFs as they arrive and report those matching standing queries
+    Watch {
+        /// Directories to watch, including subdirectories
+        #[arg(required = true)]
+        directories: Vec<PathBuf>,
+        
+        /// Text to look for in new and changed PDFs; repeat for several queries
+        #[arg(short, long)]
+        query: Vec<String>,
+        
+        /// Saved search to use as a query, with its own options; repeat for several
+        #[arg(long)]
+        saved: Vec<String>,
+        
+        /// Match --query text case-sensitively
+        #[arg(long)]
+        case_sensitive: bool,
+        
+        /// Match --query text as whole words only
+        #[arg(long)]
+        whole_word: bool,
+        
+        /// Print one JSON object per event instead of text
+        #[arg(long)]
+        json: bool,
+        
+        /// Shell command run for each match, with PDFSCAN_PATH, PDFSCAN_QUERY,
+        /// PDFSCAN_MATCHES and PDFSCAN_EVENT (the event as JSON) set
+        #[arg(long)]
+        exec: Option<String>,
+    },
+
+    /// Report on, prune or clear the text and thumbnail caches
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+
+    /// Inspect the settings read from config.toml and .pdfscan.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Show the size of the central cache and of the in-tree caches under the directories
+    Stats {
+        /// Directories to look for in-tree caches in, and to limit the central cache to
+        directories: Vec<PathBuf>,
+    },
+    /// Remove cached files of removed or changed PDFs, and caches of directories that are gone
+    Prune {
+        /// Directories to look for in-tree caches in, and to limit the central cache to
+        directories: Vec<PathBuf>,
+    },
+    /// Remove the whole central cache, or all caches of the given directories
+    Clear {
+        /// Directories whose central and in-tree caches are removed, including subdirectories
+        directories: Vec<PathBuf>,
     },
 }
 
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the settings in effect and the files they were read from
+    Show,
+}
+
 fn main() {
+    if let Some(code) = isolation::worker_main() {
+        process::exit(code);
+    }
+    
     let cli = Cli::parse();
+    
+    let mut settings = match config::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        },
+    };
+    if cli.threads.is_some() {
+        settings.threads = cli.threads;
+    }
+    if cli.isolate {
+        settings.isolation.enabled = true;
+    }
+    if let Some(timeout) = cli.timeout {
+        settings.isolation.timeout = std::time::Duration::from_secs(timeout);
+    }
+    if let Some(limit) = cli.memory_limit {
+        settings.isolation.memory_limit_mb = (limit > 0).then_some(limit);
+    }
+    if let Some(location) = cli.cache {
+        settings.cache.location = location;
+    }
+    config::init(settings);
+    let settings = config::get();
 
     let result = match cli.command {
         Commands::Extract { output_file, input_paths } => {
             extract::run(&output_file, &input_paths)
         },
-        Commands::Search { search_phrase, directories, zip } => {
-            search::run(&search_phrase, &directories, zip)
+        Commands::Search { search_phrase, directories, zip, saved } => match saved {
+            Some(name) => saved_searches::run(&name, &directories, zip),
+            None => {
+                let options = search::SearchOptions { case_sensitive: true, ..Default::default() };
+                search::run(&search_phrase.unwrap_or_default(), &directories, zip, &options)
+            },
+        },
+        Commands::Analyze {
+            keywords, input_paths, output_file, format, threshold, measure, ranking, window,
+            case_sensitive, stem, discover, discover_method, discover_top, topics, clusters,
+            clustering, language,
+        } => {
+            let defaults = &settings.analysis;
+            let language = language.unwrap_or(defaults.language);
+            if topics {
+                let options = topics::TopicOptions { method: clustering, clusters, language, ..Default::default() };
+                topics::run(&input_paths, &output_file, &options)
+            } else {
+                let matching = text::MatchOptions { case_sensitive, stemming: stem.then_some(language) };
+                let options = stats::AnalysisOptions {
+                    measure: measure.unwrap_or(defaults.measure),
+                    threshold: threshold.unwrap_or(defaults.threshold),
+                    ranking: ranking.unwrap_or(defaults.ranking),
+                    window: window.unwrap_or(defaults.window),
+                    matching,
+                };
+                let format = format
+                    .or_else(|| report::ReportFormat::from_path(Path::new(&output_file)))
+                    .unwrap_or_default();
+                if discover {
+                    let discovery = discover::DiscoveryOptions {
+                        method: discover_method,
+                        language,
+                        top: discover_top,
+                        ..Default::default()
+                    };
+                    // Discovery and analysis share one extraction of the documents
+                    stats::extract_records(&input_paths, &AtomicBool::new(false))
+                        .map_err(Into::into)
+                        .and_then(|records| {
+                            let keywords = with_discovered_keywords(keywords, &records, &discovery);
+                            let report = stats::analyze_records(&records, &keywords, &options)?;
+                            stats::write_report(&report, &output_file, format)
+                        })
+                } else {
+                    stats::run(&input_paths, &keywords, &output_file, format, &options)
+                }
+            }
+        },
+        Commands::Discover { input_paths, output_file, method, language, top, max_ngram } => {
+            let language = language.unwrap_or(settings.analysis.language);
+            let options = discover::DiscoveryOptions { method, language, top, max_ngram };
+            discover::run(&input_paths, output_file.as_deref(), &options)
+        },
+        Commands::Dedupe { input_paths, output_file, method, threshold, shingle_size } => {
+            let options = similarity::DedupeOptions { method, threshold, shingle_size };
+            similarity::run(&input_paths, output_file.as_deref(), &options)
+        },
+        Commands::Trends {
+            keywords, input_paths, output_file, group_by, period, csv, case_sensitive, stem, language,
+        } => {
+            let language = language.unwrap_or(settings.analysis.language);
+            let matching = text::MatchOptions { case_sensitive, stemming: stem.then_some(language) };
+            let options = trends::TrendOptions { group_by, period, matching };
+            trends::run(&input_paths, &keywords, output_file.as_deref(), csv, &options)
+        },
+        Commands::Watch { directories, query, saved, case_sensitive, whole_word, json, exec } => {
+            let mode = if whole_word { search::SearchMode::WholeWord } else { search::SearchMode::Substring };
+            let options = search::SearchOptions { mode, case_sensitive, ..Default::default() };
+            let mut queries: Vec<watch::StandingQuery> = query
+                .into_iter()
+                .map(|query| watch::StandingQuery { query, options: options.clone(), saved: None })
+                .collect();
+            
+            saved.iter()
+                .map(|name| saved_searches::find_saved_search(name).map(watch::StandingQuery::from))
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|saved| {
+                    queries.extend(saved);
+                    watch::run(&directories, &queries, &watch::WatchOptions { json, exec })
+                })
         },
-        Commands::Analyze { keywords, input_paths, output_file, threshold } => {
-            stats::run(&input_paths, &keywords, &output_file, threshold)
+        Commands::Cache { command } => match command {
+            CacheCommand::Stats { directories } => cache::run_stats(&directories),
+            CacheCommand::Prune { directories } => cache::run_prune(&directories),
+            CacheCommand::Clear { directories } => cache::run_clear(&directories),
+        },
+        Commands::Config { command: ConfigCommand::Show } => {
+            print!("{}", settings.to_toml());
+            Ok(())
         },
     };
 
@@ -78,4 +441,26 @@ fn main() {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
-} 
\ No newline at end of file
+}
+
+/// Add keywords discovered in the extracted input documents to the given ones
+fn with_discovered_keywords(
+    mut keywords: Vec<String>,
+    records: &[(PathBuf, cache::CacheRecord)],
+    options: &discover::DiscoveryOptions,
+) -> Vec<String> {
+    let texts: Vec<(PathBuf, String)> = records.iter()
+        .map(|(path, record)| (path.clone(), record.text()))
+        .collect();
+    let report = discover::discover_texts(&texts, options);
+    let discovered = report.keywords();
+    println!("Discovered keywords: {}", discovered.join(", "));
+    
+    for keyword in discovered {
+        if !keywords.contains(&keyword) {
+            keywords.push(keyword);
+        }
+    }
+    
+    keywords
+}
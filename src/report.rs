@@ -0,0 +1,438 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::stats::{AnalysisReport, CorrelationMeasure};
+
+/// Output format of an analysis report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// The plain text report
+    #[default]
+    Text,
+    /// A standalone page with a coloured heatmap and the full ranked list
+    Html,
+    Markdown,
+    Json,
+    /// The ranked documents with their keyword counts, one row per document
+    Csv,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 5] = [
+        ReportFormat::Text,
+        ReportFormat::Html,
+        ReportFormat::Markdown,
+        ReportFormat::Json,
+        ReportFormat::Csv,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReportFormat::Text => "text",
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+        }
+    }
+
+    /// Usual file extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Text => "txt",
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+        }
+    }
+
+    /// Format implied by a file's extension, if it is a known one
+    pub fn from_path(path: &Path) -> Option<ReportFormat> {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(ReportFormat::Text),
+            "html" | "htm" => Ok(ReportFormat::Html),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(format!(
+                "unknown report format '{}' (expected one of: {})",
+                s,
+                ReportFormat::ALL.iter().map(|f| f.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Render an analysis report in the given format
+pub fn render(report: &AnalysisReport, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text => report.to_text(),
+        ReportFormat::Html => html(report),
+        ReportFormat::Markdown => markdown(report),
+        ReportFormat::Json => json(report),
+        ReportFormat::Csv => csv(report),
+    }
+}
+
+/// Heatmap colour of a correlation scaled to -1..1: red for positive, blue for
+/// negative values, fading to a light or dark neutral grey at zero
+pub fn heat_rgb(value: f64, dark: bool) -> [u8; 3] {
+    let neutral = if dark { [60.0, 60.0, 60.0] } else { [240.0, 240.0, 240.0] };
+    let target = if value >= 0.0 { [220.0, 60.0, 50.0] } else { [50.0, 110.0, 220.0] };
+    let t = value.abs().min(1.0);
+    [0, 1, 2].map(|c| (neutral[c] + (target[c] - neutral[c]) * t) as u8)
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Title of the raw statistic matrix, if the measure has one
+fn statistic_title(measure: CorrelationMeasure) -> &'static str {
+    match measure {
+        CorrelationMeasure::ChiSquare => "Chi-square statistic",
+        _ => "PMI",
+    }
+}
+
+/// Largest absolute correlation, used to scale heatmap colours
+fn color_scale(report: &AnalysisReport) -> f64 {
+    report.correlations.iter().flatten().fold(0.0f64, |max, value| max.max(value.abs())).max(f64::EPSILON)
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html(report: &AnalysisReport) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>PDFScan Statistical Analysis Report</title>\n<style>\n");
+    html.push_str("body { font-family: sans-serif; margin: 2em; }\n");
+    html.push_str("table { border-collapse: collapse; margin-bottom: 2em; }\n");
+    html.push_str("th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: right; }\n");
+    html.push_str("th { background: #f4f4f4; }\n");
+    html.push_str("td.name, th.name { text-align: left; }\n");
+    html.push_str("td.strong { font-weight: bold; outline: 2px solid #333; outline-offset: -3px; }\n");
+    html.push_str("blockquote { color: #555; margin: 0.3em 0 1em 1em; }\n");
+    html.push_str("</style>\n</head>\n<body>\n");
+    html.push_str("<h1>PDFScan Statistical Analysis Report</h1>\n<ul>\n");
+    html.push_str(&format!("<li>Keywords: {}</li>\n", html_escape(&report.keywords.join(", "))));
+    html.push_str(&format!("<li>Total documents analyzed: {}</li>\n", report.total_documents()));
+    html.push_str(&format!("<li>Co-occurrence window: {}</li>\n", report.window));
+    html.push_str(&format!(
+        "<li>Correlation measure: {} ({})</li>\n",
+        report.measure,
+        html_escape(report.measure.scale())
+    ));
+    html.push_str(&format!("<li>Correlation threshold: {:.2}</li>\n", report.threshold));
+    html.push_str(&format!("<li>Ranking: {}</li>\n</ul>\n", report.ranking.title()));
+
+    // Correlation heatmap; outlined cells reach the threshold
+    html.push_str(&format!("<h2>Keyword Correlation Matrix ({})</h2>\n<table>\n<tr><th></th>", report.measure));
+    for keyword in &report.keywords {
+        html.push_str(&format!("<th>{}</th>", html_escape(keyword)));
+    }
+    html.push_str("</tr>\n");
+    let scale = color_scale(report);
+    for (i, keyword) in report.keywords.iter().enumerate() {
+        html.push_str(&format!("<tr><th class=\"name\">{}</th>", html_escape(keyword)));
+        for j in 0..report.keywords.len() {
+            if i == j {
+                html.push_str("<td>—</td>");
+                continue;
+            }
+
+            let value = report.correlations[i][j];
+            let [r, g, b] = heat_rgb(value / scale, false);
+            let mut details = vec![format!("{} × {}", report.keywords[i], report.keywords[j])];
+            if let Some(statistics) = &report.statistics {
+                details.push(format!("{}: {:.3}", statistic_title(report.measure), statistics[i][j]));
+            }
+            if let Some(p_values) = &report.p_values {
+                details.push(format!("p-value: {:.4}", p_values[i][j]));
+            }
+            let class = if value > 0.0 && value >= report.threshold { " class=\"strong\"" } else { "" };
+            html.push_str(&format!(
+                "<td{} style=\"background: rgb({}, {}, {})\" title=\"{}\">{:.2}</td>",
+                class,
+                r,
+                g,
+                b,
+                html_escape(&details.join("\n")),
+                value
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+
+    // Every document, in ranking order, with its keyword counts
+    html.push_str(&format!("<h2>Ranked Documents by {}</h2>\n<table>\n", report.ranking.title()));
+    html.push_str("<tr><th>#</th><th class=\"name\">Document</th><th>Score</th><th>Words</th>");
+    for keyword in &report.keywords {
+        html.push_str(&format!("<th>{}</th>", html_escape(keyword)));
+    }
+    html.push_str("</tr>\n");
+    for (i, doc) in report.documents.iter().enumerate() {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"name\" title=\"{}\">{}</td><td>{:.2}</td><td>{}</td>",
+            i + 1,
+            html_escape(&doc.path.display().to_string()),
+            html_escape(&doc.filename),
+            doc.score,
+            doc.word_count
+        ));
+        for keyword in &report.keywords {
+            html.push_str(&format!("<td>{}</td>", doc.count(keyword)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+
+    if !report.contexts.is_empty() {
+        html.push_str("<h2>Strongest Co-occurring Contexts</h2>\n<ol>\n");
+        for context in &report.contexts {
+            html.push_str(&format!(
+                "<li>{} (page {}) [{}] strength {}<blockquote>{}</blockquote></li>\n",
+                html_escape(&context.filename),
+                context.page + 1,
+                html_escape(&context.keywords.join(", ")),
+                context.strength,
+                html_escape(&context.snippet)
+            ));
+        }
+        html.push_str("</ol>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Escape characters that would break a Markdown table cell
+fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Markdown table of a keyword-by-keyword matrix
+fn markdown_matrix(keywords: &[String], matrix: &[Vec<f64>], precision: usize) -> String {
+    let mut table = String::from("| |");
+    for keyword in keywords {
+        table.push_str(&format!(" {} |", markdown_escape(keyword)));
+    }
+    table.push_str("\n|---|");
+    table.push_str(&"---:|".repeat(keywords.len()));
+    table.push('\n');
+
+    for (i, keyword) in keywords.iter().enumerate() {
+        table.push_str(&format!("| **{}** |", markdown_escape(keyword)));
+        for (j, value) in matrix[i].iter().enumerate() {
+            if i == j {
+                table.push_str(" — |");
+            } else {
+                table.push_str(&format!(" {:.*} |", precision, value));
+            }
+        }
+        table.push('\n');
+    }
+
+    table
+}
+
+fn markdown(report: &AnalysisReport) -> String {
+    let mut md = String::new();
+    md.push_str("# PDFScan Statistical Analysis Report\n\n");
+    md.push_str(&format!("- Keywords: {}\n", report.keywords.join(", ")));
+    md.push_str(&format!("- Total documents analyzed: {}\n", report.total_documents()));
+    md.push_str(&format!("- Co-occurrence window: {}\n", report.window));
+    md.push_str(&format!("- Correlation measure: {} ({})\n", report.measure, report.measure.scale()));
+    md.push_str(&format!("- Correlation threshold: {:.2}\n", report.threshold));
+    md.push_str(&format!("- Ranking: {}\n\n", report.ranking.title()));
+
+    md.push_str(&format!("## Keyword Correlation Matrix ({})\n\n", report.measure));
+    md.push_str(&markdown_matrix(&report.keywords, &report.correlations, 2));
+    if let Some(statistics) = &report.statistics {
+        md.push_str(&format!("\n### {}\n\n", statistic_title(report.measure)));
+        md.push_str(&markdown_matrix(&report.keywords, statistics, 3));
+    }
+    if let Some(p_values) = &report.p_values {
+        md.push_str("\n### p-values\n\n");
+        md.push_str(&markdown_matrix(&report.keywords, p_values, 4));
+    }
+
+    md.push_str(&format!("\n## Ranked Documents by {}\n\n| # | Document | Score | Words |", report.ranking.title()));
+    for keyword in &report.keywords {
+        md.push_str(&format!(" {} |", markdown_escape(keyword)));
+    }
+    md.push_str("\n|---:|---|---:|---:|");
+    md.push_str(&"---:|".repeat(report.keywords.len()));
+    md.push('\n');
+    for (i, doc) in report.documents.iter().enumerate() {
+        md.push_str(&format!(
+            "| {} | {} | {:.2} | {} |",
+            i + 1,
+            markdown_escape(&doc.filename),
+            doc.score,
+            doc.word_count
+        ));
+        for keyword in &report.keywords {
+            md.push_str(&format!(" {} |", doc.count(keyword)));
+        }
+        md.push('\n');
+    }
+
+    if !report.contexts.is_empty() {
+        md.push_str("\n## Strongest Co-occurring Contexts\n\n");
+        for (i, context) in report.contexts.iter().enumerate() {
+            md.push_str(&format!(
+                "{}. **{}** (page {}) [{}] strength {}\n\n   > {}\n\n",
+                i + 1,
+                context.filename,
+                context.page + 1,
+                context.keywords.join(", "),
+                context.strength,
+                context.snippet.replace('\n', " ")
+            ));
+        }
+    }
+
+    md
+}
+
+/// Quote and escape a JSON string
+pub(crate) fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// JSON number, or null for values JSON cannot represent
+fn json_number(value: f64) -> String {
+    if value.is_finite() { value.to_string() } else { "null".to_string() }
+}
+
+fn json_matrix(matrix: &[Vec<f64>]) -> String {
+    let rows: Vec<String> = matrix
+        .iter()
+        .map(|row| format!("[{}]", row.iter().map(|&v| json_number(v)).collect::<Vec<_>>().join(", ")))
+        .collect();
+    format!("[{}]", rows.join(", "))
+}
+
+fn json(report: &AnalysisReport) -> String {
+    let keywords: Vec<String> = report.keywords.iter().map(|k| json_string(k)).collect();
+    let optional_matrix = |matrix: &Option<Vec<Vec<f64>>>| match matrix {
+        Some(matrix) => json_matrix(matrix),
+        None => "null".to_string(),
+    };
+
+    let documents: Vec<String> = report.documents
+        .iter()
+        .map(|doc| {
+            let counts: Vec<String> = report.keywords
+                .iter()
+                .map(|keyword| format!("{}: {}", json_string(keyword), doc.count(keyword)))
+                .collect();
+            format!(
+                "    {{\"path\": {}, \"filename\": {}, \"score\": {}, \"word_count\": {}, \"counts\": {{{}}}}}",
+                json_string(&doc.path.display().to_string()),
+                json_string(&doc.filename),
+                json_number(doc.score),
+                doc.word_count,
+                counts.join(", ")
+            )
+        })
+        .collect();
+
+    let contexts: Vec<String> = report.contexts
+        .iter()
+        .map(|context| {
+            let keywords: Vec<String> = context.keywords.iter().map(|k| json_string(k)).collect();
+            format!(
+                "    {{\"path\": {}, \"filename\": {}, \"page\": {}, \"keywords\": [{}], \"strength\": {}, \"snippet\": {}}}",
+                json_string(&context.path.display().to_string()),
+                json_string(&context.filename),
+                context.page + 1,
+                keywords.join(", "),
+                context.strength,
+                json_string(&context.snippet)
+            )
+        })
+        .collect();
+
+    let mut json = String::from("{\n");
+    json.push_str(&format!("  \"keywords\": [{}],\n", keywords.join(", ")));
+    json.push_str(&format!("  \"total_documents\": {},\n", report.total_documents()));
+    json.push_str(&format!("  \"window\": {},\n", json_string(&report.window.to_string())));
+    json.push_str(&format!("  \"measure\": {},\n", json_string(report.measure.name())));
+    json.push_str(&format!("  \"threshold\": {},\n", json_number(report.threshold)));
+    json.push_str(&format!("  \"ranking\": {},\n", json_string(report.ranking.name())));
+    json.push_str(&format!("  \"correlations\": {},\n", json_matrix(&report.correlations)));
+    json.push_str(&format!("  \"statistics\": {},\n", optional_matrix(&report.statistics)));
+    json.push_str(&format!("  \"p_values\": {},\n", optional_matrix(&report.p_values)));
+    json.push_str(&format!("  \"documents\": [\n{}\n  ],\n", documents.join(",\n")));
+    json.push_str(&format!("  \"contexts\": [\n{}\n  ]\n", contexts.join(",\n")));
+    json.push_str("}\n");
+    json
+}
+
+fn csv(report: &AnalysisReport) -> String {
+    let mut header = vec!["rank".to_string(), "filename".to_string(), "path".to_string(), "score".to_string(), "words".to_string()];
+    header.extend(report.keywords.iter().map(|keyword| csv_field(keyword)));
+
+    let mut csv = header.join(",");
+    csv.push('\n');
+    for (i, doc) in report.documents.iter().enumerate() {
+        let mut row = vec![
+            (i + 1).to_string(),
+            csv_field(&doc.filename),
+            csv_field(&doc.path.display().to_string()),
+            format!("{:.4}", doc.score),
+            doc.word_count.to_string(),
+        ];
+        row.extend(report.keywords.iter().map(|keyword| doc.count(keyword).to_string()));
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
@@ -0,0 +1,227 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config::config_dir;
+use crate::search::{SearchMode, SearchOptions};
+
+/// Number of queries kept in the search history
+pub const MAX_HISTORY: usize = 50;
+
+/// What a search in the GUI looks through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    /// The document open in the viewer
+    #[default]
+    CurrentDocument,
+    /// Every PDF in a directory
+    Directory,
+}
+
+impl SearchScope {
+    pub const ALL: [SearchScope; 2] = [SearchScope::CurrentDocument, SearchScope::Directory];
+
+    /// Name used in the saved searches file
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchScope::CurrentDocument => "document",
+            SearchScope::Directory => "directory",
+        }
+    }
+}
+
+impl fmt::Display for SearchScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SearchScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "document" | "current" => Ok(SearchScope::CurrentDocument),
+            "directory" | "dir" => Ok(SearchScope::Directory),
+            _ => Err(format!(
+                "unknown search scope '{}' (expected one of: {})",
+                s,
+                SearchScope::ALL.iter().map(|scope| scope.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// A named search, runnable from the Search sidebar or with `pdfscan search --saved`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub scope: SearchScope,
+    /// Directory searched when the scope is a directory
+    pub directory: Option<PathBuf>,
+    pub options: SearchOptions,
+}
+
+impl SavedSearch {
+    /// One line of the saved searches file:
+    /// `name<TAB>mode<TAB>case|nocase<TAB>scope<TAB>directory<TAB>filter<TAB>query`
+    fn to_line(&self) -> String {
+        [
+            clean_field(&self.name),
+            self.options.mode.name().to_string(),
+            if self.options.case_sensitive { "case" } else { "nocase" }.to_string(),
+            self.scope.name().to_string(),
+            self.directory.as_ref().map(|dir| clean_field(&dir.to_string_lossy())).unwrap_or_default(),
+            clean_field(&self.options.filter),
+            clean_field(&self.query),
+        ]
+        .join("\t")
+    }
+
+    /// Summary of the query and options, e.g. `"cat", whole words, in /papers`
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("\"{}\"", self.query)];
+        if self.options.mode == SearchMode::WholeWord {
+            parts.push("whole words".to_string());
+        }
+        if self.options.case_sensitive {
+            parts.push("case sensitive".to_string());
+        }
+        match (&self.directory, self.scope) {
+            (Some(directory), SearchScope::Directory) => parts.push(format!("in {}", directory.display())),
+            _ => parts.push("in the current document".to_string()),
+        }
+        if !self.options.filter.is_empty() {
+            parts.push(format!("files matching \"{}\"", self.options.filter));
+        }
+        parts.join(", ")
+    }
+
+    fn from_line(line: &str) -> Option<SavedSearch> {
+        let mut parts = line.splitn(7, '\t');
+        let name = parts.next()?.to_string();
+        let mode = parts.next()?.parse().ok()?;
+        let case_sensitive = parts.next()? == "case";
+        let scope = parts.next()?.parse().ok()?;
+        let directory = Some(parts.next()?).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+        let filter = parts.next()?.to_string();
+        let query = parts.next()?.to_string();
+
+        Some(SavedSearch {
+            name,
+            query,
+            scope,
+            directory,
+            options: SearchOptions { mode, case_sensitive, filter },
+        })
+    }
+}
+
+/// Tabs and line breaks separate fields and entries, so they become spaces
+fn clean_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Parse the saved searches file, skipping lines that can't be read
+pub fn parse_saved_searches(content: &str) -> Vec<SavedSearch> {
+    content.lines().filter_map(SavedSearch::from_line).collect()
+}
+
+/// Render the saved searches file
+pub fn format_saved_searches(searches: &[SavedSearch]) -> String {
+    searches.iter().map(|search| search.to_line() + "\n").collect()
+}
+
+/// Read a file in the config directory; a missing file reads as empty
+fn read_config_file(name: &str) -> io::Result<String> {
+    match config_dir().map(|dir| dir.join(name)) {
+        Some(path) if path.exists() => fs::read_to_string(path),
+        _ => Ok(String::new()),
+    }
+}
+
+fn write_config_file(name: &str, content: &str) -> io::Result<()> {
+    let Some(dir) = config_dir() else {
+        return Ok(());
+    };
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(name), content)
+}
+
+/// Load the saved searches, in the order they were saved
+pub fn load_saved_searches() -> io::Result<Vec<SavedSearch>> {
+    read_config_file("saved_searches.txt").map(|content| parse_saved_searches(&content))
+}
+
+pub fn save_saved_searches(searches: &[SavedSearch]) -> io::Result<()> {
+    write_config_file("saved_searches.txt", &format_saved_searches(searches))
+}
+
+/// Load the search history, most recent query first
+pub fn load_history() -> io::Result<Vec<String>> {
+    read_config_file("search_history.txt")
+        .map(|content| content.lines().filter(|line| !line.is_empty()).map(String::from).collect())
+}
+
+/// Put a query at the front of the history, dropping an earlier copy and the oldest
+/// queries beyond `MAX_HISTORY`
+pub fn add_to_history(history: &mut Vec<String>, query: &str) {
+    let query = clean_field(query).trim().to_string();
+    if query.is_empty() {
+        return;
+    }
+
+    history.retain(|entry| *entry != query);
+    history.insert(0, query);
+    history.truncate(MAX_HISTORY);
+}
+
+pub fn save_history(history: &[String]) -> io::Result<()> {
+    write_config_file("search_history.txt", &history.join("\n"))
+}
+
+/// Look up a saved search by name
+pub fn find_saved_search(name: &str) -> Result<SavedSearch, Box<dyn Error>> {
+    let searches = load_saved_searches()?;
+    if let Some(search) = searches.iter().find(|search| search.name == name) {
+        return Ok(search.clone());
+    }
+
+    let names: Vec<&str> = searches.iter().map(|search| search.name.as_str()).collect();
+    Err(if names.is_empty() {
+        format!("No saved search named '{}'; there are no saved searches yet", name)
+    } else {
+        format!("No saved search named '{}' (saved searches: {})", name, names.join(", "))
+    }
+    .into())
+}
+
+/// Run a saved search over directories, by default the one it was saved with
+pub fn run(name: &str, directories: &[PathBuf], zip_output: bool) -> Result<(), Box<dyn Error>> {
+    let search = find_saved_search(name)?;
+
+    let directories = if !directories.is_empty() {
+        directories.to_vec()
+    } else {
+        match (&search.directory, search.scope) {
+            (Some(directory), SearchScope::Directory) => vec![directory.clone()],
+            _ => return Err(format!(
+                "Saved search '{}' searches the current document; give --directories to run it here",
+                name
+            ).into()),
+        }
+    };
+
+    println!(
+        "Running saved search '{}': \"{}\" ({}{})",
+        search.name,
+        search.query,
+        search.options.mode,
+        if search.options.case_sensitive { ", case sensitive" } else { "" }
+    );
+    crate::search::run(&search.query, &directories, zip_output, &search.options)
+}
@@ -5,6 +5,7 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
 use walkdir::WalkDir;
 use zip::write::FileOptions;
 use chrono;
@@ -44,18 +45,126 @@ impl From<zip::result::ZipError> for SearchError {
     }
 }
 
+/// How a search phrase is matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Anywhere in the text, also inside longer words
+    #[default]
+    Substring,
+    /// Only where it is not preceded or followed by a letter or digit
+    WholeWord,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 2] = [SearchMode::Substring, SearchMode::WholeWord];
+
+    /// Name used on the command line and in saved searches
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchMode::Substring => "substring",
+            SearchMode::WholeWord => "whole-word",
+        }
+    }
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "substring" | "phrase" => Ok(SearchMode::Substring),
+            "whole-word" | "word" => Ok(SearchMode::WholeWord),
+            _ => Err(format!(
+                "unknown search mode '{}' (expected one of: {})",
+                s,
+                SearchMode::ALL.iter().map(|m| m.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Options of a phrase search
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub case_sensitive: bool,
+    /// Only search files whose name contains this, ignoring case; empty for all files
+    pub filter: String,
+}
+
+impl SearchOptions {
+    /// Whether a file passes the file name filter
+    pub fn accepts(&self, path: &Path) -> bool {
+        self.filter.is_empty() || path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase()
+            .contains(&self.filter.to_lowercase())
+    }
+}
+
+/// Character offsets of the non-overlapping matches of a phrase in a text
+pub fn find_matches(text: &str, phrase: &str, options: &SearchOptions) -> Vec<usize> {
+    // Fold case one character at a time so offsets stay those of the original text
+    let fold = |c: char| {
+        if options.case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let text: Vec<char> = text.chars().map(fold).collect();
+    let phrase: Vec<char> = phrase.chars().map(fold).collect();
+
+    let mut matches = Vec::new();
+    if phrase.is_empty() {
+        return matches;
+    }
+
+    let mut start = 0;
+    while start + phrase.len() <= text.len() {
+        let end = start + phrase.len();
+        let bounded = options.mode != SearchMode::WholeWord
+            || ((start == 0 || !text[start - 1].is_alphanumeric())
+                && (end == text.len() || !text[end].is_alphanumeric()));
+
+        if bounded && text[start..end] == phrase[..] {
+            matches.push(start);
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+
+    matches
+}
+
 /// Main function to run the search functionality
-pub fn run(search_phrase: &str, directories: &[PathBuf], zip_output: bool) -> Result<(), Box<dyn Error>> {
-    let search_dirs = if directories.is_empty() {
-        // Use home directory as default if no directories provided
+pub fn run(
+    search_phrase: &str,
+    directories: &[PathBuf],
+    zip_output: bool,
+    options: &SearchOptions,
+) -> Result<(), Box<dyn Error>> {
+    let roots = &crate::config::get().search.roots;
+    let search_dirs = if !directories.is_empty() {
+        directories.to_vec()
+    } else if !roots.is_empty() {
+        roots.clone()
+    } else {
+        // Use home directory as default if no directories provided or configured
         match dirs::home_dir() {
             Some(home_dir) => vec![home_dir],
             None => return Err(Box::new(SearchError::OtherError(
                 "Unable to determine the user's home directory".to_string()
             ))),
         }
-    } else {
-        directories.to_vec()
     };
 
     // Validate all directories exist
@@ -68,7 +177,7 @@ pub fn run(search_phrase: &str, directories: &[PathBuf], zip_output: bool) -> Re
     }
 
     // Search for PDF files
-    let results = search_pdf_files(search_phrase, &search_dirs)?;
+    let results = search_pdf_files(search_phrase, &search_dirs, options)?;
     
     // Output results
     println!("\nFound {} matching PDF files:", results.len());
@@ -89,7 +198,7 @@ pub fn run(search_phrase: &str, directories: &[PathBuf], zip_output: bool) -> Re
 }
 
 /// Search for PDF files containing the given phrase
-fn search_pdf_files(search_phrase: &str, directories: &[PathBuf]) -> Result<Vec<String>, SearchError> {
+fn search_pdf_files(search_phrase: &str, directories: &[PathBuf], options: &SearchOptions) -> Result<Vec<String>, SearchError> {
     // Using Arc<Mutex<Vec<String>>> to safely share results between threads
     let results: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
     let mut handles = Vec::new();
@@ -98,10 +207,11 @@ fn search_pdf_files(search_phrase: &str, directories: &[PathBuf]) -> Result<Vec<
         let results_clone = results.clone();
         let search_phrase_clone = search_phrase.to_string();
         let directory_clone = directory.clone();
+        let options_clone = options.clone();
 
         handles.push(thread::spawn(move || {
             println!("Searching in: {}", directory_clone.display());
-            search_directory(&directory_clone, &search_phrase_clone, results_clone);
+            search_directory(&directory_clone, &search_phrase_clone, &options_clone, results_clone);
         }));
     }
 
@@ -120,13 +230,13 @@ fn search_pdf_files(search_phrase: &str, directories: &[PathBuf]) -> Result<Vec<
 }
 
 /// Search for PDFs in a single directory
-fn search_directory(dir: &PathBuf, search_phrase: &str, results: Arc<Mutex<Vec<String>>>) {
+fn search_directory(dir: &PathBuf, search_phrase: &str, options: &SearchOptions, results: Arc<Mutex<Vec<String>>>) {
     for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
         let path = entry.path();
 
         if path.is_file() {
             if let Some(extension) = path.extension() {
-                if extension == "pdf" {
+                if extension == "pdf" && options.accepts(path) {
                     let path_str = path.to_string_lossy().into_owned();
                     
                     // If no search phrase specified, include all PDFs
@@ -137,7 +247,7 @@ fn search_directory(dir: &PathBuf, search_phrase: &str, results: Arc<Mutex<Vec<S
                     }
 
                     // Check if PDF contains the search phrase
-                    match search_phrase_in_pdf(path, search_phrase) {
+                    match search_phrase_in_pdf(path, search_phrase, options) {
                         Ok(true) => {
                             let mut locked_results = results.lock().unwrap();
                             locked_results.push(path_str);
@@ -152,15 +262,14 @@ fn search_directory(dir: &PathBuf, search_phrase: &str, results: Arc<Mutex<Vec<S
 }
 
 /// Check if a PDF file contains the search phrase
-fn search_phrase_in_pdf(file_path: &Path, search_phrase: &str) -> Result<bool, SearchError> {
-    let bytes = std::fs::read(file_path)?;
-    
-    let text = pdf_extract::extract_text_from_mem(&bytes)
+fn search_phrase_in_pdf(file_path: &Path, search_phrase: &str, options: &SearchOptions) -> Result<bool, SearchError> {
+    let text = crate::cache::extract_record(file_path)
+        .map(|record| record.text())
         .map_err(|e| SearchError::PdfError(
             format!("Error extracting text from {}: {}", file_path.display(), e)
         ))?;
 
-    Ok(text.contains(search_phrase))
+    Ok(!find_matches(&text, search_phrase, options).is_empty())
 }
 
 /// Create a zip file containing the specified PDF files
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use rayon::prelude::*;
+use indicatif::ProgressBar;
+use sha2::{Digest, Sha256};
+
+use crate::stats::{collect_pdf_paths, StatsError};
+
+/// How near-duplicate text is detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarityMethod {
+    /// Estimated Jaccard similarity of word shingles
+    #[default]
+    MinHash,
+    /// Hamming similarity of 64-bit word SimHashes
+    SimHash,
+}
+
+impl SimilarityMethod {
+    pub const ALL: [SimilarityMethod; 2] = [SimilarityMethod::MinHash, SimilarityMethod::SimHash];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimilarityMethod::MinHash => "minhash",
+            SimilarityMethod::SimHash => "simhash",
+        }
+    }
+}
+
+impl fmt::Display for SimilarityMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SimilarityMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "minhash" => Ok(SimilarityMethod::MinHash),
+            "simhash" => Ok(SimilarityMethod::SimHash),
+            _ => Err(format!("unknown similarity method '{}' (expected minhash or simhash)", s)),
+        }
+    }
+}
+
+/// Settings of a duplicate search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DedupeOptions {
+    pub method: SimilarityMethod,
+    /// Documents at least this similar (0 to 1) are near-duplicates
+    pub threshold: f64,
+    /// Words per shingle for MinHash
+    pub shingle_size: usize,
+}
+
+impl Default for DedupeOptions {
+    fn default() -> Self {
+        Self {
+            method: SimilarityMethod::default(),
+            threshold: 0.8,
+            shingle_size: 5,
+        }
+    }
+}
+
+/// Number of MinHash values per document
+const MINHASH_SIZE: usize = 128;
+
+/// MinHash values are split into this many LSH bands; documents sharing a band
+/// are compared
+const LSH_BANDS: usize = 32;
+
+/// Signatures of one document
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    pub path: PathBuf,
+    /// Size and SHA-256 digest of the file bytes, equal only for identical files
+    pub size: u64,
+    pub content_hash: [u8; 32],
+    /// Empty if the document has no extractable text
+    pub minhash: Vec<u64>,
+    pub simhash: u64,
+}
+
+impl Fingerprint {
+    pub fn new(path: PathBuf, bytes: &[u8], text: &str, shingle_size: usize) -> Self {
+        let tokens = crate::text::tokenize(text, false);
+        let token_hashes: Vec<u64> = tokens.iter().map(|token| fnv1a(token.as_bytes())).collect();
+
+        // Word shingles; a text shorter than one shingle is a single shingle
+        let shingle_size = shingle_size.clamp(1, token_hashes.len().max(1));
+        let shingles: Vec<u64> = token_hashes
+            .windows(shingle_size)
+            .map(|window| window.iter().fold(FNV_OFFSET, |hash, &token| mix(hash ^ token)))
+            .collect();
+
+        let minhash = if shingles.is_empty() {
+            Vec::new()
+        } else {
+            (0..MINHASH_SIZE as u64)
+                .map(|i| {
+                    let seed = mix(i + 1);
+                    shingles.iter().map(|&shingle| mix(shingle ^ seed)).min().unwrap_or(u64::MAX)
+                })
+                .collect()
+        };
+
+        let mut weights = [0i64; 64];
+        for hash in &token_hashes {
+            for (bit, weight) in weights.iter_mut().enumerate() {
+                *weight += if (hash >> bit) & 1 == 1 { 1 } else { -1 };
+            }
+        }
+        let simhash = if token_hashes.is_empty() {
+            0
+        } else {
+            weights
+                .iter()
+                .enumerate()
+                .filter(|(_, &weight)| weight > 0)
+                .fold(0u64, |simhash, (bit, _)| simhash | (1 << bit))
+        };
+
+        Self {
+            path,
+            size: bytes.len() as u64,
+            content_hash: Sha256::digest(bytes).into(),
+            minhash,
+            simhash,
+        }
+    }
+
+    /// Whether the document has text to compare
+    pub fn has_text(&self) -> bool {
+        !self.minhash.is_empty()
+    }
+
+    /// Whether both files have identical bytes
+    pub fn is_exact_duplicate(&self, other: &Fingerprint) -> bool {
+        self.size == other.size && self.content_hash == other.content_hash
+    }
+
+    /// Text similarity from 0 to 1; 0 if either document has no text
+    pub fn similarity(&self, other: &Fingerprint, method: SimilarityMethod) -> f64 {
+        if !self.has_text() || !other.has_text() {
+            return 0.0;
+        }
+
+        match method {
+            SimilarityMethod::MinHash => {
+                let equal = self.minhash.iter().zip(&other.minhash).filter(|(a, b)| a == b).count();
+                equal as f64 / MINHASH_SIZE as f64
+            },
+            SimilarityMethod::SimHash => 1.0 - (self.simhash ^ other.simhash).count_ones() as f64 / 64.0,
+        }
+    }
+}
+
+/// One document of a duplicate cluster
+#[derive(Debug, Clone)]
+pub struct ClusterMember {
+    pub path: PathBuf,
+    /// Identical bytes to the first member
+    pub exact: bool,
+    /// Text similarity to the first member
+    pub similarity: f64,
+}
+
+/// A group of duplicate or near-duplicate documents
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    pub members: Vec<ClusterMember>,
+}
+
+impl Cluster {
+    /// Whether all members are byte-for-byte copies of each other
+    pub fn is_exact(&self) -> bool {
+        self.members.iter().skip(1).all(|member| member.exact)
+    }
+}
+
+/// Result of a duplicate search
+#[derive(Debug, Clone)]
+pub struct DedupeReport {
+    pub method: SimilarityMethod,
+    pub threshold: f64,
+    pub total_documents: usize,
+    /// Documents without extractable text, only compared by their bytes
+    pub without_text: usize,
+    /// Largest clusters first
+    pub clusters: Vec<Cluster>,
+}
+
+impl DedupeReport {
+    /// Render the plain text report written by `pdfscan dedupe`
+    pub fn to_text(&self) -> String {
+        let mut report = String::new();
+        report.push_str("PDFScan Duplicate Report\n");
+        report.push_str("================================\n\n");
+        report.push_str(&format!("Method: {}\n", self.method));
+        report.push_str(&format!("Similarity threshold: {:.2}\n", self.threshold));
+        report.push_str(&format!("Total documents analyzed: {}\n", self.total_documents));
+        if self.without_text > 0 {
+            report.push_str(&format!(
+                "Documents without extractable text (compared by bytes only): {}\n",
+                self.without_text
+            ));
+        }
+        report.push_str(&format!("Duplicate clusters: {}\n", self.clusters.len()));
+
+        for (i, cluster) in self.clusters.iter().enumerate() {
+            report.push_str(&format!(
+                "\nCluster {} ({} documents{})\n",
+                i + 1,
+                cluster.members.len(),
+                if cluster.is_exact() { ", identical files" } else { "" }
+            ));
+            for (j, member) in cluster.members.iter().enumerate() {
+                let note = if j == 0 {
+                    String::new()
+                } else if member.exact {
+                    "  (identical bytes)".to_string()
+                } else {
+                    format!("  (similarity {:.2})", member.similarity)
+                };
+                report.push_str(&format!("  {}{}\n", member.path.display(), note));
+            }
+        }
+
+        report
+    }
+}
+
+/// Group fingerprints into clusters of duplicates and near-duplicates
+pub fn cluster(fingerprints: &[Fingerprint], options: &DedupeOptions) -> DedupeReport {
+    let mut groups = UnionFind::new(fingerprints.len());
+
+    // Identical files
+    let mut by_content: HashMap<(u64, [u8; 32]), usize> = HashMap::new();
+    for (i, fingerprint) in fingerprints.iter().enumerate() {
+        match by_content.get(&(fingerprint.size, fingerprint.content_hash)) {
+            Some(&first) => groups.union(first, i),
+            None => {
+                by_content.insert((fingerprint.size, fingerprint.content_hash), i);
+            },
+        }
+    }
+
+    // Near-duplicate text
+    let with_text: Vec<usize> = (0..fingerprints.len()).filter(|&i| fingerprints[i].has_text()).collect();
+    let similar = |a: usize, b: usize| {
+        fingerprints[a].similarity(&fingerprints[b], options.method) >= options.threshold
+    };
+
+    match options.method {
+        SimilarityMethod::MinHash => {
+            // Only documents sharing an LSH band are compared
+            let rows = MINHASH_SIZE / LSH_BANDS;
+            for band in 0..LSH_BANDS {
+                let mut buckets: HashMap<&[u64], Vec<usize>> = HashMap::new();
+                for &i in &with_text {
+                    let key = &fingerprints[i].minhash[band * rows..(band + 1) * rows];
+                    buckets.entry(key).or_default().push(i);
+                }
+                for bucket in buckets.values().filter(|bucket| bucket.len() > 1) {
+                    for (n, &a) in bucket.iter().enumerate() {
+                        for &b in &bucket[n + 1..] {
+                            if groups.find(a) != groups.find(b) && similar(a, b) {
+                                groups.union(a, b);
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        SimilarityMethod::SimHash => {
+            for (n, &a) in with_text.iter().enumerate() {
+                for &b in &with_text[n + 1..] {
+                    if groups.find(a) != groups.find(b) && similar(a, b) {
+                        groups.union(a, b);
+                    }
+                }
+            }
+        },
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..fingerprints.len() {
+        members.entry(groups.find(i)).or_default().push(i);
+    }
+
+    let mut clusters: Vec<Cluster> = members
+        .into_values()
+        .filter(|indices| indices.len() > 1)
+        .map(|mut indices| {
+            indices.sort_by(|&a, &b| fingerprints[a].path.cmp(&fingerprints[b].path));
+            let first = &fingerprints[indices[0]];
+            Cluster {
+                members: indices
+                    .iter()
+                    .map(|&i| ClusterMember {
+                        path: fingerprints[i].path.clone(),
+                        exact: fingerprints[i].is_exact_duplicate(first),
+                        similarity: if fingerprints[i].is_exact_duplicate(first) {
+                            1.0
+                        } else {
+                            fingerprints[i].similarity(first, options.method)
+                        },
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.members.len().cmp(&a.members.len()).then_with(|| a.members[0].path.cmp(&b.members[0].path))
+    });
+
+    DedupeReport {
+        method: options.method,
+        threshold: options.threshold,
+        total_documents: fingerprints.len(),
+        without_text: fingerprints.iter().filter(|fingerprint| !fingerprint.has_text()).count(),
+        clusters,
+    }
+}
+
+/// Extract the text of a PDF file
+pub fn document_text(path: &Path) -> Result<String, StatsError> {
+    crate::cache::extract_record(path)
+        .map(|record| record.text())
+        .map_err(|e| StatsError::PdfError(format!("Error extracting text from {}: {}", path.display(), e)))
+}
+
+/// Find duplicate and near-duplicate PDF files in the given paths.
+///
+/// Once `cancel` is set the remaining files are skipped and an error is returned.
+pub fn dedupe(input_paths: &[String], options: &DedupeOptions, cancel: &AtomicBool) -> Result<DedupeReport, StatsError> {
+    let pdf_paths = collect_pdf_paths(input_paths)?;
+
+    if pdf_paths.is_empty() {
+        return Err(StatsError::OtherError(
+            "No PDF files found in the provided paths".to_string()
+        ));
+    }
+
+    let pb = ProgressBar::new(pdf_paths.len() as u64);
+    pb.set_message("Fingerprinting PDFs");
+
+    let fingerprints: Vec<Fingerprint> = pdf_paths.par_iter()
+        .filter_map(|path| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let bytes = match fs::read(path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    eprintln!("Error reading {}: {}", path.display(), e);
+                    pb.inc(1);
+                    return None;
+                }
+            };
+            // Scanned documents without a text layer are still compared by their bytes
+            let text = document_text(path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                String::new()
+            });
+
+            pb.inc(1);
+
+            Some(Fingerprint::new(path.clone(), &bytes, &text, options.shingle_size))
+        })
+        .collect();
+
+    if cancel.load(Ordering::Relaxed) {
+        pb.abandon_with_message("Fingerprinting cancelled");
+        return Err(StatsError::OtherError("Duplicate search cancelled".to_string()));
+    }
+
+    pb.finish_with_message("Fingerprinting complete");
+
+    Ok(cluster(&fingerprints, options))
+}
+
+/// Run the duplicate search, writing the report to a file or stdout
+pub fn run(input_paths: &[String], output_file: Option<&str>, options: &DedupeOptions) -> Result<(), Box<dyn Error>> {
+    let report = dedupe(input_paths, options, &AtomicBool::new(false))?;
+
+    match output_file {
+        Some(output_file) => {
+            fs::write(output_file, report.to_text())?;
+            println!("Successfully generated duplicate report in '{}'", output_file);
+        },
+        None => print!("{}", report.to_text()),
+    }
+
+    Ok(())
+}
+
+/// One line of a text diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Largest differing region, in lines of one text times lines of the other,
+/// that is diffed line by line; larger regions are shown as replaced wholesale
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line diff of two texts, based on their longest common subsequence
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().map(str::trim_end).collect();
+    let new: Vec<&str> = new.lines().map(str::trim_end).collect();
+
+    // Only the region between the common prefix and suffix needs the LCS table
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut diff: Vec<DiffLine> = old[..prefix].iter().map(|line| DiffLine::Same(line.to_string())).collect();
+
+    if a.len() * b.len() > MAX_DIFF_CELLS {
+        diff.extend(a.iter().map(|line| DiffLine::Removed(line.to_string())));
+        diff.extend(b.iter().map(|line| DiffLine::Added(line.to_string())));
+    } else {
+        // lcs[i * width + j] is the LCS length of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                diff.push(DiffLine::Same(a[i].to_string()));
+                i += 1;
+                j += 1;
+            } else if j == b.len() || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+                diff.push(DiffLine::Removed(a[i].to_string()));
+                i += 1;
+            } else {
+                diff.push(DiffLine::Added(b[j].to_string()));
+                j += 1;
+            }
+        }
+    }
+
+    diff.extend(old[old.len() - suffix..].iter().map(|line| DiffLine::Same(line.to_string())));
+    diff
+}
+
+/// Disjoint sets of document indices
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self { parents: (0..size).collect() }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // Path compression
+        let mut i = i;
+        while self.parents[i] != root {
+            let next = self.parents[i];
+            self.parents[i] = root;
+            i = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit FNV-1a hash, stable across runs and platforms
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// SplitMix64 finalizer, used to derive independent hash functions
+pub(crate) fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -3,10 +3,15 @@ use std::error::Error;
 use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use rayon::prelude::*;
 use walkdir::WalkDir;
 use indicatif::ProgressBar;
 
+use crate::cache::CacheRecord;
+use crate::report::ReportFormat;
+use crate::text::{KeywordMatcher, MatchOptions};
+
 /// Custom error type for statistical analysis operations
 #[derive(Debug)]
 pub enum StatsError {
@@ -33,234 +38,959 @@ impl From<std::io::Error> for StatsError {
     }
 }
 
-/// Represents a document with its keyword occurrences and correlation
-#[derive(Debug)]
-struct Document {
-    filename: String,
-    keyword_counts: HashMap<String, usize>,
-    correlation_score: f64,
+/// How keyword correlations are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorrelationMeasure {
+    /// Sum of `min(count1, count2)` per document divided by the document count (unbounded)
+    CoOccurrence,
+    /// Pearson correlation of per-document counts
+    #[default]
+    Pearson,
+    /// Spearman rank correlation of per-document counts
+    Spearman,
+    /// Jaccard index of the sets of documents containing each keyword
+    Jaccard,
+    /// Dice coefficient of the sets of documents containing each keyword
+    Dice,
+    /// Pointwise mutual information of keyword presence, thresholded as NPMI
+    Pmi,
+    /// Normalized pointwise mutual information of keyword presence
+    Npmi,
+    /// Chi-square test of independence of keyword presence
+    ChiSquare,
+}
+
+impl CorrelationMeasure {
+    pub const ALL: [CorrelationMeasure; 8] = [
+        CorrelationMeasure::CoOccurrence,
+        CorrelationMeasure::Pearson,
+        CorrelationMeasure::Spearman,
+        CorrelationMeasure::Jaccard,
+        CorrelationMeasure::Dice,
+        CorrelationMeasure::Pmi,
+        CorrelationMeasure::Npmi,
+        CorrelationMeasure::ChiSquare,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            CorrelationMeasure::CoOccurrence => "cooccurrence",
+            CorrelationMeasure::Pearson => "pearson",
+            CorrelationMeasure::Spearman => "spearman",
+            CorrelationMeasure::Jaccard => "jaccard",
+            CorrelationMeasure::Dice => "dice",
+            CorrelationMeasure::Pmi => "pmi",
+            CorrelationMeasure::Npmi => "npmi",
+            CorrelationMeasure::ChiSquare => "chi-square",
+        }
+    }
+
+    /// Description of the scale the threshold is applied on
+    pub fn scale(&self) -> &'static str {
+        match self {
+            CorrelationMeasure::CoOccurrence => "average co-occurrences per document",
+            CorrelationMeasure::Pearson | CorrelationMeasure::Spearman => "coefficient, -1 to 1",
+            CorrelationMeasure::Jaccard | CorrelationMeasure::Dice => "0 to 1",
+            CorrelationMeasure::Pmi | CorrelationMeasure::Npmi => "NPMI, -1 to 1",
+            CorrelationMeasure::ChiSquare => "phi coefficient, -1 to 1",
+        }
+    }
+}
+
+impl fmt::Display for CorrelationMeasure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for CorrelationMeasure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cooccurrence" | "co-occurrence" => Ok(CorrelationMeasure::CoOccurrence),
+            "pearson" => Ok(CorrelationMeasure::Pearson),
+            "spearman" => Ok(CorrelationMeasure::Spearman),
+            "jaccard" => Ok(CorrelationMeasure::Jaccard),
+            "dice" => Ok(CorrelationMeasure::Dice),
+            "pmi" => Ok(CorrelationMeasure::Pmi),
+            "npmi" => Ok(CorrelationMeasure::Npmi),
+            "chi-square" | "chisquare" | "chi2" => Ok(CorrelationMeasure::ChiSquare),
+            _ => Err(format!(
+                "unknown correlation measure '{}' (expected one of: {})",
+                s,
+                CorrelationMeasure::ALL.iter().map(|m| m.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// How documents are ranked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RankingMethod {
+    /// Sum of correlations above the threshold of keyword pairs that co-occur in the document
+    #[default]
+    Correlation,
+    /// TF-IDF with term frequency per 1,000 words
+    TfIdf,
+    /// Okapi BM25 (k1 = 1.2, b = 0.75)
+    Bm25,
+}
+
+impl RankingMethod {
+    pub const ALL: [RankingMethod; 3] = [
+        RankingMethod::Correlation,
+        RankingMethod::TfIdf,
+        RankingMethod::Bm25,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            RankingMethod::Correlation => "correlation",
+            RankingMethod::TfIdf => "tfidf",
+            RankingMethod::Bm25 => "bm25",
+        }
+    }
+
+    /// Heading of the ranked document list
+    pub fn title(&self) -> &'static str {
+        match self {
+            RankingMethod::Correlation => "Keyword Correlation",
+            RankingMethod::TfIdf => "TF-IDF",
+            RankingMethod::Bm25 => "BM25",
+        }
+    }
+}
+
+impl fmt::Display for RankingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for RankingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "correlation" => Ok(RankingMethod::Correlation),
+            "tfidf" | "tf-idf" => Ok(RankingMethod::TfIdf),
+            "bm25" => Ok(RankingMethod::Bm25),
+            _ => Err(format!("unknown ranking method '{}' (expected correlation, tfidf or bm25)", s)),
+        }
+    }
+}
+
+/// Text unit within which keywords count as co-occurring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CooccurrenceWindow {
+    #[default]
+    Document,
+    Sentence,
+    Paragraph,
+    Page,
+    /// Consecutive, non-overlapping runs of N words
+    Words(usize),
+}
+
+impl fmt::Display for CooccurrenceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CooccurrenceWindow::Document => f.write_str("document"),
+            CooccurrenceWindow::Sentence => f.write_str("sentence"),
+            CooccurrenceWindow::Paragraph => f.write_str("paragraph"),
+            CooccurrenceWindow::Page => f.write_str("page"),
+            CooccurrenceWindow::Words(n) => write!(f, "words:{}", n),
+        }
+    }
+}
+
+impl std::str::FromStr for CooccurrenceWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        if let Some(n) = s.strip_prefix("words:") {
+            return match n.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(CooccurrenceWindow::Words(n)),
+                _ => Err(format!("invalid word window size '{}'", n)),
+            };
+        }
+        
+        match s.as_str() {
+            "document" => Ok(CooccurrenceWindow::Document),
+            "sentence" => Ok(CooccurrenceWindow::Sentence),
+            "paragraph" => Ok(CooccurrenceWindow::Paragraph),
+            "page" => Ok(CooccurrenceWindow::Page),
+            _ => Err(format!(
+                "unknown co-occurrence window '{}' (expected document, sentence, paragraph, page or words:N)", s
+            )),
+        }
+    }
+}
+
+/// Settings of a keyword analysis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalysisOptions {
+    pub measure: CorrelationMeasure,
+    /// Correlation threshold on the measure's normalized scale
+    pub threshold: f64,
+    pub ranking: RankingMethod,
+    pub window: CooccurrenceWindow,
+    pub matching: MatchOptions,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        Self {
+            measure: CorrelationMeasure::default(),
+            threshold: 0.1,
+            ranking: RankingMethod::default(),
+            window: CooccurrenceWindow::default(),
+            matching: MatchOptions::default(),
+        }
+    }
+}
+
+/// A window in which several keywords occur together
+#[derive(Debug, Clone)]
+pub struct CooccurrenceContext {
+    pub path: PathBuf,
+    pub filename: String,
+    /// Page (0-based) the window starts on
+    pub page: usize,
+    /// Keywords found in the window
+    pub keywords: Vec<String>,
+    /// Sum of `min(count1, count2)` over the keyword pairs in the window
+    pub strength: usize,
+    pub snippet: String,
+}
+
+/// Keyword counts within the co-occurrence windows of a document
+#[derive(Debug, Clone, Default)]
+pub struct WindowCounts {
+    /// Counts per keyword (in analysis order) of each window containing a keyword
+    pub windows: Vec<Vec<usize>>,
+    /// Number of windows without any keyword
+    pub empty: usize,
+    /// Strongest windows with more than one keyword
+    pub contexts: Vec<CooccurrenceContext>,
+}
+
+/// Represents a document with its keyword occurrences and ranking score
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub path: PathBuf,
+    pub filename: String,
+    pub keyword_counts: HashMap<String, usize>,
+    /// Number of words in the document, used for length normalization
+    pub word_count: usize,
+    /// Per-window counts, when co-occurrence is measured below document level
+    pub windows: Option<WindowCounts>,
+    pub score: f64,
+}
+
+impl Document {
+    pub fn new(path: PathBuf, keyword_counts: HashMap<String, usize>, word_count: usize) -> Self {
+        let filename = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        Self {
+            path,
+            filename,
+            keyword_counts,
+            word_count,
+            windows: None,
+            score: 0.0,
+        }
+    }
+
+    /// Attach per-window keyword counts
+    pub fn with_windows(mut self, windows: WindowCounts) -> Self {
+        self.windows = Some(windows);
+        self
+    }
+
+    /// Number of occurrences of a keyword in this document
+    pub fn count(&self, keyword: &str) -> usize {
+        self.keyword_counts.get(keyword).copied().unwrap_or(0)
+    }
+}
+
+/// Result of a keyword analysis
+#[derive(Debug, Clone)]
+pub struct AnalysisReport {
+    /// Analyzed keywords; matrix indices refer to this order
+    pub keywords: Vec<String>,
+    pub measure: CorrelationMeasure,
+    /// Symmetric keyword correlation matrix on the measure's normalized scale (the diagonal is zero)
+    pub correlations: Vec<Vec<f64>>,
+    /// Raw statistic where it differs from the normalized value (PMI, chi-square)
+    pub statistics: Option<Vec<Vec<f64>>>,
+    /// p-values of the chi-square test
+    pub p_values: Option<Vec<Vec<f64>>>,
+    pub ranking: RankingMethod,
+    pub window: CooccurrenceWindow,
+    /// Documents with their keyword counts, ranked by score
+    pub documents: Vec<Document>,
+    /// Strongest co-occurrence windows across all documents
+    pub contexts: Vec<CooccurrenceContext>,
+    pub threshold: f64,
+}
+
+impl AnalysisReport {
+    pub fn total_documents(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Documents with a positive score, best first
+    pub fn ranked_documents(&self) -> impl Iterator<Item = &Document> {
+        self.documents.iter().filter(|doc| doc.score > 0.0)
+    }
+
+    /// Documents where both keywords occur, within one window when co-occurrence is
+    /// measured below document level, in ranking order
+    pub fn cooccurring_documents(&self, i: usize, j: usize) -> impl Iterator<Item = &Document> {
+        let (first, second) = (&self.keywords[i], &self.keywords[j]);
+        self.documents.iter().filter(move |doc| match &doc.windows {
+            Some(windows) => windows.windows.iter().any(|counts| counts[i] > 0 && counts[j] > 0),
+            None => doc.count(first) > 0 && doc.count(second) > 0,
+        })
+    }
+
+    /// Rank the documents again with another method
+    pub fn rerank(&mut self, ranking: RankingMethod) {
+        self.ranking = ranking;
+        score_documents(&mut self.documents, &self.keywords, &self.correlations, self.threshold, ranking);
+    }
+
+    /// Generate correlation matrix visualization (text-based)
+    pub fn correlation_matrix_text(&self) -> String {
+        let mut result = String::new();
+        
+        result.push_str(&format!("Keyword Correlation Matrix ({}):\n\n", self.measure));
+        result.push_str(&matrix_text(&self.keywords, &self.correlations));
+        
+        if let Some(statistics) = &self.statistics {
+            let title = match self.measure {
+                CorrelationMeasure::ChiSquare => "Chi-square Statistic",
+                _ => "PMI",
+            };
+            result.push_str(&format!("\n{}:\n\n", title));
+            result.push_str(&matrix_text(&self.keywords, statistics));
+        }
+        
+        if let Some(p_values) = &self.p_values {
+            result.push_str("\np-values:\n\n");
+            result.push_str(&matrix_text(&self.keywords, p_values));
+        }
+        
+        // Keyword index mapping
+        result.push_str("\nKeyword Index Mapping:\n");
+        for (i, keyword) in self.keywords.iter().enumerate() {
+            result.push_str(&format!("{}: {}\n", i, keyword));
+        }
+        
+        result
+    }
+
+    /// Render the plain text report written by `pdfscan analyze`
+    pub fn to_text(&self) -> String {
+        let mut report = String::new();
+        report.push_str("PDFScan Statistical Analysis Report\n");
+        report.push_str("================================\n\n");
+        report.push_str(&format!("Keywords: {}\n", self.keywords.join(", ")));
+        report.push_str(&format!("Total documents analyzed: {}\n", self.total_documents()));
+        report.push_str(&format!("Co-occurrence window: {}\n", self.window));
+        report.push_str(&format!("Correlation measure: {} ({})\n", self.measure, self.measure.scale()));
+        report.push_str(&format!("Correlation threshold: {:.2}\n", self.threshold));
+        report.push_str(&format!("Ranking: {}\n\n", self.ranking));
+        
+        report.push_str(&self.correlation_matrix_text());
+        report.push_str(&format!("\n\nRanked Documents by {}:\n", self.ranking.title()));
+        report.push_str("==========================================\n");
+        
+        for (i, doc) in self.ranked_documents().enumerate().take(20) {
+            report.push_str(&format!("{}. {} (score: {:.2})\n", i+1, doc.filename, doc.score));
+        }
+        
+        if !self.contexts.is_empty() {
+            report.push_str("\n\nStrongest Co-occurring Contexts:\n");
+            report.push_str("==========================================\n");
+            
+            for (i, context) in self.contexts.iter().enumerate() {
+                report.push_str(&format!(
+                    "{}. {} (page {}) [{}] strength {}\n   \"{}\"\n",
+                    i + 1,
+                    context.filename,
+                    context.page + 1,
+                    context.keywords.join(", "),
+                    context.strength,
+                    context.snippet
+                ));
+            }
+        }
+        
+        report
+    }
+}
+
+/// Render one keyword-by-keyword matrix with index headers
+fn matrix_text(keywords: &[String], matrix: &[Vec<f64>]) -> String {
+    let mut result = String::new();
+    
+    // Header with keyword indices
+    result.push_str("    ");
+    for i in 0..keywords.len() {
+        result.push_str(&format!("{:<4}", i));
+    }
+    result.push('\n');
+    
+    // Matrix values
+    for (i, row) in matrix.iter().enumerate().take(keywords.len()) {
+        result.push_str(&format!("{:<3} ", i));
+        for (j, value) in row.iter().enumerate().take(keywords.len()) {
+            if i == j {
+                result.push_str("---- ");
+            } else {
+                result.push_str(&format!("{:.2} ", value));
+            }
+        }
+        result.push('\n');
+    }
+    
+    result
+}
+
+/// Correlation of one keyword pair
+struct PairCorrelation {
+    /// Value on the measure's normalized scale, compared against the threshold
+    value: f64,
+    statistic: Option<f64>,
+    p_value: Option<f64>,
 }
 
-/// Represents a keyword analysis result
+impl PairCorrelation {
+    fn value(value: f64) -> Self {
+        Self { value, statistic: None, p_value: None }
+    }
+}
+
+/// Computes keyword correlations and document ranking
 #[derive(Debug)]
-pub struct KeywordAnalysis {
+struct KeywordAnalysis {
     keywords: Vec<String>,
+    measure: CorrelationMeasure,
+    window: CooccurrenceWindow,
     documents: Vec<Document>,
     correlations: Vec<Vec<f64>>,
-    total_documents: usize,
+    statistics: Option<Vec<Vec<f64>>>,
+    p_values: Option<Vec<Vec<f64>>>,
 }
 
 impl KeywordAnalysis {
-    fn new(keywords: Vec<String>) -> Self {
+    fn new(keywords: Vec<String>, measure: CorrelationMeasure, window: CooccurrenceWindow) -> Self {
         let keyword_count = keywords.len();
         Self {
             keywords,
+            measure,
+            window,
             documents: Vec::new(),
             correlations: vec![vec![0.0; keyword_count]; keyword_count],
-            total_documents: 0,
+            statistics: None,
+            p_values: None,
         }
     }
 
     /// Calculate keyword correlations across all documents
     fn calculate_correlations(&mut self) {
-        // Reset correlations
         let keyword_count = self.keywords.len();
         self.correlations = vec![vec![0.0; keyword_count]; keyword_count];
+        let mut statistics = vec![vec![0.0; keyword_count]; keyword_count];
+        let mut p_values = vec![vec![0.0; keyword_count]; keyword_count];
         
-        // Calculate co-occurrences
-        for doc in &self.documents {
-            for (i, k1) in self.keywords.iter().enumerate() {
-                let count1 = *doc.keyword_counts.get(k1).unwrap_or(&0);
-                if count1 > 0 {
-                    for (j, k2) in self.keywords.iter().enumerate().skip(i + 1) {
-                        let count2 = *doc.keyword_counts.get(k2).unwrap_or(&0);
-                        if count2 > 0 {
-                            // Use minimum of the two counts as correlation strength
-                            let min_count = std::cmp::min(count1, count2) as f64;
-                            self.correlations[i][j] += min_count;
-                        }
-                    }
-                }
-            }
-        }
+        let counts = self.observations();
         
-        // Normalize correlations by document count
-        if !self.documents.is_empty() {
-            let doc_count = self.documents.len() as f64;
-            for i in 0..keyword_count {
-                for j in (i + 1)..keyword_count {
-                    self.correlations[i][j] /= doc_count;
+        for i in 0..keyword_count {
+            for j in (i + 1)..keyword_count {
+                let pair = self.pair_correlation(&counts[i], &counts[j]);
+                
+                self.correlations[i][j] = pair.value;
+                self.correlations[j][i] = pair.value;
+                if let Some(statistic) = pair.statistic {
+                    statistics[i][j] = statistic;
+                    statistics[j][i] = statistic;
+                }
+                if let Some(p_value) = pair.p_value {
+                    p_values[i][j] = p_value;
+                    p_values[j][i] = p_value;
                 }
             }
         }
+        
+        self.statistics = match self.measure {
+            CorrelationMeasure::Pmi | CorrelationMeasure::ChiSquare => Some(statistics),
+            _ => None,
+        };
+        self.p_values = match self.measure {
+            CorrelationMeasure::ChiSquare => Some(p_values),
+            _ => None,
+        };
     }
 
-    /// Rank documents based on keyword correlations
-    fn rank_documents(&mut self, threshold: f64) -> Vec<(String, f64)> {
-        // Calculate weights for documents based on correlations
-        for doc in &mut self.documents {
-            let mut score = 0.0;
-            
-            for (i, k1) in self.keywords.iter().enumerate() {
-                let count1 = *doc.keyword_counts.get(k1).unwrap_or(&0);
-                if count1 > 0 {
-                    for (j, k2) in self.keywords.iter().enumerate().skip(i + 1) {
-                        let count2 = *doc.keyword_counts.get(k2).unwrap_or(&0);
-                        if count2 > 0 && self.correlations[i][j] >= threshold {
-                            // Add correlation score to document weight
-                            let correlation_strength = self.correlations[i][j];
-                            score += correlation_strength * (count1.min(count2) as f64);
+    /// Per-keyword count vectors over the observed units (documents or windows)
+    fn observations(&self) -> Vec<Vec<f64>> {
+        let mut counts: Vec<Vec<f64>> = vec![Vec::new(); self.keywords.len()];
+        
+        for doc in &self.documents {
+            match (&doc.windows, self.window) {
+                (Some(windows), window) if window != CooccurrenceWindow::Document => {
+                    for window_counts in &windows.windows {
+                        for (i, count) in window_counts.iter().enumerate().take(counts.len()) {
+                            counts[i].push(*count as f64);
                         }
                     }
-                }
+                    for keyword_counts in counts.iter_mut() {
+                        keyword_counts.extend(std::iter::repeat_n(0.0, windows.empty));
+                    }
+                },
+                _ => {
+                    for (i, keyword) in self.keywords.iter().enumerate() {
+                        counts[i].push(doc.count(keyword) as f64);
+                    }
+                },
             }
-            
-            doc.correlation_score = score;
         }
         
-        // Sort documents by score
-        let mut ranked_docs = self.documents.iter()
-            .map(|doc| (doc.filename.clone(), doc.correlation_score))
-            .collect::<Vec<_>>();
-        
-        ranked_docs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        ranked_docs
+        counts
     }
 
-    /// Generate correlation matrix visualization (text-based)
-    fn generate_correlation_matrix(&self) -> String {
-        let mut result = String::new();
+    /// Correlation of two keywords given their counts per observed unit
+    fn pair_correlation(&self, a: &[f64], b: &[f64]) -> PairCorrelation {
+        let n = a.len() as f64;
         
-        // Header with keyword indices
-        result.push_str("Keyword Correlation Matrix:\n\n");
-        result.push_str("    ");
-        for i in 0..self.keywords.len() {
-            result.push_str(&format!("{:<4}", i));
+        // 2x2 contingency table of keyword presence
+        let (mut n11, mut n10, mut n01, mut n00) = (0.0, 0.0, 0.0, 0.0);
+        for (&x, &y) in a.iter().zip(b) {
+            match (x > 0.0, y > 0.0) {
+                (true, true) => n11 += 1.0,
+                (true, false) => n10 += 1.0,
+                (false, true) => n01 += 1.0,
+                (false, false) => n00 += 1.0,
+            }
         }
-        result.push('\n');
         
-        // Correlation values
-        for i in 0..self.keywords.len() {
-            result.push_str(&format!("{:<3} ", i));
-            for j in 0..self.keywords.len() {
-                if i < j {
-                    result.push_str(&format!("{:.2} ", self.correlations[i][j]));
-                } else if i > j {
-                    result.push_str(&format!("{:.2} ", self.correlations[j][i]));
+        match self.measure {
+            CorrelationMeasure::CoOccurrence => {
+                // Use minimum of the two counts as correlation strength
+                let sum: f64 = a.iter().zip(b).map(|(x, y)| x.min(*y)).sum();
+                PairCorrelation::value(sum / n.max(1.0))
+            },
+            CorrelationMeasure::Pearson => PairCorrelation::value(pearson(a, b)),
+            CorrelationMeasure::Spearman => PairCorrelation::value(pearson(&ranks(a), &ranks(b))),
+            CorrelationMeasure::Jaccard => {
+                let union = n11 + n10 + n01;
+                PairCorrelation::value(if union > 0.0 { n11 / union } else { 0.0 })
+            },
+            CorrelationMeasure::Dice => {
+                let total = 2.0 * n11 + n10 + n01;
+                PairCorrelation::value(if total > 0.0 { 2.0 * n11 / total } else { 0.0 })
+            },
+            CorrelationMeasure::Pmi | CorrelationMeasure::Npmi => {
+                let (pmi, npmi) = pmi(n11, n10, n01, n);
+                let statistic = if self.measure == CorrelationMeasure::Pmi { Some(pmi) } else { None };
+                PairCorrelation { value: npmi, statistic, p_value: None }
+            },
+            CorrelationMeasure::ChiSquare => {
+                let chi2 = chi_square(n11, n10, n01, n00);
+                let p_value = chi_square_p_value(chi2);
+                // Phi coefficient: the strength of the association, signed by its direction;
+                // significance is reported separately as the p-value
+                let sign = if n11 * n00 >= n10 * n01 { 1.0 } else { -1.0 };
+                let phi = if n > 0.0 { (chi2 / n).sqrt() } else { 0.0 };
+                PairCorrelation {
+                    value: sign * phi,
+                    statistic: Some(chi2),
+                    p_value: Some(p_value),
+                }
+            },
+        }
+    }
+
+    /// Rank documents with the chosen method
+    fn rank_documents(&mut self, threshold: f64, ranking: RankingMethod) {
+        score_documents(&mut self.documents, &self.keywords, &self.correlations, threshold, ranking);
+    }
+
+    fn into_report(self, threshold: f64, ranking: RankingMethod) -> AnalysisReport {
+        // Strongest contexts over all documents
+        let mut contexts: Vec<CooccurrenceContext> = self.documents
+            .iter()
+            .filter_map(|doc| doc.windows.as_ref())
+            .flat_map(|windows| windows.contexts.iter().cloned())
+            .collect();
+        contexts.sort_by_key(|context| std::cmp::Reverse(context.strength));
+        contexts.truncate(MAX_CONTEXTS);
+        
+        AnalysisReport {
+            window: self.window,
+            contexts,
+            keywords: self.keywords,
+            measure: self.measure,
+            correlations: self.correlations,
+            statistics: self.statistics,
+            p_values: self.p_values,
+            ranking,
+            documents: self.documents,
+            threshold,
+        }
+    }
+}
+
+/// Score and sort documents, best first
+fn score_documents(
+    documents: &mut [Document],
+    keywords: &[String],
+    correlations: &[Vec<f64>],
+    threshold: f64,
+    ranking: RankingMethod,
+) {
+    let doc_count = documents.len() as f64;
+    
+    // Number of documents containing each keyword
+    let document_frequencies: Vec<f64> = keywords
+        .iter()
+        .map(|k| documents.iter().filter(|doc| doc.count(k) > 0).count() as f64)
+        .collect();
+    
+    // Average length for BM25; documents without a word count aren't length normalized
+    let average_length = if doc_count > 0.0 {
+        documents.iter().map(|doc| doc.word_count as f64).sum::<f64>() / doc_count
+    } else {
+        0.0
+    };
+    
+    for doc in documents.iter_mut() {
+        doc.score = match ranking {
+            RankingMethod::Correlation => correlation_score(doc, keywords, correlations, threshold),
+            RankingMethod::TfIdf => keywords
+                .iter()
+                .zip(&document_frequencies)
+                .map(|(k, &df)| {
+                    // Smoothed IDF stays positive for keywords found in every document
+                    let idf = ((1.0 + doc_count) / (1.0 + df)).ln() + 1.0;
+                    let count = doc.count(k) as f64;
+                    let tf = if doc.word_count > 0 {
+                        count * 1000.0 / doc.word_count as f64
+                    } else {
+                        count
+                    };
+                    tf * idf
+                })
+                .sum(),
+            RankingMethod::Bm25 => {
+                const K1: f64 = 1.2;
+                const B: f64 = 0.75;
+                
+                let length_ratio = if average_length > 0.0 && doc.word_count > 0 {
+                    doc.word_count as f64 / average_length
                 } else {
-                    result.push_str(&format!("---- "));
+                    1.0
+                };
+                
+                keywords
+                    .iter()
+                    .zip(&document_frequencies)
+                    .map(|(k, &df)| {
+                        let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                        let tf = doc.count(k) as f64;
+                        idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length_ratio))
+                    })
+                    .sum()
+            },
+        };
+    }
+    
+    // Sort documents by score
+    documents.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+/// Sum of correlations of keyword pairs co-occurring in the document, weighted by the smaller count
+fn correlation_score(doc: &Document, keywords: &[String], correlations: &[Vec<f64>], threshold: f64) -> f64 {
+    let mut score = 0.0;
+    
+    for (i, k1) in keywords.iter().enumerate() {
+        let count1 = doc.count(k1);
+        if count1 > 0 {
+            for (j, k2) in keywords.iter().enumerate().skip(i + 1) {
+                let count2 = doc.count(k2);
+                if count2 > 0 && correlations[i][j] > 0.0 && correlations[i][j] >= threshold {
+                    // Add correlation score to document weight
+                    let correlation_strength = correlations[i][j];
+                    score += correlation_strength * (count1.min(count2) as f64);
                 }
             }
-            result.push('\n');
         }
-        
-        // Keyword index mapping
-        result.push_str("\nKeyword Index Mapping:\n");
-        for (i, keyword) in self.keywords.iter().enumerate() {
-            result.push_str(&format!("{}: {}\n", i, keyword));
+    }
+    
+    score
+}
+
+/// Pearson correlation coefficient; 0 when either variable is constant
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    if n == 0.0 {
+        return 0.0;
+    }
+    
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+    
+    if variance_a == 0.0 || variance_b == 0.0 {
+        0.0
+    } else {
+        covariance / (variance_a * variance_b).sqrt()
+    }
+}
+
+/// Ranks of the values (1-based), averaging ties
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(std::cmp::Ordering::Equal));
+    
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
         }
-        
-        result
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        for &index in &order[start..=end] {
+            ranks[index] = rank;
+        }
+        start = end + 1;
     }
+    
+    ranks
 }
 
-/// Run statistical analysis on PDF files
-pub fn run(
+/// PMI and NPMI of keyword presence from the contingency counts
+fn pmi(n11: f64, n10: f64, n01: f64, n: f64) -> (f64, f64) {
+    if n11 == 0.0 || n == 0.0 {
+        // Never seen together
+        return (f64::NEG_INFINITY, -1.0);
+    }
+    
+    let p_xy = n11 / n;
+    let p_x = (n11 + n10) / n;
+    let p_y = (n11 + n01) / n;
+    let pmi = (p_xy / (p_x * p_y)).ln();
+    
+    // Both keywords occur in every document
+    if p_xy >= 1.0 {
+        return (pmi, 1.0);
+    }
+    
+    (pmi, pmi / -p_xy.ln())
+}
+
+/// Pearson's chi-square statistic of a 2x2 contingency table
+fn chi_square(n11: f64, n10: f64, n01: f64, n00: f64) -> f64 {
+    let n = n11 + n10 + n01 + n00;
+    let denominator = (n11 + n10) * (n01 + n00) * (n11 + n01) * (n10 + n00);
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    
+    n * (n11 * n00 - n10 * n01).powi(2) / denominator
+}
+
+/// p-value of a chi-square statistic with one degree of freedom
+fn chi_square_p_value(chi2: f64) -> f64 {
+    erfc((chi2 / 2.0).sqrt()).clamp(0.0, 1.0)
+}
+
+/// Complementary error function (Numerical Recipes `erfcc`, relative error below 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let result = t * (-z * z - 1.26551223
+        + t * (1.00002368
+        + t * (0.37409196
+        + t * (0.09678418
+        + t * (-0.18628806
+        + t * (0.27886807
+        + t * (-1.13520398
+        + t * (1.48851587
+        + t * (-0.82215223
+        + t * 0.17087277))))))))).exp();
+    
+    if x >= 0.0 { result } else { 2.0 - result }
+}
+
+/// Analyze keyword correlations from already counted keyword occurrences
+pub fn analyze_counts(
+    documents: Vec<Document>,
+    keywords: &[String],
+    options: &AnalysisOptions,
+) -> AnalysisReport {
+    let mut analysis = KeywordAnalysis::new(keywords.to_vec(), options.measure, options.window);
+    analysis.documents = documents;
+    
+    analysis.calculate_correlations();
+    analysis.rank_documents(options.threshold, options.ranking);
+    analysis.into_report(options.threshold, options.ranking)
+}
+
+/// Progress of an analysis, reported after each PDF file
+#[derive(Debug, Clone)]
+pub struct AnalysisProgress {
+    /// Files processed so far
+    pub done: usize,
+    pub total: usize,
+    /// The file just processed
+    pub path: PathBuf,
+    /// Why the file could not be read, if it couldn't; it then counts as empty
+    pub error: Option<String>,
+}
+
+/// Analyze keyword correlations in PDF files
+pub fn analyze(
     input_paths: &[String],
     keywords: &[String],
-    output_file: &str,
-    correlation_threshold: f64,
-) -> Result<(), Box<dyn Error>> {
+    options: &AnalysisOptions,
+) -> Result<AnalysisReport, StatsError> {
+    let pb = ProgressBar::new(0);
+    pb.set_message("Analyzing PDFs");
+    
+    let report = analyze_with_progress(input_paths, keywords, options, &|progress| {
+        if let Some(e) = &progress.error {
+            eprintln!("Error processing {}: {}", progress.path.display(), e);
+        }
+        pb.set_length(progress.total as u64);
+        pb.inc(1);
+    }, &AtomicBool::new(false))?;
+    
+    pb.finish_with_message("Analysis complete");
+    
+    Ok(report)
+}
+
+/// Analyze keyword correlations in PDF files, calling `progress` after each file.
+///
+/// Once `cancel` is set the remaining files are skipped and the report covers only
+/// the files processed so far; it is an error if there are none.
+pub fn analyze_with_progress(
+    input_paths: &[String],
+    keywords: &[String],
+    options: &AnalysisOptions,
+    progress: &(dyn Fn(AnalysisProgress) + Sync),
+    cancel: &AtomicBool,
+) -> Result<AnalysisReport, StatsError> {
     if keywords.is_empty() {
-        return Err(Box::new(StatsError::OtherError(
+        return Err(StatsError::OtherError(
             "No keywords provided for analysis".to_string()
-        )));
+        ));
     }
     
     // Collect PDF paths
     let pdf_paths = collect_pdf_paths(input_paths)?;
     
     if pdf_paths.is_empty() {
-        return Err(Box::new(StatsError::OtherError(
+        return Err(StatsError::OtherError(
             "No PDF files found in the provided paths".to_string()
-        )));
+        ));
     }
     
-    // Create progress bar
-    let pb = ProgressBar::new(pdf_paths.len() as u64);
-    pb.set_message("Analyzing PDFs");
-    
-    // Initialize keyword analysis
-    let mut analysis = KeywordAnalysis::new(keywords.iter().map(|s| s.to_string()).collect());
+    let matcher = KeywordMatcher::new(keywords, options.matching);
+    let done = AtomicUsize::new(0);
     
     // Process PDFs in parallel
     let documents: Vec<Document> = pdf_paths.par_iter()
-        .map(|path| {
-            let filename = path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string();
+        .filter_map(|path| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
             
-            let keyword_counts = match extract_keyword_counts(path, keywords) {
-                Ok(counts) => counts,
-                Err(e) => {
-                    eprintln!("Error processing {}: {}", filename, e);
-                    HashMap::new()
-                }
+            let (document, error) = match extract_keyword_counts(path, &matcher, options.window) {
+                Ok(document) => (document, None),
+                Err(e) => (Document::new(path.clone(), HashMap::new(), 0), Some(e.to_string())),
             };
             
-            pb.inc(1);
+            progress(AnalysisProgress {
+                done: done.fetch_add(1, Ordering::Relaxed) + 1,
+                total: pdf_paths.len(),
+                path: path.clone(),
+                error,
+            });
             
-            Document {
-                filename,
-                keyword_counts,
-                correlation_score: 0.0,
-            }
+            Some(document)
         })
         .collect();
     
-    pb.finish_with_message("Analysis complete");
-    
-    // Update analysis with documents
-    analysis.documents = documents;
-    analysis.total_documents = analysis.documents.len();
-    
-    // Calculate correlations
-    analysis.calculate_correlations();
-    
-    // Rank documents
-    let ranked_docs = analysis.rank_documents(correlation_threshold);
-    
-    // Generate report
-    let correlation_matrix = analysis.generate_correlation_matrix();
-    
-    // Create report
-    let mut report = String::new();
-    report.push_str(&format!("PDFScan Statistical Analysis Report\n"));
-    report.push_str(&format!("================================\n\n"));
-    report.push_str(&format!("Keywords: {}\n", keywords.join(", ")));
-    report.push_str(&format!("Total documents analyzed: {}\n", analysis.total_documents));
-    report.push_str(&format!("Correlation threshold: {:.2}\n\n", correlation_threshold));
-    
-    report.push_str(&correlation_matrix);
-    report.push_str("\n\nRanked Documents by Keyword Correlation:\n");
-    report.push_str("==========================================\n");
+    if documents.is_empty() {
+        return Err(StatsError::OtherError("Analysis cancelled".to_string()));
+    }
     
-    for (i, (filename, score)) in ranked_docs.iter().enumerate().take(20) {
-        if *score > 0.0 {
-            report.push_str(&format!("{}. {} (score: {:.2})\n", i+1, filename, score));
-        }
+    Ok(analyze_counts(documents, keywords, options))
+}
+
+/// Analyze keyword correlations in already extracted PDF files
+pub fn analyze_records(
+    records: &[(PathBuf, CacheRecord)],
+    keywords: &[String],
+    options: &AnalysisOptions,
+) -> Result<AnalysisReport, StatsError> {
+    if keywords.is_empty() {
+        return Err(StatsError::OtherError(
+            "No keywords provided for analysis".to_string()
+        ));
     }
     
-    // Write to output file
-    fs::write(output_file, report)?;
+    let matcher = KeywordMatcher::new(keywords, options.matching);
+    let documents = records.par_iter()
+        .map(|(path, record)| count_keywords(path, record, &matcher, options.window))
+        .collect();
+    
+    Ok(analyze_counts(documents, keywords, options))
+}
+
+/// Run statistical analysis on PDF files, writing the report in the given format
+pub fn run(
+    input_paths: &[String],
+    keywords: &[String],
+    output_file: &str,
+    format: ReportFormat,
+    options: &AnalysisOptions,
+) -> Result<(), Box<dyn Error>> {
+    let report = analyze(input_paths, keywords, options)?;
+    write_report(&report, output_file, format)
+}
+
+/// Write an analysis report in the given format
+pub fn write_report(report: &AnalysisReport, output_file: &str, format: ReportFormat) -> Result<(), Box<dyn Error>> {
+    fs::write(output_file, crate::report::render(report, format))?;
     
     println!("Successfully generated statistical analysis report in '{}'", output_file);
     Ok(())
 }
 
 /// Collect all PDF file paths from the provided input paths
-fn collect_pdf_paths(input_paths: &[String]) -> Result<Vec<PathBuf>, StatsError> {
+pub fn collect_pdf_paths(input_paths: &[String]) -> Result<Vec<PathBuf>, StatsError> {
     let mut pdf_paths: Vec<PathBuf> = Vec::new();
     
     for path in input_paths {
@@ -285,21 +1015,242 @@ fn collect_pdf_paths(input_paths: &[String]) -> Result<Vec<PathBuf>, StatsError>
     Ok(pdf_paths)
 }
 
-/// Extract keyword counts from a PDF file
-fn extract_keyword_counts(path: &Path, keywords: &[String]) -> Result<HashMap<String, usize>, StatsError> {
-    let bytes = fs::read(path)?;
+/// Extract the text of every PDF file in the provided input paths.
+///
+/// Files whose text cannot be extracted are reported and kept with empty text.
+/// Once `cancel` is set the remaining files are skipped and an error is returned.
+pub fn extract_texts(input_paths: &[String], cancel: &AtomicBool) -> Result<Vec<(PathBuf, String)>, StatsError> {
+    let records = extract_records(input_paths, cancel)?;
+    Ok(records.into_iter().map(|(path, record)| (path, record.text())).collect())
+}
+
+/// Extract the record of every PDF file in the provided input paths, like `extract_texts`
+pub fn extract_records(input_paths: &[String], cancel: &AtomicBool) -> Result<Vec<(PathBuf, CacheRecord)>, StatsError> {
+    let pdf_paths = collect_pdf_paths(input_paths)?;
+
+    if pdf_paths.is_empty() {
+        return Err(StatsError::OtherError(
+            "No PDF files found in the provided paths".to_string()
+        ));
+    }
+
+    let pb = ProgressBar::new(pdf_paths.len() as u64);
+    pb.set_message("Extracting text");
+
+    let records = pdf_paths.par_iter()
+        .filter_map(|path| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let record = crate::cache::extract_record(path).unwrap_or_else(|e| {
+                eprintln!("Error processing {}: {}", path.display(), e);
+                CacheRecord::default()
+            });
+
+            pb.inc(1);
+
+            Some((path.clone(), record))
+        })
+        .collect();
+
+    if cancel.load(Ordering::Relaxed) {
+        pb.abandon_with_message("Text extraction cancelled");
+        return Err(StatsError::OtherError("Text extraction cancelled".to_string()));
+    }
+
+    pb.finish_with_message("Text extraction complete");
+
+    Ok(records)
+}
+
+/// Extract keyword counts and the word count from a PDF file
+pub fn extract_keyword_counts(path: &Path, matcher: &KeywordMatcher, window: CooccurrenceWindow) -> Result<Document, StatsError> {
+    let record = crate::cache::extract_record(path).map_err(|e| StatsError::PdfError(
+        format!("Error extracting text from {}: {}", path.display(), e)
+    ))?;
+    
+    Ok(count_keywords(path, &record, matcher, window))
+}
+
+/// Count keywords and words in the extracted record of a PDF file
+pub fn count_keywords(path: &Path, record: &CacheRecord, matcher: &KeywordMatcher, window: CooccurrenceWindow) -> Document {
+    // Windows need page boundaries; whole documents don't
+    let whole;
+    let pages = if window == CooccurrenceWindow::Document || record.pages.is_empty() {
+        whole = [record.text()];
+        &whole[..]
+    } else {
+        &record.pages[..]
+    };
     
-    let text = pdf_extract::extract_text_from_mem(&bytes)
-        .map_err(|e| StatsError::PdfError(
-            format!("Error extracting text from {}: {}", path.display(), e)
-        ))?;
+    let mut totals = vec![0; matcher.keywords().len()];
+    let mut word_count = 0;
     
-    let mut counts = HashMap::new();
+    for text in pages {
+        for (total, count) in totals.iter_mut().zip(matcher.count(text)) {
+            *total += count;
+        }
+        word_count += text.split_whitespace().count();
+    }
     
-    for keyword in keywords {
-        let count = text.matches(keyword).count();
-        counts.insert(keyword.to_string(), count);
+    let counts = matcher.keywords().iter().cloned().zip(totals).collect();
+    let document = Document::new(path.to_path_buf(), counts, word_count);
+    if window == CooccurrenceWindow::Document {
+        return document;
     }
     
-    Ok(counts)
-} 
\ No newline at end of file
+    let windows = count_windows(&document, pages, matcher, window);
+    document.with_windows(windows)
+}
+
+/// Maximum number of co-occurrence contexts kept per document and in the report
+const MAX_CONTEXTS: usize = 20;
+
+/// Maximum length of a context snippet in characters
+const SNIPPET_LENGTH: usize = 240;
+
+/// Count keywords within each window of the page texts
+pub fn count_windows(document: &Document, pages: &[String], matcher: &KeywordMatcher, window: CooccurrenceWindow) -> WindowCounts {
+    let keywords = matcher.keywords();
+    let mut result = WindowCounts::default();
+    
+    for (page, text) in pages.iter().enumerate() {
+        for segment in split_windows(text, window) {
+            let counts = matcher.count(&segment);
+            let present: Vec<usize> = (0..counts.len()).filter(|&i| counts[i] > 0).collect();
+            
+            if present.is_empty() {
+                result.empty += 1;
+                continue;
+            }
+            
+            if present.len() > 1 {
+                let mut strength = 0;
+                for (n, &i) in present.iter().enumerate() {
+                    for &j in &present[n + 1..] {
+                        strength += counts[i].min(counts[j]);
+                    }
+                }
+                
+                // Only the strongest windows are kept, so don't build snippets for weaker ones
+                let weakest = result.contexts.last().map(|c: &CooccurrenceContext| c.strength).unwrap_or(0);
+                if result.contexts.len() < MAX_CONTEXTS || strength > weakest {
+                    result.contexts.push(CooccurrenceContext {
+                        path: document.path.clone(),
+                        filename: document.filename.clone(),
+                        page,
+                        keywords: present.iter().map(|&i| keywords[i].clone()).collect(),
+                        strength,
+                        snippet: snippet(&segment, &keywords[present[0]]),
+                    });
+                    result.contexts.sort_by_key(|context| std::cmp::Reverse(context.strength));
+                    result.contexts.truncate(MAX_CONTEXTS);
+                }
+            }
+            
+            result.windows.push(counts);
+        }
+    }
+    
+    result
+}
+
+/// Split a page of text into co-occurrence windows
+fn split_windows(text: &str, window: CooccurrenceWindow) -> Vec<String> {
+    match window {
+        CooccurrenceWindow::Document | CooccurrenceWindow::Page => vec![text.to_string()],
+        CooccurrenceWindow::Paragraph => paragraphs(text),
+        CooccurrenceWindow::Sentence => paragraphs(text)
+            .iter()
+            .flat_map(|paragraph| sentences(paragraph))
+            .collect(),
+        CooccurrenceWindow::Words(n) => text
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .chunks(n.max(1))
+            .map(|words| words.join(" "))
+            .collect(),
+    }
+}
+
+/// Paragraphs are separated by blank lines
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !current.trim().is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            current.clear();
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    if !current.trim().is_empty() {
+        paragraphs.push(current);
+    }
+    
+    paragraphs
+}
+
+/// Sentences end with `.`, `!` or `?` followed by whitespace
+fn sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    
+    while let Some((i, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?') {
+            let at_boundary = chars.peek().map(|(_, next)| next.is_whitespace()).unwrap_or(true);
+            if at_boundary {
+                let end = i + c.len_utf8();
+                let sentence = text[start..end].trim();
+                if !sentence.is_empty() {
+                    sentences.push(sentence.to_string());
+                }
+                start = end;
+            }
+        }
+    }
+    
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest.to_string());
+    }
+    
+    sentences
+}
+
+/// Whitespace-normalized excerpt of a window, centered on the first keyword if it is long
+fn snippet(text: &str, keyword: &str) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let length = normalized.chars().count();
+    if length <= SNIPPET_LENGTH {
+        return normalized;
+    }
+    
+    // First word of the first synonym; ASCII lowercasing keeps byte offsets intact
+    let word = keyword
+        .split('|')
+        .find_map(|alternative| alternative.split_whitespace().next())
+        .unwrap_or(keyword)
+        .to_ascii_lowercase();
+    let keyword_char = normalized
+        .to_ascii_lowercase()
+        .find(&word)
+        .map(|byte| normalized[..byte].chars().count())
+        .unwrap_or(0);
+    let start = keyword_char.saturating_sub(SNIPPET_LENGTH / 2).min(length - SNIPPET_LENGTH);
+    let excerpt: String = normalized.chars().skip(start).take(SNIPPET_LENGTH).collect();
+    
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        excerpt,
+        if start + SNIPPET_LENGTH < length { "..." } else { "" }
+    )
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Languages with a bundled stopword list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    French,
+    Spanish,
+    Italian,
+    Dutch,
+    Portuguese,
+}
+
+impl Language {
+    pub const ALL: [Language; 7] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+        Language::Italian,
+        Language::Dutch,
+        Language::Portuguese,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "english",
+            Language::German => "german",
+            Language::French => "french",
+            Language::Spanish => "spanish",
+            Language::Italian => "italian",
+            Language::Dutch => "dutch",
+            Language::Portuguese => "portuguese",
+        }
+    }
+
+    /// The bundled stopword list, lowercased
+    pub fn stopwords(&self) -> &'static [&'static str] {
+        match self {
+            Language::English => ENGLISH,
+            Language::German => GERMAN,
+            Language::French => FRENCH,
+            Language::Spanish => SPANISH,
+            Language::Italian => ITALIAN,
+            Language::Dutch => DUTCH,
+            Language::Portuguese => PORTUGUESE,
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "english" | "en" => Ok(Language::English),
+            "german" | "de" => Ok(Language::German),
+            "french" | "fr" => Ok(Language::French),
+            "spanish" | "es" => Ok(Language::Spanish),
+            "italian" | "it" => Ok(Language::Italian),
+            "dutch" | "nl" => Ok(Language::Dutch),
+            "portuguese" | "pt" => Ok(Language::Portuguese),
+            _ => Err(format!(
+                "unknown language '{}' (expected one of: {})",
+                s,
+                Language::ALL.iter().map(|l| l.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// A set of stopwords for one or more languages
+#[derive(Debug, Clone, Default)]
+pub struct Stopwords {
+    words: HashSet<&'static str>,
+}
+
+impl Stopwords {
+    pub fn new(languages: &[Language]) -> Self {
+        Self {
+            words: languages.iter().flat_map(|l| l.stopwords().iter().copied()).collect(),
+        }
+    }
+
+    /// Whether a lowercased token is a stopword
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+}
+
+const ENGLISH: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "aren't", "as", "at", "be", "because", "been", "before", "being", "below", "between",
+    "both", "but", "by", "can", "can't", "cannot", "could", "couldn't", "did", "didn't", "do",
+    "does", "doesn't", "doing", "don't", "down", "during", "each", "eg", "either", "et", "al",
+    "etc", "few", "for", "from", "further", "had", "hadn't", "has", "hasn't", "have", "haven't",
+    "having", "he", "he'd", "he'll", "he's", "her", "here", "here's", "hers", "herself", "him",
+    "himself", "his", "how", "how's", "however", "i", "i'd", "i'll", "i'm", "i've", "ie", "if",
+    "in", "into", "is", "isn't", "it", "it's", "its", "itself", "just", "let's", "may", "me",
+    "might", "more", "most", "must", "mustn't", "my", "myself", "no", "nor", "not", "now", "of",
+    "off", "often", "on", "once", "one", "only", "or", "other", "ought", "our", "ours",
+    "ourselves", "out", "over", "own", "same", "shall", "shan't", "she", "she'd", "she'll",
+    "she's", "should", "shouldn't", "since", "so", "some", "such", "than", "that", "that's",
+    "the", "their", "theirs", "them", "themselves", "then", "there", "there's", "therefore",
+    "these", "they", "they'd", "they'll", "they're", "they've", "this", "those", "though",
+    "through", "thus", "to", "too", "two", "under", "until", "up", "upon", "us", "use", "used",
+    "using", "very", "via", "was", "wasn't", "we", "we'd", "we'll", "we're", "we've", "well",
+    "were", "weren't", "what", "what's", "when", "when's", "where", "where's", "whether",
+    "which", "while", "who", "who's", "whom", "why", "why's", "will", "with", "within",
+    "without", "won't", "would", "wouldn't", "yet", "you", "you'd", "you'll", "you're",
+    "you've", "your", "yours", "yourself", "yourselves",
+];
+
+const GERMAN: &[&str] = &[
+    "aber", "alle", "allem", "allen", "aller", "alles", "als", "also", "am", "an", "ander",
+    "andere", "anderen", "anderer", "auch", "auf", "aus", "bei", "bin", "bis", "bist", "da",
+    "damit", "dann", "das", "dass", "daß", "dazu", "dein", "dem", "den", "denn", "der", "des",
+    "dessen", "deshalb", "die", "dies", "diese", "diesem", "diesen", "dieser", "dieses", "doch",
+    "dort", "du", "durch", "ein", "eine", "einem", "einen", "einer", "eines", "er", "es", "etwas",
+    "euch", "euer", "für", "gegen", "hab", "habe", "haben", "hat", "hatte", "hier", "hin",
+    "hinter", "ich", "ihm", "ihn", "ihnen", "ihr", "ihre", "im", "in", "indem", "ins", "ist",
+    "jede", "jedem", "jeden", "jeder", "jedes", "jetzt", "kann", "kein", "keine", "können",
+    "man", "manche", "mein", "mich", "mir", "mit", "muss", "nach", "nicht", "nichts", "noch",
+    "nun", "nur", "ob", "oder", "ohne", "sehr", "sein", "seine", "seinem", "seinen", "seiner",
+    "sich", "sie", "sind", "so", "solche", "soll", "sondern", "sowie", "über", "um", "und",
+    "uns", "unser", "unter", "viel", "vom", "von", "vor", "war", "waren", "was", "weil", "welche",
+    "welchem", "welchen", "welcher", "wenn", "werden", "wie", "wieder", "will", "wir", "wird",
+    "wo", "wurde", "wurden", "zu", "zum", "zur", "zwischen",
+];
+
+const FRENCH: &[&str] = &[
+    "a", "à", "ai", "au", "aussi", "aux", "avec", "avez", "avons", "c'est", "ce", "ceci",
+    "cela", "ces", "cet", "cette", "comme", "d'un", "d'une", "dans", "de", "des", "donc",
+    "dont", "du", "elle", "elles", "en", "entre", "est", "et", "été", "être", "eu", "il",
+    "ils", "j'ai", "je", "l'on", "la", "le", "les", "leur", "leurs", "lui", "ma", "mais", "me",
+    "même", "mes", "moi", "mon", "ne", "nos", "notre", "nous", "on", "ont", "ou", "où", "par",
+    "pas", "peut", "plus", "pour", "qu'il", "que", "qui", "sa", "sans", "se", "ses", "si",
+    "son", "sont", "sous", "sur", "ta", "te", "tes", "toi", "ton", "tous", "tout", "toute",
+    "toutes", "très", "tu", "un", "une", "vos", "votre", "vous", "y",
+];
+
+const SPANISH: &[&str] = &[
+    "a", "al", "algo", "algunos", "ante", "antes", "como", "con", "contra", "cual", "cuando",
+    "de", "del", "desde", "donde", "durante", "e", "el", "él", "ella", "ellas", "ellos", "en",
+    "entre", "era", "es", "esa", "esas", "ese", "eso", "esos", "esta", "está", "están", "estas",
+    "este", "esto", "estos", "fue", "fueron", "ha", "han", "hasta", "hay", "la", "las", "le",
+    "les", "lo", "los", "más", "me", "mi", "mucho", "muy", "nada", "ni", "no", "nos", "nosotros",
+    "o", "otra", "otras", "otro", "otros", "para", "pero", "poco", "por", "porque", "que", "qué",
+    "quien", "se", "sea", "ser", "si", "sí", "sin", "sobre", "son", "su", "sus", "también",
+    "tanto", "te", "tiene", "todo", "todos", "tu", "un", "una", "uno", "unos", "y", "ya", "yo",
+];
+
+const ITALIAN: &[&str] = &[
+    "a", "ad", "agli", "ai", "al", "alla", "alle", "allo", "anche", "avere", "c'è", "che",
+    "chi", "con", "come", "cosa", "da", "dai", "dal", "dalla", "dalle", "degli", "dei", "del",
+    "della", "delle", "dello", "di", "dove", "e", "è", "ed", "essere", "gli", "ha", "hanno",
+    "i", "il", "in", "io", "la", "le", "lei", "lo", "loro", "lui", "ma", "mi", "molto",
+    "ne", "negli", "nei", "nel", "nella", "nelle", "noi", "non", "o", "per", "più", "perché",
+    "questa", "queste", "questi", "questo", "quella", "quelle", "quelli", "quello", "se", "si",
+    "sia", "sono", "su", "sua", "sue", "sui", "sul", "sulla", "suo", "suoi", "ti", "tra", "tu",
+    "tutti", "tutto", "un", "una", "uno", "voi",
+];
+
+const DUTCH: &[&str] = &[
+    "aan", "al", "alles", "als", "ben", "bij", "dan", "dat", "de", "der", "deze", "die", "dit",
+    "doch", "door", "dus", "een", "en", "er", "ge", "geen", "haar", "had", "heb", "hebben",
+    "heeft", "hem", "het", "hier", "hij", "hoe", "hun", "ik", "in", "is", "ja", "je", "kan",
+    "kon", "maar", "me", "meer", "men", "met", "mij", "mijn", "na", "naar", "niet", "niets",
+    "nog", "nu", "of", "om", "omdat", "ons", "ook", "op", "over", "te", "tegen", "toch", "toen",
+    "tot", "u", "uit", "uw", "van", "veel", "voor", "was", "wat", "we", "wel", "werd", "wie",
+    "wij", "wordt", "worden", "zal", "ze", "zelf", "zich", "zij", "zijn", "zo", "zonder", "zou",
+];
+
+const PORTUGUESE: &[&str] = &[
+    "a", "à", "ao", "aos", "as", "até", "com", "como", "da", "das", "de", "dela", "dele",
+    "depois", "do", "dos", "e", "é", "ela", "elas", "ele", "eles", "em", "entre", "era", "essa",
+    "essas", "esse", "esses", "esta", "está", "estas", "este", "estes", "eu", "foi", "foram",
+    "há", "isso", "isto", "já", "lhe", "mais", "mas", "me", "mesmo", "muito", "na", "nas", "não",
+    "nem", "no", "nos", "nós", "num", "numa", "o", "os", "ou", "para", "pela", "pelas", "pelo",
+    "pelos", "por", "qual", "quando", "que", "quem", "se", "sem", "ser", "seu", "seus", "só",
+    "sua", "suas", "também", "te", "tem", "um", "uma", "você", "vocês",
+];
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use rust_stemmers::{Algorithm, Stemmer};
+
+use crate::stopwords::Language;
+
+/// Split text into phrase segments of lowercased word tokens.
+///
+/// Letters and digits form words; apostrophes and hyphens are kept inside a word
+/// ("don't", "state-of-the-art") but never at its start or end. Segments end at
+/// sentence and clause punctuation, so no phrase spans a comma, full stop, bracket
+/// or quote. Line breaks do not end a segment because PDF text wraps lines in the
+/// middle of sentences.
+pub fn segments(text: &str) -> Vec<Vec<String>> {
+    split(text, true)
+}
+
+/// Split text into word tokens, lowercased unless `case_sensitive`
+pub fn tokenize(text: &str, case_sensitive: bool) -> Vec<String> {
+    split(text, !case_sensitive).into_iter().flatten().collect()
+}
+
+/// Whether a token is worth considering as (part of) a keyword: at least two
+/// characters and at least one letter
+pub fn is_word(token: &str) -> bool {
+    token.chars().count() >= 2 && token.chars().any(|c| c.is_alphabetic())
+}
+
+/// How keywords are matched against document text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MatchOptions {
+    /// Match letter case exactly
+    pub case_sensitive: bool,
+    /// Reduce words to their Snowball stem in this language before matching
+    pub stemming: Option<Language>,
+}
+
+/// Counts whole-word occurrences of keywords in text.
+///
+/// A keyword may be a group of synonyms separated by `|` (`car|automobile|vehicle`),
+/// each of which may be a phrase; an occurrence of any of them counts for the keyword.
+pub struct KeywordMatcher {
+    keywords: Vec<String>,
+    case_sensitive: bool,
+    stemmer: Option<Stemmer>,
+    /// Keyword index and normalized tokens of every alternative, by first token
+    alternatives: HashMap<String, Vec<(usize, Vec<String>)>>,
+}
+
+impl KeywordMatcher {
+    pub fn new(keywords: &[String], options: MatchOptions) -> Self {
+        let mut matcher = Self {
+            keywords: keywords.to_vec(),
+            case_sensitive: options.case_sensitive,
+            stemmer: options.stemming.map(|language| Stemmer::create(algorithm(language))),
+            alternatives: HashMap::new(),
+        };
+
+        for (index, keyword) in keywords.iter().enumerate() {
+            for alternative in keyword.split('|') {
+                let tokens = matcher.tokens(alternative);
+                if let Some(first) = tokens.first() {
+                    matcher.alternatives.entry(first.clone()).or_default().push((index, tokens));
+                }
+            }
+        }
+
+        matcher
+    }
+
+    /// The keywords as given, synonym groups included
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// Number of occurrences of each keyword in the text, in keyword order
+    pub fn count(&self, text: &str) -> Vec<usize> {
+        let tokens = self.tokens(text);
+        let mut counts = vec![0; self.keywords.len()];
+        let mut matched = Vec::new();
+
+        for (i, token) in tokens.iter().enumerate() {
+            let Some(alternatives) = self.alternatives.get(token) else {
+                continue;
+            };
+
+            // Synonyms of one keyword starting at the same word count once
+            matched.clear();
+            for (index, alternative) in alternatives {
+                if !matched.contains(index) && tokens[i..].starts_with(alternative) {
+                    matched.push(*index);
+                    counts[*index] += 1;
+                }
+            }
+        }
+
+        counts
+    }
+
+    /// Tokenize and normalize text the way keywords are matched
+    fn tokens(&self, text: &str) -> Vec<String> {
+        let tokens = tokenize(text, self.case_sensitive);
+        match &self.stemmer {
+            Some(stemmer) => tokens.iter().map(|token| stemmer.stem(token).into_owned()).collect(),
+            None => tokens,
+        }
+    }
+}
+
+/// Snowball algorithm for a language
+fn algorithm(language: Language) -> Algorithm {
+    match language {
+        Language::English => Algorithm::English,
+        Language::German => Algorithm::German,
+        Language::French => Algorithm::French,
+        Language::Spanish => Algorithm::Spanish,
+        Language::Italian => Algorithm::Italian,
+        Language::Dutch => Algorithm::Dutch,
+        Language::Portuguese => Algorithm::Portuguese,
+    }
+}
+
+/// Split text into segments of word tokens, optionally lowercased
+fn split(text: &str, lowercase: bool) -> Vec<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut segment = Vec::new();
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if lowercase {
+                word.extend(c.to_lowercase());
+            } else {
+                word.push(c);
+            }
+        } else if is_joiner(c) && !word.is_empty() {
+            word.push(if c == '’' { '\'' } else { c });
+        } else {
+            finish_word(&mut word, &mut segment);
+            if is_boundary(c) && !segment.is_empty() {
+                segments.push(std::mem::take(&mut segment));
+            }
+        }
+    }
+
+    finish_word(&mut word, &mut segment);
+    if !segment.is_empty() {
+        segments.push(segment);
+    }
+
+    segments
+}
+
+/// Characters allowed inside a word
+fn is_joiner(c: char) -> bool {
+    matches!(c, '\'' | '’' | '-')
+}
+
+/// Punctuation that ends a phrase segment
+fn is_boundary(c: char) -> bool {
+    matches!(
+        c,
+        '.' | ',' | ';' | ':' | '!' | '?' | '(' | ')' | '[' | ']' | '{' | '}'
+            | '"' | '“' | '”' | '«' | '»' | '…' | '•' | '|' | '/' | '–' | '—'
+    )
+}
+
+/// Push the current word onto the segment, dropping trailing joiners
+fn finish_word(word: &mut String, segment: &mut Vec<String>) {
+    let trimmed = word.trim_end_matches(is_joiner);
+    if !trimmed.is_empty() {
+        segment.push(trimmed.to_string());
+    }
+    word.clear();
+}
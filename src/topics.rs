@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::str::FromStr;
+
+use crate::similarity::mix;
+use crate::stats::{collect_pdf_paths, extract_texts, StatsError};
+use crate::stopwords::{Language, Stopwords};
+use crate::text::{is_word, tokenize};
+
+/// How document vectors are grouped into topics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClusteringMethod {
+    /// Spherical k-means on cosine similarity, seeded deterministically
+    #[default]
+    KMeans,
+    /// Agglomerative clustering with average linkage on cosine similarity
+    Hierarchical,
+}
+
+impl ClusteringMethod {
+    pub const ALL: [ClusteringMethod; 2] = [
+        ClusteringMethod::KMeans,
+        ClusteringMethod::Hierarchical,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClusteringMethod::KMeans => "kmeans",
+            ClusteringMethod::Hierarchical => "hierarchical",
+        }
+    }
+}
+
+impl fmt::Display for ClusteringMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ClusteringMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "kmeans" | "k-means" => Ok(ClusteringMethod::KMeans),
+            "hierarchical" | "agglomerative" => Ok(ClusteringMethod::Hierarchical),
+            _ => Err(format!(
+                "unknown clustering method '{}' (expected one of: {})",
+                s,
+                ClusteringMethod::ALL.iter().map(|m| m.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Options for topic clustering
+#[derive(Debug, Clone, Copy)]
+pub struct TopicOptions {
+    pub method: ClusteringMethod,
+    /// Number of topics to form
+    pub clusters: usize,
+    /// Stopwords left out of the document vectors
+    pub language: Language,
+    /// Number of terms describing each topic
+    pub top_terms: usize,
+}
+
+impl Default for TopicOptions {
+    fn default() -> Self {
+        Self {
+            method: ClusteringMethod::default(),
+            clusters: 5,
+            language: Language::default(),
+            top_terms: 10,
+        }
+    }
+}
+
+/// A document assigned to a topic
+#[derive(Debug, Clone)]
+pub struct TopicMember {
+    pub path: PathBuf,
+    /// Cosine similarity to the topic centroid
+    pub similarity: f64,
+}
+
+/// A group of documents about the same subject
+#[derive(Debug, Clone)]
+pub struct Topic {
+    /// Terms with the highest mean TF-IDF weight in the topic, with that weight
+    pub terms: Vec<(String, f64)>,
+    /// Member documents, closest to the centroid first
+    pub members: Vec<TopicMember>,
+}
+
+impl Topic {
+    /// Short label made of the leading terms
+    pub fn label(&self) -> String {
+        self.terms.iter().take(3).map(|(term, _)| term.as_str()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Result of topic clustering over a set of documents
+#[derive(Debug, Clone)]
+pub struct TopicReport {
+    pub method: ClusteringMethod,
+    pub total_documents: usize,
+    /// Documents without any vocabulary term, left out of the clustering
+    pub without_text: Vec<PathBuf>,
+    pub vocabulary_size: usize,
+    /// Topics, largest first
+    pub topics: Vec<Topic>,
+}
+
+impl TopicReport {
+    /// Render the plain text report written by `pdfscan analyze --topics`
+    pub fn to_text(&self) -> String {
+        let mut report = String::new();
+        report.push_str("PDFScan Topic Clustering Report\n");
+        report.push_str("===============================\n\n");
+        report.push_str(&format!("Method: {}\n", self.method));
+        report.push_str(&format!("Total documents analyzed: {}\n", self.total_documents));
+        report.push_str(&format!("Vocabulary size: {}\n", self.vocabulary_size));
+        report.push_str(&format!("Topics found: {}\n", self.topics.len()));
+
+        for (i, topic) in self.topics.iter().enumerate() {
+            report.push_str(&format!("\n\nTopic {}: {} ({} documents)\n", i + 1, topic.label(), topic.members.len()));
+            report.push_str("==========================================\n");
+
+            let terms: Vec<String> = topic.terms
+                .iter()
+                .map(|(term, weight)| format!("{} ({:.3})", term, weight))
+                .collect();
+            report.push_str(&format!("Top terms: {}\n\nDocuments:\n", terms.join(", ")));
+
+            for member in &topic.members {
+                report.push_str(&format!(
+                    "- {} (similarity: {:.2})\n",
+                    member.path.file_name().unwrap_or_default().to_string_lossy(),
+                    member.similarity
+                ));
+            }
+        }
+
+        if !self.without_text.is_empty() {
+            report.push_str("\n\nDocuments Without Extractable Text:\n");
+            report.push_str("==========================================\n");
+            for path in &self.without_text {
+                report.push_str(&format!("- {}\n", path.display()));
+            }
+        }
+
+        report
+    }
+}
+
+/// Terms beyond this many, by document frequency, are left out of the vectors
+const MAX_VOCABULARY: usize = 5000;
+
+/// Largest number of documents clustered hierarchically. Average linkage keeps a
+/// similarity matrix of all document pairs (200 MB at this size); larger collections
+/// need k-means.
+pub const MAX_HIERARCHICAL_DOCUMENTS: usize = 5000;
+
+/// K-means iteration limit and the seed of its initial centroid choice
+const KMEANS_ITERATIONS: usize = 100;
+const KMEANS_SEED: u64 = 0x5eed;
+
+/// Sparse vector of (term index, weight), sorted by term index
+type Vector = Vec<(usize, f64)>;
+
+/// Group already extracted document texts into topics.
+///
+/// Hierarchical clustering of more than `MAX_HIERARCHICAL_DOCUMENTS` documents with
+/// text is refused.
+pub fn cluster_texts(texts: &[(PathBuf, String)], options: &TopicOptions) -> Result<TopicReport, StatsError> {
+    let stopwords = Stopwords::new(&[options.language]);
+
+    let counts: Vec<HashMap<String, usize>> = texts
+        .iter()
+        .map(|(_, text)| {
+            let mut counts = HashMap::new();
+            for token in tokenize(text, false) {
+                if is_word(&token) && !stopwords.contains(&token) {
+                    *counts.entry(token).or_insert(0) += 1;
+                }
+            }
+            counts
+        })
+        .collect();
+
+    let vocabulary = vocabulary(&counts);
+    let vectors = tfidf_vectors(&counts, &vocabulary);
+
+    let mut without_text = Vec::new();
+    let mut paths = Vec::new();
+    let vectors: Vec<Vector> = texts
+        .iter()
+        .zip(vectors)
+        .filter_map(|((path, _), vector)| {
+            if vector.is_empty() {
+                without_text.push(path.clone());
+                None
+            } else {
+                paths.push(path.clone());
+                Some(vector)
+            }
+        })
+        .collect();
+
+    check_size(options.method, vectors.len())?;
+
+    let k = options.clusters.clamp(1, vectors.len().max(1));
+    let assignments = match options.method {
+        ClusteringMethod::KMeans => kmeans(&vectors, vocabulary.len(), k),
+        ClusteringMethod::Hierarchical => hierarchical(&vectors, k),
+    };
+
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); k];
+    for (document, &cluster) in assignments.iter().enumerate() {
+        groups[cluster].push(document);
+    }
+
+    let mut topics: Vec<Topic> = groups
+        .into_iter()
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            let centroid = centroid(&vectors, &group, vocabulary.len());
+
+            let mut terms: Vec<(String, f64)> = centroid
+                .iter()
+                .enumerate()
+                .filter(|(_, weight)| **weight > 0.0)
+                .map(|(term, weight)| (vocabulary[term].clone(), *weight))
+                .collect();
+            terms.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            terms.truncate(options.top_terms);
+
+            let norm = centroid.iter().map(|w| w * w).sum::<f64>().sqrt();
+            let mut members: Vec<TopicMember> = group
+                .iter()
+                .map(|&document| TopicMember {
+                    path: paths[document].clone(),
+                    similarity: if norm > 0.0 { dot_dense(&vectors[document], &centroid) / norm } else { 0.0 },
+                })
+                .collect();
+            members.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| a.path.cmp(&b.path)));
+
+            Topic { terms, members }
+        })
+        .collect();
+    topics.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then_with(|| a.members[0].path.cmp(&b.members[0].path)));
+
+    Ok(TopicReport {
+        method: options.method,
+        total_documents: texts.len(),
+        without_text,
+        vocabulary_size: vocabulary.len(),
+        topics,
+    })
+}
+
+/// Refuse hierarchical clustering of more documents than it can hold in memory
+fn check_size(method: ClusteringMethod, documents: usize) -> Result<(), StatsError> {
+    if method == ClusteringMethod::Hierarchical && documents > MAX_HIERARCHICAL_DOCUMENTS {
+        return Err(StatsError::OtherError(format!(
+            "Hierarchical clustering is limited to {} documents ({} given); use k-means for larger collections",
+            MAX_HIERARCHICAL_DOCUMENTS, documents
+        )));
+    }
+    Ok(())
+}
+
+/// Group the PDF files in the given paths into topics; stops with an error once `cancel` is set
+pub fn cluster_documents(input_paths: &[String], options: &TopicOptions, cancel: &AtomicBool) -> Result<TopicReport, StatsError> {
+    // Don't extract a collection that is too large to cluster
+    check_size(options.method, collect_pdf_paths(input_paths)?.len())?;
+    let texts = extract_texts(input_paths, cancel)?;
+
+    cluster_texts(&texts, options)
+}
+
+/// Run topic clustering and write the report to a file
+pub fn run(input_paths: &[String], output_file: &str, options: &TopicOptions) -> Result<(), Box<dyn Error>> {
+    let report = cluster_documents(input_paths, options, &AtomicBool::new(false))?;
+
+    fs::write(output_file, report.to_text())?;
+    println!("Successfully generated topic clustering report in '{}'", output_file);
+
+    Ok(())
+}
+
+/// Terms used for the document vectors, sorted alphabetically.
+///
+/// With three or more documents, terms found in only one document cannot link
+/// documents and are left out; the most widespread terms are kept up to
+/// `MAX_VOCABULARY`.
+fn vocabulary(counts: &[HashMap<String, usize>]) -> Vec<String> {
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for document in counts {
+        for term in document.keys() {
+            *document_frequency.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    let min_frequency = if counts.len() >= 3 { 2 } else { 1 };
+    let mut terms: Vec<(&str, usize)> = document_frequency
+        .into_iter()
+        .filter(|(_, frequency)| *frequency >= min_frequency)
+        .collect();
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    terms.truncate(MAX_VOCABULARY);
+
+    let mut vocabulary: Vec<String> = terms.into_iter().map(|(term, _)| term.to_string()).collect();
+    vocabulary.sort();
+    vocabulary
+}
+
+/// L2-normalized TF-IDF vectors with sublinear term frequency and smoothed idf
+fn tfidf_vectors(counts: &[HashMap<String, usize>], vocabulary: &[String]) -> Vec<Vector> {
+    let index: HashMap<&str, usize> = vocabulary.iter().enumerate().map(|(i, term)| (term.as_str(), i)).collect();
+
+    let mut document_frequency = vec![0usize; vocabulary.len()];
+    for document in counts {
+        for term in document.keys() {
+            if let Some(&i) = index.get(term.as_str()) {
+                document_frequency[i] += 1;
+            }
+        }
+    }
+
+    let n = counts.len() as f64;
+    counts
+        .iter()
+        .map(|document| {
+            let mut vector: Vector = document
+                .iter()
+                .filter_map(|(term, &count)| {
+                    let i = *index.get(term.as_str())?;
+                    let idf = ((1.0 + n) / (1.0 + document_frequency[i] as f64)).ln() + 1.0;
+                    Some((i, (1.0 + (count as f64).ln()) * idf))
+                })
+                .collect();
+            vector.sort_by_key(|&(i, _)| i);
+
+            let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+            for (_, weight) in &mut vector {
+                *weight /= norm;
+            }
+            vector
+        })
+        .collect()
+}
+
+/// Mean of the member vectors, as a dense vector
+fn centroid(vectors: &[Vector], members: &[usize], dimensions: usize) -> Vec<f64> {
+    let mut centroid = vec![0.0; dimensions];
+    for &member in members {
+        for &(i, weight) in &vectors[member] {
+            centroid[i] += weight;
+        }
+    }
+    for weight in &mut centroid {
+        *weight /= members.len() as f64;
+    }
+    centroid
+}
+
+fn dot_dense(vector: &Vector, dense: &[f64]) -> f64 {
+    vector.iter().map(|&(i, weight)| weight * dense[i]).sum()
+}
+
+fn dot_sparse(a: &Vector, b: &Vector) -> f64 {
+    let (mut i, mut j, mut sum) = (0, 0, 0.0);
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                sum += a[i].1 * b[j].1;
+                i += 1;
+                j += 1;
+            },
+        }
+    }
+    sum
+}
+
+/// Spherical k-means with k-means++ seeding from a fixed seed, so the same
+/// documents always give the same topics. Returns the cluster of every vector.
+fn kmeans(vectors: &[Vector], dimensions: usize, k: usize) -> Vec<usize> {
+    if vectors.is_empty() {
+        return Vec::new();
+    }
+
+    let mut draws = 0u64;
+    let mut random = || {
+        draws += 1;
+        mix(KMEANS_SEED + draws)
+    };
+
+    let mut seeds = vec![(random() % vectors.len() as u64) as usize];
+    let mut nearest: Vec<f64> = vectors.iter().map(|v| dot_sparse(v, &vectors[seeds[0]])).collect();
+    while seeds.len() < k {
+        // Pick the next seed with probability proportional to its squared cosine distance
+        let weights: Vec<f64> = nearest.iter().map(|s| (1.0 - s).max(0.0).powi(2)).collect();
+        let total: f64 = weights.iter().sum();
+        let next = if total > 0.0 {
+            let mut target = (random() >> 11) as f64 / (1u64 << 53) as f64 * total;
+            weights.iter().position(|&w| {
+                target -= w;
+                target < 0.0
+            }).unwrap_or(vectors.len() - 1)
+        } else {
+            (0..vectors.len()).find(|i| !seeds.contains(i)).unwrap_or(0)
+        };
+        seeds.push(next);
+        for (best, vector) in nearest.iter_mut().zip(vectors) {
+            *best = best.max(dot_sparse(vector, &vectors[next]));
+        }
+    }
+
+    let mut centroids: Vec<Vec<f64>> = seeds
+        .iter()
+        .map(|&seed| centroid(vectors, &[seed], dimensions))
+        .collect();
+    let mut assignments = vec![usize::MAX; vectors.len()];
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        let mut similarities = vec![0.0; vectors.len()];
+        for (i, vector) in vectors.iter().enumerate() {
+            let (cluster, similarity) = centroids
+                .iter()
+                .map(|centroid| dot_dense(vector, centroid))
+                .enumerate()
+                .fold((0, f64::MIN), |best, (c, s)| if s > best.1 { (c, s) } else { best });
+            similarities[i] = similarity;
+            if assignments[i] != cluster {
+                assignments[i] = cluster;
+                changed = true;
+            }
+        }
+
+        // An emptied cluster takes over the document furthest from its centroid
+        let mut sizes = vec![0usize; k];
+        for &cluster in &assignments {
+            sizes[cluster] += 1;
+        }
+        for cluster in 0..k {
+            if sizes[cluster] == 0 {
+                let furthest = (0..vectors.len())
+                    .filter(|&i| sizes[assignments[i]] > 1)
+                    .min_by(|&a, &b| similarities[a].total_cmp(&similarities[b]));
+                if let Some(i) = furthest {
+                    sizes[assignments[i]] -= 1;
+                    sizes[cluster] += 1;
+                    assignments[i] = cluster;
+                    similarities[i] = 1.0;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..vectors.len()).filter(|&i| assignments[i] == cluster).collect();
+            *centroid = self::centroid(vectors, &members, dimensions);
+            let norm = centroid.iter().map(|w| w * w).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                for weight in centroid.iter_mut() {
+                    *weight /= norm;
+                }
+            }
+        }
+    }
+
+    assignments
+}
+
+/// Average-linkage agglomerative clustering, merging the most similar pair of
+/// clusters until `k` remain. Returns the cluster of every vector.
+fn hierarchical(vectors: &[Vector], k: usize) -> Vec<usize> {
+    let n = vectors.len();
+    let mut similarity = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let s = dot_sparse(&vectors[i], &vectors[j]);
+            similarity[i][j] = s;
+            similarity[j][i] = s;
+        }
+    }
+
+    let mut sizes = vec![1usize; n];
+    let mut active = vec![true; n];
+    let mut parent: Vec<usize> = (0..n).collect();
+
+    // Most similar other active cluster of every cluster
+    let best_of = |similarity: &[Vec<f64>], active: &[bool], i: usize| -> Option<(usize, f64)> {
+        (0..n)
+            .filter(|&j| j != i && active[j])
+            .map(|j| (j, similarity[i][j]))
+            .fold(None, |best: Option<(usize, f64)>, (j, s)| match best {
+                Some((_, b)) if b >= s => best,
+                _ => Some((j, s)),
+            })
+    };
+    let mut best: Vec<Option<(usize, f64)>> = (0..n).map(|i| best_of(&similarity, &active, i)).collect();
+
+    for _ in k..n {
+        let Some((i, (j, _))) = (0..n)
+            .filter(|&i| active[i])
+            .filter_map(|i| best[i].map(|b| (i, b)))
+            .fold(None, |top: Option<(usize, (usize, f64))>, (i, b)| match top {
+                Some((_, (_, s))) if s >= b.1 => top,
+                _ => Some((i, b)),
+            })
+        else {
+            break;
+        };
+
+        // Merge j into i; the similarity to the merged cluster is the size-weighted average
+        for other in 0..n {
+            if active[other] && other != i && other != j {
+                let merged = (sizes[i] as f64 * similarity[i][other] + sizes[j] as f64 * similarity[j][other])
+                    / (sizes[i] + sizes[j]) as f64;
+                similarity[i][other] = merged;
+                similarity[other][i] = merged;
+            }
+        }
+        sizes[i] += sizes[j];
+        active[j] = false;
+        parent[j] = i;
+
+        for other in 0..n {
+            if !active[other] {
+                continue;
+            }
+            match best[other] {
+                Some((b, _)) if other == i || b == i || b == j => best[other] = best_of(&similarity, &active, other),
+                Some((_, s)) if similarity[other][i] > s => best[other] = Some((i, similarity[other][i])),
+                _ => {},
+            }
+        }
+    }
+
+    // Number the remaining clusters in order of their first document
+    let mut roots = HashMap::new();
+    (0..n)
+        .map(|document| {
+            let mut root = document;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            let next = roots.len();
+            *roots.entry(root).or_insert(next)
+        })
+        .collect()
+}
@@ -0,0 +1,406 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::Datelike;
+use rayon::prelude::*;
+use indicatif::ProgressBar;
+
+use crate::report::csv_field;
+use crate::stats::{collect_pdf_paths, extract_keyword_counts, CooccurrenceWindow, Document, StatsError};
+use crate::text::{KeywordMatcher, MatchOptions};
+
+/// What documents are grouped by to form a series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupBy {
+    /// The directory containing the PDF
+    #[default]
+    Folder,
+    /// The creation date in the PDF's document information
+    Created,
+    /// The file modification time
+    Modified,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 3] = [
+        GroupBy::Folder,
+        GroupBy::Created,
+        GroupBy::Modified,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            GroupBy::Folder => "folder",
+            GroupBy::Created => "created",
+            GroupBy::Modified => "modified",
+        }
+    }
+
+    /// Whether groups are date periods
+    pub fn is_date(&self) -> bool {
+        *self != GroupBy::Folder
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "folder" | "directory" | "dir" => Ok(GroupBy::Folder),
+            "created" | "creation-date" => Ok(GroupBy::Created),
+            "modified" | "mtime" => Ok(GroupBy::Modified),
+            _ => Err(format!(
+                "unknown grouping '{}' (expected one of: {})",
+                s,
+                GroupBy::ALL.iter().map(|g| g.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Length of the date periods documents are grouped into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Period {
+    Day,
+    #[default]
+    Month,
+    Year,
+}
+
+impl Period {
+    pub const ALL: [Period; 3] = [Period::Day, Period::Month, Period::Year];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Month => "month",
+            Period::Year => "year",
+        }
+    }
+
+    /// Group key of a date, sortable as text: `2024-03-15`, `2024-03` or `2024`
+    pub fn key(&self, year: i32, month: u32, day: u32) -> String {
+        match self {
+            Period::Day => format!("{:04}-{:02}-{:02}", year, month, day),
+            Period::Month => format!("{:04}-{:02}", year, month),
+            Period::Year => format!("{:04}", year),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" => Ok(Period::Day),
+            "month" | "monthly" => Ok(Period::Month),
+            "year" | "yearly" => Ok(Period::Year),
+            _ => Err(format!(
+                "unknown period '{}' (expected one of: {})",
+                s,
+                Period::ALL.iter().map(|p| p.name()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+/// Options for keyword frequency series
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrendOptions {
+    pub group_by: GroupBy,
+    /// Period of date groups; ignored when grouping by folder
+    pub period: Period,
+    pub matching: MatchOptions,
+}
+
+/// Keyword counts of the documents in one folder or date period
+#[derive(Debug, Clone)]
+pub struct TrendGroup {
+    pub key: String,
+    pub documents: usize,
+    pub word_count: usize,
+    /// Occurrences of each keyword, in keyword order
+    pub counts: Vec<usize>,
+}
+
+impl TrendGroup {
+    /// Occurrences of a keyword per 1,000 words, comparable between groups of different size
+    pub fn frequency(&self, keyword: usize) -> f64 {
+        if self.word_count == 0 {
+            0.0
+        } else {
+            self.counts[keyword] as f64 * 1000.0 / self.word_count as f64
+        }
+    }
+}
+
+/// Per-keyword frequency series over folders or date periods
+#[derive(Debug, Clone)]
+pub struct TrendReport {
+    pub group_by: GroupBy,
+    pub period: Period,
+    pub keywords: Vec<String>,
+    /// Groups in key order, i.e. chronologically for dates
+    pub groups: Vec<TrendGroup>,
+    /// Documents without a date to group them by
+    pub undated: Vec<PathBuf>,
+}
+
+impl TrendReport {
+    /// Describe the grouping, e.g. "folder" or "created (month)"
+    pub fn grouping(&self) -> String {
+        if self.group_by.is_date() {
+            format!("{} ({})", self.group_by, self.period)
+        } else {
+            self.group_by.to_string()
+        }
+    }
+
+    /// Render the plain text report written by `pdfscan trends`
+    pub fn to_text(&self) -> String {
+        let mut report = String::new();
+        report.push_str("PDFScan Keyword Trends Report\n");
+        report.push_str("=============================\n\n");
+        report.push_str(&format!("Grouped by: {}\n", self.grouping()));
+        report.push_str(&format!("Keywords: {}\n", self.keywords.join(", ")));
+        report.push_str(&format!("Groups: {}\n\n", self.groups.len()));
+
+        for group in &self.groups {
+            report.push_str(&format!(
+                "{} ({} documents, {} words)\n",
+                group.key, group.documents, group.word_count
+            ));
+            for (i, keyword) in self.keywords.iter().enumerate() {
+                report.push_str(&format!(
+                    "  {}: {} ({:.2} per 1,000 words)\n",
+                    keyword,
+                    group.counts[i],
+                    group.frequency(i)
+                ));
+            }
+        }
+
+        if !self.undated.is_empty() {
+            report.push_str("\nDocuments Without a Date:\n");
+            for path in &self.undated {
+                report.push_str(&format!("- {}\n", path.display()));
+            }
+        }
+
+        report
+    }
+
+    /// Render the series as CSV: one row per group with the count and the
+    /// frequency per 1,000 words of every keyword
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["group".to_string(), "documents".to_string(), "words".to_string()];
+        for keyword in &self.keywords {
+            header.push(csv_field(keyword));
+            header.push(csv_field(&format!("{} per 1000 words", keyword)));
+        }
+
+        let mut csv = header.join(",");
+        csv.push('\n');
+        for group in &self.groups {
+            let mut row = vec![csv_field(&group.key), group.documents.to_string(), group.word_count.to_string()];
+            for i in 0..self.keywords.len() {
+                row.push(group.counts[i].to_string());
+                row.push(format!("{:.4}", group.frequency(i)));
+            }
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+/// Sum the keyword counts of documents by group key; documents without a key are
+/// listed as undated
+pub fn group_documents(
+    documents: &[(Option<String>, Document)],
+    keywords: &[String],
+    options: &TrendOptions,
+) -> TrendReport {
+    let mut groups: BTreeMap<String, TrendGroup> = BTreeMap::new();
+    let mut undated = Vec::new();
+
+    for (key, document) in documents {
+        let Some(key) = key else {
+            undated.push(document.path.clone());
+            continue;
+        };
+
+        let group = groups.entry(key.clone()).or_insert_with(|| TrendGroup {
+            key: key.clone(),
+            documents: 0,
+            word_count: 0,
+            counts: vec![0; keywords.len()],
+        });
+        group.documents += 1;
+        group.word_count += document.word_count;
+        for (count, keyword) in group.counts.iter_mut().zip(keywords) {
+            *count += document.count(keyword);
+        }
+    }
+
+    undated.sort();
+
+    TrendReport {
+        group_by: options.group_by,
+        period: options.period,
+        keywords: keywords.to_vec(),
+        groups: groups.into_values().collect(),
+        undated,
+    }
+}
+
+/// Year, month and day of a PDF date string such as `D:20240315093000+01'00'`;
+/// missing month and day default to the first
+pub fn parse_pdf_date(value: &str) -> Option<(i32, u32, u32)> {
+    let digits: String = value
+        .trim()
+        .trim_start_matches("D:")
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let year = digits.get(0..4)?.parse().ok()?;
+    let month = digits.get(4..6).and_then(|m| m.parse().ok()).unwrap_or(1);
+    let day = digits.get(6..8).and_then(|d| d.parse().ok()).unwrap_or(1);
+
+    ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some((year, month, day))
+}
+
+/// Creation date from the document information dictionary of a PDF
+fn pdf_creation_date(path: &Path) -> Option<(i32, u32, u32)> {
+    let bytes = fs::read(path).ok()?;
+    let document = lopdf::Document::load_mem(&bytes).ok()?;
+    let info = document.trailer.get_deref(b"Info", &document).ok()?.as_dict().ok()?;
+    let value = info.get_deref(b"CreationDate", &document).ok()?.as_str().ok()?;
+
+    parse_pdf_date(&pdf_text_string(value))
+}
+
+/// Decode a PDF text string, which is either UTF-16BE with a byte order mark or single-byte
+pub(crate) fn pdf_text_string(value: &[u8]) -> String {
+    match value {
+        [0xfe, 0xff, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&units)
+        },
+        _ => value.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Group key of a document, or `None` if it has no date to group by
+fn group_key(path: &Path, options: &TrendOptions) -> Option<String> {
+    match options.group_by {
+        GroupBy::Folder => path.parent().map(|parent| parent.display().to_string()),
+        GroupBy::Created => pdf_creation_date(path)
+            .map(|(year, month, day)| options.period.key(year, month, day)),
+        GroupBy::Modified => {
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+            let date = chrono::DateTime::<chrono::Local>::from(modified);
+            Some(options.period.key(date.year(), date.month(), date.day()))
+        },
+    }
+}
+
+/// Count keywords in the PDF files in the given paths and group the counts.
+///
+/// Once `cancel` is set the remaining files are skipped and an error is returned.
+pub fn trends(
+    input_paths: &[String],
+    keywords: &[String],
+    options: &TrendOptions,
+    cancel: &AtomicBool,
+) -> Result<TrendReport, StatsError> {
+    if keywords.is_empty() {
+        return Err(StatsError::OtherError(
+            "No keywords provided for analysis".to_string()
+        ));
+    }
+
+    let pdf_paths = collect_pdf_paths(input_paths)?;
+
+    if pdf_paths.is_empty() {
+        return Err(StatsError::OtherError(
+            "No PDF files found in the provided paths".to_string()
+        ));
+    }
+
+    let matcher = KeywordMatcher::new(keywords, options.matching);
+
+    let pb = ProgressBar::new(pdf_paths.len() as u64);
+    pb.set_message("Counting keywords");
+
+    let documents: Vec<(Option<String>, Document)> = pdf_paths.par_iter()
+        .filter_map(|path| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let document = extract_keyword_counts(path, &matcher, CooccurrenceWindow::Document)
+                .unwrap_or_else(|e| {
+                    eprintln!("Error processing {}: {}", path.display(), e);
+                    Document::new(path.clone(), HashMap::new(), 0)
+                });
+            let key = group_key(path, options);
+
+            pb.inc(1);
+
+            Some((key, document))
+        })
+        .collect();
+
+    if cancel.load(Ordering::Relaxed) {
+        pb.abandon_with_message("Counting cancelled");
+        return Err(StatsError::OtherError("Counting cancelled".to_string()));
+    }
+
+    pb.finish_with_message("Counting complete");
+
+    Ok(group_documents(&documents, keywords, options))
+}
+
+/// Run the trend analysis, writing a text report or CSV to a file or stdout
+pub fn run(
+    input_paths: &[String],
+    keywords: &[String],
+    output_file: Option<&str>,
+    csv: bool,
+    options: &TrendOptions,
+) -> Result<(), Box<dyn Error>> {
+    let report = trends(input_paths, keywords, options, &AtomicBool::new(false))?;
+    let output = if csv { report.to_csv() } else { report.to_text() };
+
+    match output_file {
+        Some(output_file) => {
+            fs::write(output_file, output)?;
+            println!("Successfully generated keyword trends report in '{}'", output_file);
+        },
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
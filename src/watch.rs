@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::cache::{extract_record, record_cache_path, save_record_to_cache};
+use crate::report::json_string;
+use crate::saved_searches::SavedSearch;
+use crate::search::{find_matches, SearchOptions};
+
+/// How long a PDF must go without further events before it is read, so that
+/// files still being copied are not read half-written
+pub const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// How long `run` waits for filesystem events between checks for settled files
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Custom error type for watching directories
+#[derive(Debug)]
+pub enum WatchError {
+    NotifyError(notify::Error),
+    OtherError(String),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchError::NotifyError(e) => write!(f, "Watch error: {}", e),
+            WatchError::OtherError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for WatchError {}
+
+impl From<notify::Error> for WatchError {
+    fn from(err: notify::Error) -> Self {
+        WatchError::NotifyError(err)
+    }
+}
+
+/// A PDF that was added, changed or removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Added or changed, and now quiet for `DEBOUNCE`
+    Updated(PathBuf),
+    Removed(PathBuf),
+}
+
+/// Whether a path is a PDF outside the cache directories
+pub fn is_watched_pdf(path: &Path) -> bool {
+    let is_pdf = path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("pdf"))
+        .unwrap_or(false);
+    let cache_name = &crate::config::get().cache.directory_name;
+    is_pdf && !path.components().any(|component| component.as_os_str() == cache_name.as_str())
+}
+
+/// PDFs with recent filesystem events, reported once they have settled
+#[derive(Debug, Default)]
+pub struct ChangeQueue {
+    /// Time of the last event for each path
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl ChangeQueue {
+    /// Note an event for a path at the given time
+    pub fn touch(&mut self, path: PathBuf, at: Instant) {
+        self.pending.insert(path, at);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Take the paths without events for `DEBOUNCE`, in path order; a path that no
+    /// longer exists was removed
+    pub fn ready(&mut self, now: Instant) -> Vec<Change> {
+        let mut ready: Vec<PathBuf> = self.pending
+            .iter()
+            .filter(|(_, &at)| now.saturating_duration_since(at) >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+        ready.sort();
+
+        ready.into_iter()
+            .map(|path| {
+                self.pending.remove(&path);
+                if path.is_file() {
+                    Change::Updated(path)
+                } else {
+                    Change::Removed(path)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Watches directories, including subdirectories, for added, changed and removed PDFs
+pub struct DirectoryWatcher {
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    queue: ChangeQueue,
+}
+
+impl DirectoryWatcher {
+    /// Start watching; `on_event` is called from the watcher's thread after each
+    /// filesystem event, e.g. to wake up a user interface
+    pub fn new(directories: &[PathBuf], on_event: impl Fn() + Send + 'static) -> Result<Self, WatchError> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+            on_event();
+        })?;
+
+        for directory in directories {
+            if !directory.is_dir() {
+                return Err(WatchError::OtherError(format!("Path is not a directory: {}", directory.display())));
+            }
+            watcher.watch(directory, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            queue: ChangeQueue::default(),
+        })
+    }
+
+    /// Whether there are changes still waiting to settle
+    pub fn has_pending(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    /// Wait up to `timeout` for filesystem events and return the changes that have
+    /// settled. Errors reported by the watcher are returned alongside.
+    pub fn changes(&mut self, timeout: Duration) -> Result<(Vec<Change>, Vec<String>), WatchError> {
+        let deadline = Instant::now() + timeout;
+        let mut errors = Vec::new();
+
+        loop {
+            match self.events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(Ok(event)) => self.record(event),
+                Ok(Err(e)) => errors.push(e.to_string()),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(WatchError::OtherError("The filesystem watcher stopped".to_string()));
+                },
+            }
+
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        Ok((self.queue.ready(Instant::now()), errors))
+    }
+
+    fn record(&mut self, event: Event) {
+        let relevant = match event.kind {
+            // Reading a PDF is no change, but closing it after writing is
+            EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+            EventKind::Access(_) => false,
+            _ => true,
+        };
+        if !relevant {
+            return;
+        }
+
+        let now = Instant::now();
+        for path in event.paths.into_iter().filter(|path| is_watched_pdf(path)) {
+            self.queue.touch(path, now);
+        }
+    }
+}
+
+/// A query that new and changed PDFs are checked against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandingQuery {
+    pub query: String,
+    pub options: SearchOptions,
+    /// Name of the saved search the query comes from
+    pub saved: Option<String>,
+}
+
+impl From<SavedSearch> for StandingQuery {
+    fn from(search: SavedSearch) -> Self {
+        Self { query: search.query, options: search.options, saved: Some(search.name) }
+    }
+}
+
+/// Something that happened to a watched PDF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    Match { path: PathBuf, query: String, saved: Option<String>, matches: usize },
+    /// The text was extracted and cached
+    Indexed { path: PathBuf },
+    Removed { path: PathBuf },
+    Error { path: PathBuf, message: String },
+}
+
+impl WatchEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            WatchEvent::Match { .. } => "match",
+            WatchEvent::Indexed { .. } => "indexed",
+            WatchEvent::Removed { .. } => "removed",
+            WatchEvent::Error { .. } => "error",
+        }
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            WatchEvent::Match { path, .. }
+            | WatchEvent::Indexed { path }
+            | WatchEvent::Removed { path }
+            | WatchEvent::Error { path, .. } => path,
+        }
+    }
+
+    /// The event as one line of JSON
+    pub fn to_json(&self, time: &str) -> String {
+        let mut fields = vec![
+            format!("\"event\": {}", json_string(self.name())),
+            format!("\"time\": {}", json_string(time)),
+            format!("\"path\": {}", json_string(&self.path().display().to_string())),
+        ];
+
+        match self {
+            WatchEvent::Match { query, saved, matches, .. } => {
+                fields.push(format!("\"query\": {}", json_string(query)));
+                fields.push(format!("\"saved\": {}", saved.as_deref().map_or("null".to_string(), json_string)));
+                fields.push(format!("\"matches\": {}", matches));
+            },
+            WatchEvent::Error { message, .. } => {
+                fields.push(format!("\"message\": {}", json_string(message)));
+            },
+            WatchEvent::Indexed { .. } | WatchEvent::Removed { .. } => {},
+        }
+
+        format!("{{{}}}", fields.join(", "))
+    }
+
+    /// The event as a line of text
+    pub fn to_text(&self, time: &str) -> String {
+        let path = self.path().display();
+        match self {
+            WatchEvent::Match { query, saved, matches, .. } => {
+                let saved = saved.as_ref().map(|name| format!(" (saved search '{}')", name)).unwrap_or_default();
+                format!("[{}] MATCH {}: {} match(es) for \"{}\"{}", time, path, matches, query, saved)
+            },
+            WatchEvent::Indexed { .. } => format!("[{}] Indexed {}", time, path),
+            WatchEvent::Removed { .. } => format!("[{}] Removed {}", time, path),
+            WatchEvent::Error { message, .. } => format!("[{}] Error reading {}: {}", time, path, message),
+        }
+    }
+}
+
+/// Options for `pdfscan watch`
+#[derive(Debug, Clone, Default)]
+pub struct WatchOptions {
+    /// Print events as JSON lines instead of text
+    pub json: bool,
+    /// Shell command run for each match
+    pub exec: Option<String>,
+}
+
+/// Extract the text of a new or changed PDF into the cache of its directory and check
+/// it against the queries
+pub fn index_pdf(path: &Path, queries: &[StandingQuery]) -> Vec<WatchEvent> {
+    let record = match extract_record(path) {
+        Ok(record) => record,
+        Err(message) => return vec![WatchEvent::Error { path: path.to_path_buf(), message }],
+    };
+    let text = record.text();
+
+    let cache_path = record_cache_path(path);
+    let mut events = vec![match save_record_to_cache(&cache_path, &record) {
+        Ok(()) => WatchEvent::Indexed { path: path.to_path_buf() },
+        Err(e) => WatchEvent::Error { path: path.to_path_buf(), message: format!("Cannot write cache: {}", e) },
+    }];
+
+    for query in queries.iter().filter(|query| query.options.accepts(path)) {
+        let matches = find_matches(&text, &query.query, &query.options).len();
+        if matches > 0 {
+            events.push(WatchEvent::Match {
+                path: path.to_path_buf(),
+                query: query.query.clone(),
+                saved: query.saved.clone(),
+                matches,
+            });
+        }
+    }
+
+    events
+}
+
+/// Run the hook command for a match, with the event in environment variables
+fn run_hook(command: &str, event: &WatchEvent, json: &str) {
+    let WatchEvent::Match { path, query, matches, .. } = event else {
+        return;
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    let status = shell
+        .arg(command)
+        .env("PDFSCAN_PATH", path)
+        .env("PDFSCAN_QUERY", query)
+        .env("PDFSCAN_MATCHES", matches.to_string())
+        .env("PDFSCAN_EVENT", json)
+        .status();
+
+    match status {
+        Ok(status) if !status.success() => eprintln!("Hook command exited with {} for {}", status, path.display()),
+        Err(e) => eprintln!("Cannot run hook command: {}", e),
+        Ok(_) => {},
+    }
+}
+
+/// Print an event: matches to stdout, the rest to stderr unless printing JSON
+fn emit(event: &WatchEvent, options: &WatchOptions) {
+    let time = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+    let json = event.to_json(&time);
+
+    if options.json {
+        println!("{}", json);
+    } else if matches!(event, WatchEvent::Match { .. }) {
+        println!("{}", event.to_text(&time));
+    } else {
+        eprintln!("{}", event.to_text(&time));
+    }
+
+    if let Some(command) = &options.exec {
+        run_hook(command, event, &json);
+    }
+}
+
+/// Watch directories until interrupted, indexing new and changed PDFs and
+/// reporting those that match any of the queries
+pub fn run(directories: &[PathBuf], queries: &[StandingQuery], options: &WatchOptions) -> Result<(), Box<dyn Error>> {
+    let mut watcher = DirectoryWatcher::new(directories, || {})?;
+
+    let listed: Vec<String> = directories.iter().map(|dir| dir.display().to_string()).collect();
+    eprintln!(
+        "Watching {} for new and changed PDFs with {} quer{} (Ctrl+C to stop)",
+        listed.join(", "),
+        queries.len(),
+        if queries.len() == 1 { "y" } else { "ies" }
+    );
+
+    loop {
+        let (changes, errors) = watcher.changes(POLL_INTERVAL)?;
+        for error in errors {
+            eprintln!("Watch error: {}", error);
+        }
+
+        for change in changes {
+            match change {
+                Change::Updated(path) => {
+                    for event in index_pdf(&path, queries) {
+                        emit(&event, options);
+                    }
+                },
+                Change::Removed(path) => {
+                    let _ = fs::remove_file(record_cache_path(&path));
+                    emit(&WatchEvent::Removed { path }, options);
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use egui::Color32;
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, Stream};
+use pdfscan::gui::annotations::{load_annotations, save_annotations, Annotation, AnnotationKind};
+use std::fs;
+use std::path::Path;
+
+/// Write a one-page PDF showing `text`
+fn write_pdf(path: &Path, text: &str) {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+            Operation::new("Td", vec![72.into(), 700.into()]),
+            Operation::new("Tj", vec![Object::string_literal(text)]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+    });
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![page_id.into()],
+        "Count" => 1,
+    }));
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).unwrap();
+}
+
+#[test]
+fn test_saved_annotations_are_appended_and_reloaded() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-annotations-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("annotated.pdf");
+    write_pdf(&path, "annotate me");
+    let original = fs::read(&path).unwrap();
+
+    let highlight = Annotation::new_highlight(0, [72.0, 690.0, 180.0, 710.0], Color32::from_rgb(255, 230, 0));
+    let note = Annotation::new_note(0, 300.0, 500.0, "Check this".to_string());
+    assert_eq!(save_annotations(&path, &[highlight, note]).unwrap(), 2);
+
+    // An incremental update leaves the original bytes in place
+    let saved = fs::read(&path).unwrap();
+    assert!(saved.len() > original.len());
+    assert_eq!(&saved[..original.len()], &original[..]);
+    assert!(!path.with_extension("pdf.pdfscan-tmp").exists());
+
+    let doc = Document::load(&path).unwrap();
+    let page_id = doc.get_pages()[&1];
+    let annots = doc.get_dictionary(page_id).unwrap().get(b"Annots").unwrap().as_array().unwrap();
+    assert_eq!(annots.len(), 2);
+
+    let loaded = load_annotations(&doc);
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.unsaved_count(), 0);
+    assert_eq!(loaded.media_box(0), [0.0, 0.0, 595.0, 842.0]);
+    let page = loaded.page(0);
+    let highlight = page.iter().find(|a| a.kind == AnnotationKind::Highlight).unwrap();
+    assert_eq!(highlight.rect, [72.0, 690.0, 180.0, 710.0]);
+    assert_eq!(highlight.quad_points.len(), 1);
+    let note = page.iter().find(|a| a.kind == AnnotationKind::Note).unwrap();
+    assert_eq!(note.contents, "Check this");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,170 @@
+use pdfscan::cache::{
+    cache_directory_for, cache_stats, central_entries, clear, directory_key, get_cache_path, in_tree_entries,
+    load_record_from_cache, prune, record_cache_path, save_record_to_cache, source_stamp, CacheLocation, CacheRecord,
+    LEGACY_BACKEND,
+};
+use pdfscan::config::{self, Config};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_central_cache_is_keyed_by_absolute_path() {
+    let mut cache = Config::default().cache;
+    cache.location = CacheLocation::Central;
+    cache.central_directory = Some(PathBuf::from("/var/cache/pdfscan"));
+
+    let papers = cache_directory_for(Path::new("/data/a/papers"), &cache);
+    let other = cache_directory_for(Path::new("/data/b/papers"), &cache);
+
+    assert_eq!(papers, Path::new("/var/cache/pdfscan").join(directory_key(Path::new("/data/a/papers"))));
+    assert_ne!(papers, other);
+    assert_eq!(directory_key(Path::new("/data/a/papers")).len(), 16);
+
+    cache.location = CacheLocation::InTree;
+    assert_eq!(cache_directory_for(Path::new("/data/a/papers"), &cache), PathBuf::from("/data/a/papers/.pdfscan"));
+}
+
+#[test]
+fn test_prune_removes_cache_of_removed_pdfs() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-cache-{}", std::process::id()));
+    let cache_dir = dir.join(".pdfscan");
+    fs::create_dir_all(cache_dir.join("thumbnails")).unwrap();
+    fs::write(dir.join("kept.pdf"), b"%PDF-1.4").unwrap();
+    fs::write(cache_dir.join("kept.txt"), "kept").unwrap();
+    fs::write(cache_dir.join("gone.txt"), "gone").unwrap();
+    fs::write(cache_dir.join("thumbnails").join("gone_p1_18dpi.png"), b"png").unwrap();
+
+    let entries = in_tree_entries(std::slice::from_ref(&dir), ".pdfscan");
+    let stats = cache_stats(&entries);
+    assert_eq!((stats.directories, stats.texts, stats.thumbnails, stats.stale), (1, 2, 1, 2));
+
+    let removed = prune(&entries).unwrap();
+    assert_eq!((removed.texts, removed.thumbnails, removed.directories), (1, 1, 0));
+    assert!(cache_dir.join("kept.txt").exists());
+    assert!(!cache_dir.join("gone.txt").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_prune_keeps_records_of_nested_pdfs() {
+    // Cache with the defaults, not as the developer's config.toml or a .pdfscan.toml says
+    config::init(Config::default());
+    let dir = std::env::temp_dir().join(format!("pdfscan-cache-nested-{}", std::process::id()));
+    let nested = dir.join("2024").join("q1");
+    fs::create_dir_all(&nested).unwrap();
+    let pdf = nested.join("report.pdf");
+    fs::write(&pdf, b"%PDF-1.4").unwrap();
+
+    // Records are kept next to the PDF's own directory, however deep below the scanned root
+    let record_path = record_cache_path(&pdf);
+    assert_eq!(record_path, nested.join(".pdfscan").join("report.record.zst"));
+    save_record_to_cache(&record_path, &CacheRecord::from_text("quarterly", "test", "1")).unwrap();
+
+    let entries = in_tree_entries(std::slice::from_ref(&dir), ".pdfscan");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(cache_stats(&entries).stale, 0);
+    assert_eq!(prune(&entries).unwrap(), Default::default());
+    assert!(record_path.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_only_recognisable_caches_are_managed() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-cache-foreign-{}", std::process::id()));
+    let pdfs = dir.join("pdfs");
+    fs::create_dir_all(&pdfs).unwrap();
+
+    // A central root shared with other files: only key-named directories with a note are caches
+    let root = dir.join("central");
+    let key = directory_key(&pdfs);
+    fs::create_dir_all(root.join(&key)).unwrap();
+    fs::write(root.join(&key).join("source.txt"), pdfs.canonicalize().unwrap().to_string_lossy().as_bytes()).unwrap();
+    fs::create_dir_all(root.join("projects")).unwrap();
+    fs::write(root.join("projects").join("notes.md"), "mine").unwrap();
+    fs::create_dir_all(root.join("0123456789abcdef")).unwrap();
+
+    let central = central_entries(&root, &[]).unwrap();
+    assert_eq!(central.len(), 1);
+    assert_eq!(central[0].cache_dir, root.join(&key));
+
+    // A directory that happens to have the cache's name but holds other files
+    fs::create_dir_all(pdfs.join(".pdfscan")).unwrap();
+    fs::write(pdfs.join(".pdfscan").join("settings.json"), "{}").unwrap();
+    assert!(in_tree_entries(std::slice::from_ref(&dir), ".pdfscan").is_empty());
+
+    clear(&central).unwrap();
+    assert!(!root.join(&key).exists());
+    assert!(root.join("projects").join("notes.md").exists());
+    assert!(root.join("0123456789abcdef").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_record_round_trip() {
+    let record = CacheRecord {
+        pages: vec!["First page\n".to_string(), "Zweite Seite – ü\n".to_string()],
+        metadata: vec![("Title".to_string(), "Annual Report".to_string())],
+        backend: "pdf-extract".to_string(),
+        backend_version: "0.7".to_string(),
+        source_size: 48_213,
+        source_modified: 1_700_000_000_123_456_789,
+        warnings: vec!["Cannot read document information".to_string()],
+    };
+
+    let bytes = record.encode().unwrap();
+    let decoded = CacheRecord::decode(&bytes).unwrap();
+
+    assert_eq!(decoded, record);
+    assert_eq!(decoded.text(), "First page\n\nZweite Seite – ü\n");
+    assert_eq!(decoded.metadata_value("Title"), Some("Annual Report"));
+    assert!(CacheRecord::decode(&bytes[..bytes.len() - 3]).is_err());
+
+    // Pages are extracted without a trailing line break
+    let record = CacheRecord { pages: vec!["last word".to_string(), "first word".to_string()], ..Default::default() };
+    assert_eq!(record.text(), "last word\nfirst word");
+}
+
+#[test]
+fn test_plain_text_cache_is_migrated() {
+    // Cache with the defaults, not as the developer's config.toml or a .pdfscan.toml says
+    config::init(Config::default());
+    let dir = std::env::temp_dir().join(format!("pdfscan-migrate-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let pdf = dir.join("report.pdf");
+    fs::write(&pdf, b"%PDF-1.4").unwrap();
+    fs::write(dir.join("report.txt"), "old cached text").unwrap();
+
+    let cache_path = get_cache_path(&pdf, &dir);
+    let record = load_record_from_cache(&pdf, &cache_path).unwrap();
+
+    assert_eq!(record.text(), "old cached text");
+    assert_eq!(record.backend, LEGACY_BACKEND);
+    assert!(cache_path.exists());
+    assert!(!dir.join("report.txt").exists());
+    assert_eq!(load_record_from_cache(&pdf, &cache_path).unwrap().text(), "old cached text");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_records_of_changed_pdfs_are_stale() {
+    // Cache with the defaults, not as the developer's config.toml or a .pdfscan.toml says
+    config::init(Config::default());
+    let dir = std::env::temp_dir().join(format!("pdfscan-cache-stale-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let pdf = dir.join("report.pdf");
+    fs::write(&pdf, b"%PDF-1.4").unwrap();
+
+    let cache_path = record_cache_path(&pdf);
+    let record = CacheRecord::from_text("first draft", "test", "1").with_source(source_stamp(&pdf).unwrap());
+    save_record_to_cache(&cache_path, &record).unwrap();
+    assert_eq!(load_record_from_cache(&pdf, &cache_path), Some(record));
+
+    fs::write(&pdf, b"%PDF-1.7 edited").unwrap();
+    assert_eq!(load_record_from_cache(&pdf, &cache_path), None);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
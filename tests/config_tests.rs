@@ -0,0 +1,64 @@
+use pdfscan::config::{Config, ConfigError};
+use pdfscan::stats::{CooccurrenceWindow, CorrelationMeasure};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[test]
+fn test_later_files_override_earlier_ones() {
+    let mut config = Config::default();
+    let user = "[viewer]\ndpi = 150\n\n[analysis]\nmeasure = \"pearson\"\nthreshold = 0.3\n";
+    let project = "[search]\nroots = [\"papers\", \"/archive\"]\n\n[analysis]\nwindow = \"words:50\"\nthreshold = 1\n";
+
+    config.merge_toml(user, Path::new("/home/me/.config/pdfscan/config.toml")).unwrap();
+    config.merge_toml(project, Path::new("/work/thesis/.pdfscan.toml")).unwrap();
+
+    assert_eq!(config.viewer.dpi, 150);
+    assert_eq!(config.viewer.thumbnail_dpi, 18);
+    assert_eq!(config.analysis.measure, CorrelationMeasure::Pearson);
+    assert_eq!(config.analysis.window, CooccurrenceWindow::Words(50));
+    assert_eq!(config.analysis.threshold, 1.0);
+    // Relative roots are relative to the file that names them
+    assert_eq!(config.search.roots, vec![PathBuf::from("/work/thesis/papers"), PathBuf::from("/archive")]);
+    assert_eq!(config.sources.len(), 2);
+}
+
+#[test]
+fn test_invalid_settings_are_errors() {
+    let path = Path::new("config.toml");
+
+    let unknown = Config::default().merge_toml("[viewer]\nzoom = 2\n", path);
+    assert!(matches!(unknown, Err(ConfigError::UnknownKey(_, key)) if key == "viewer.zoom"));
+
+    let negative = Config::default().merge_toml("[viewer]\ndpi = -5\n", path);
+    assert!(matches!(negative, Err(ConfigError::InvalidValue(_, key, _)) if key == "viewer.dpi"));
+
+    let measure = Config::default().merge_toml("[analysis]\nmeasure = \"cosine\"\n", path);
+    assert!(matches!(measure, Err(ConfigError::InvalidValue(..))));
+}
+
+#[test]
+fn test_shown_config_reads_back_the_same() {
+    let mut config = Config::default();
+    config.merge_toml("[cache]\ndirectory_name = \".cache\"\n\n[performance]\nthreads = 4\n", Path::new("/p/.pdfscan.toml")).unwrap();
+
+    let mut reread = Config::default();
+    reread.merge_toml(&config.to_toml(), Path::new("/p/.pdfscan.toml")).unwrap();
+
+    assert_eq!(reread.cache.directory_name, ".cache");
+    assert_eq!(reread.threads, Some(4));
+    assert_eq!(reread.analysis, config.analysis);
+    assert_eq!(reread.search, config.search);
+}
+
+#[test]
+fn test_extraction_isolation_settings() {
+    let mut config = Config::default();
+    assert!(!config.isolation.enabled);
+
+    config.merge_toml("[extraction]\nisolate = true\ntimeout_secs = 15\nmemory_limit_mb = 0\n", Path::new("config.toml")).unwrap();
+
+    assert!(config.isolation.enabled);
+    assert_eq!(config.isolation.timeout, Duration::from_secs(15));
+    assert_eq!(config.isolation.memory_limit_mb, None);
+    assert!(Config::default().merge_toml("[extraction]\nisolate = \"yes\"\n", Path::new("config.toml")).is_err());
+}
@@ -0,0 +1,77 @@
+use pdfscan::discover::{discover_texts, DiscoveryMethod, DiscoveryOptions};
+use pdfscan::stopwords::{Language, Stopwords};
+use pdfscan::text::segments;
+use std::path::PathBuf;
+
+fn corpus() -> Vec<(PathBuf, String)> {
+    vec![
+        (
+            PathBuf::from("networks.pdf"),
+            "Neural networks learn representations. Training neural networks needs data, \
+             and neural networks generalize."
+                .to_string(),
+        ),
+        (
+            PathBuf::from("gardens.pdf"),
+            "The garden has roses. Roses need sunlight and the garden needs water.".to_string(),
+        ),
+    ]
+}
+
+#[test]
+fn test_segments_split_at_punctuation() {
+    let segments = segments("State-of-the-art models don't wrap\nlines; they (mostly) work.");
+
+    assert_eq!(
+        segments,
+        vec![
+            vec!["state-of-the-art", "models", "don't", "wrap", "lines"],
+            vec!["they"],
+            vec!["mostly"],
+            vec!["work"],
+        ]
+    );
+}
+
+#[test]
+fn test_stopwords_per_language() {
+    let english = Stopwords::new(&[Language::English]);
+    assert!(english.contains("the"));
+    assert!(!english.contains("und"));
+
+    let german = Stopwords::new(&["de".parse().unwrap()]);
+    assert!(german.contains("und"));
+}
+
+#[test]
+fn test_tfidf_proposes_repeated_phrases() {
+    let report = discover_texts(&corpus(), &DiscoveryOptions::default());
+
+    assert_eq!(report.documents.len(), 2);
+    assert_eq!(report.documents[0].terms[0].text, "neural networks");
+    assert!(report.keywords().contains(&"neural networks".to_string()));
+    // Words of a listed phrase and stopwords are not proposed on their own
+    assert!(!report.keywords().contains(&"neural".to_string()));
+    assert!(!report.keywords().contains(&"the".to_string()));
+    assert!(report.to_text().contains("Suggested keywords: --keywords \"neural networks\""));
+}
+
+#[test]
+fn test_rake_and_textrank_rank_per_document() {
+    for method in [DiscoveryMethod::Rake, DiscoveryMethod::TextRank] {
+        let options = DiscoveryOptions { method, top: 5, ..Default::default() };
+        let report = discover_texts(&corpus(), &options);
+
+        assert_eq!(report.corpus.len(), 5, "{}", method);
+        assert_eq!(report.documents[0].terms[0].text, "neural networks", "{}", method);
+        assert!(report.documents[1].terms.iter().any(|term| term.text == "sunlight"), "{}", method);
+        assert!(report.documents[1].terms.iter().all(|term| !term.text.contains("neural")), "{}", method);
+    }
+}
+
+#[test]
+fn test_method_names_parse() {
+    assert_eq!("tf-idf".parse::<DiscoveryMethod>(), Ok(DiscoveryMethod::TfIdf));
+    assert_eq!("TextRank".parse::<DiscoveryMethod>(), Ok(DiscoveryMethod::TextRank));
+    assert!("lda".parse::<DiscoveryMethod>().is_err());
+}
@@ -0,0 +1,152 @@
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, Stream};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Write a one-page PDF showing `text`
+fn write_pdf(path: &Path, text: &str) {
+    write_pdf_with_font(path, text, dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+}
+
+/// Write a one-page PDF showing `text` in the given font
+fn write_pdf_with_font(path: &Path, text: &str, font: lopdf::Dictionary) {
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(font);
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+    let content = Content {
+        operations: vec![
+            Operation::new("BT", vec![]),
+            Operation::new("Tf", vec!["F1".into(), 12.into()]),
+            Operation::new("Td", vec![72.into(), 700.into()]),
+            Operation::new("Tj", vec![Object::string_literal(text)]),
+            Operation::new("ET", vec![]),
+        ],
+    };
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+    });
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![page_id.into()],
+        "Count" => 1,
+        "Resources" => resources_id,
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+    }));
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).unwrap();
+}
+
+/// Run `pdfscan extract` with isolated extraction on a directory of PDFs
+fn extract_isolated(dir: &Path, pdfs: &Path, output_file: &Path) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_pdfscan"))
+        .args(["--isolate", "--timeout", "1", "--cache", "disabled", "extract"])
+        .arg(output_file)
+        .arg(pdfs)
+        .current_dir(dir)
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_isolated_extract_records_failures_and_continues() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-isolation-{}", std::process::id()));
+    let pdfs = dir.join("pdfs");
+    fs::create_dir_all(&pdfs).unwrap();
+    write_pdf(&pdfs.join("good.pdf"), "isolated extraction works");
+    fs::write(pdfs.join("broken.pdf"), b"%PDF-1.4 this is not a PDF").unwrap();
+    // Opening a FIFO blocks until something writes to it, so its worker never finishes
+    let fifo: Option<PathBuf> = cfg!(unix).then(|| pdfs.join("stuck.pdf"));
+    if let Some(fifo) = &fifo {
+        assert!(Command::new("mkfifo").arg(fifo).status().unwrap().success());
+    }
+
+    let output_file = dir.join("out.txt");
+    let output = extract_isolated(&dir, &pdfs, &output_file);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "stderr: {}", stderr);
+    let expected = if fifo.is_some() { "from 1 of 3 PDFs" } else { "from 1 of 2 PDFs" };
+    assert!(stdout.contains(expected), "stdout: {}", stdout);
+    assert!(fs::read_to_string(&output_file).unwrap().contains("isolated extraction works"));
+
+    // The worker's error for the malformed file, and the timeout for the stuck one
+    let failure = |name: &str| stderr.lines().find(|line| line.contains(name)).unwrap_or_default().to_string();
+    assert!(failure("broken.pdf").contains("Cannot extract text"), "stderr: {}", stderr);
+    if fifo.is_some() {
+        assert!(failure("stuck.pdf").contains("Extraction timed out after 1 s"), "stderr: {}", stderr);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_isolated_extract_ignores_extractor_output() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-isolation-glyphs-{}", std::process::id()));
+    let pdfs = dir.join("pdfs");
+    fs::create_dir_all(&pdfs).unwrap();
+    // The extractor prints a diagnostic to stdout for a glyph name it doesn't know
+    write_pdf_with_font(&pdfs.join("glyphs.pdf"), "unknown glyphs are harmless", dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => dictionary! {
+            "Type" => "Encoding",
+            "BaseEncoding" => "WinAnsiEncoding",
+            "Differences" => vec![200.into(), "pdfscanNoSuchGlyph".into()],
+        },
+    });
+
+    let output_file = dir.join("out.txt");
+    let output = extract_isolated(&dir, &pdfs, &output_file);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "stderr: {}", stderr);
+    assert!(stdout.contains("from 1 of 1 PDFs"), "stdout: {}, stderr: {}", stdout, stderr);
+    assert!(fs::read_to_string(&output_file).unwrap().contains("unknown glyphs are harmless"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_worker_does_not_follow_symlinks_or_create_record_files() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-isolation-symlink-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let pdf = dir.join("report.pdf");
+    write_pdf(&pdf, "worker output");
+    let victim = dir.join("victim.txt");
+    fs::write(&victim, "keep me").unwrap();
+    let link = dir.join("planted.record.zst");
+    std::os::unix::fs::symlink(&victim, &link).unwrap();
+
+    let worker = |output: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_pdfscan")).arg("--extract-worker").arg(&pdf).arg(output).output().unwrap()
+    };
+
+    assert!(!worker(&link).status.success());
+    assert_eq!(fs::read_to_string(&victim).unwrap(), "keep me");
+    let missing = dir.join("missing.record.zst");
+    assert!(!worker(&missing).status.success());
+    assert!(!missing.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
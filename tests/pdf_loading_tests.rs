@@ -1,14 +1,14 @@
 // Integration tests for PDF loading functionality
 // These tests verify that PDFs can be loaded and text extracted correctly
 
-use pdfscan::gui::PdfViewer;
+use pdfscan::gui::{JobManager, PdfViewer};
 use std::path::Path;
 use lopdf::Document;
 
 #[test]
 fn test_pdf_viewer_initialization() {
     // Test that PdfViewer can be created without errors
-    let viewer = PdfViewer::new();
+    let viewer = PdfViewer::new(JobManager::new(&egui::Context::default()));
     
     // Verify initial state using public methods
     assert_eq!(viewer.total_pages(), 0);
@@ -40,7 +40,7 @@ fn test_text_extraction_api() {
 #[test]
 fn test_pdf_viewer_load_pdf() {
     // Test that PdfViewer can initiate PDF loading
-    let mut viewer = PdfViewer::new();
+    let mut viewer = PdfViewer::new(JobManager::new(&egui::Context::default()));
     
     // Create a temporary file path (doesn't need to exist for this test)
     let test_path = Path::new("/tmp/test_nonexistent.pdf");
@@ -56,7 +56,7 @@ fn test_pdf_viewer_load_pdf() {
 #[test]
 fn test_pdf_viewer_page_navigation() {
     // Test that page navigation API is available
-    let viewer = PdfViewer::new();
+    let viewer = PdfViewer::new(JobManager::new(&egui::Context::default()));
     
     // Verify we can get page count (public API)
     assert_eq!(viewer.total_pages(), 0);
@@ -68,7 +68,7 @@ fn test_pdf_viewer_page_navigation() {
 #[test]
 fn test_pdf_viewer_state_management() {
     // Test that PdfViewer correctly manages its state
-    let viewer = PdfViewer::new();
+    let viewer = PdfViewer::new(JobManager::new(&egui::Context::default()));
     
     // Verify initial state using public methods
     assert_eq!(viewer.total_pages(), 0);
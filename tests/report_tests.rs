@@ -0,0 +1,55 @@
+use pdfscan::report::{render, ReportFormat};
+use pdfscan::stats::{analyze_counts, AnalysisOptions, AnalysisReport, Document};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+fn report() -> AnalysisReport {
+    let keywords = vec!["alpha".to_string(), "beta|b<eta>".to_string()];
+    let documents = (0..25)
+        .map(|i| {
+            let counts: HashMap<String, usize> = [("alpha".to_string(), i % 3), ("beta|b<eta>".to_string(), i % 2)]
+                .into_iter()
+                .collect();
+            Document::new(PathBuf::from(format!("doc{:02}.pdf", i)), counts, 100)
+        })
+        .collect();
+
+    analyze_counts(documents, &keywords, &AnalysisOptions::default())
+}
+
+#[test]
+fn test_html_has_heatmap_and_every_document() {
+    let html = render(&report(), ReportFormat::Html);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<th>beta|b&lt;eta&gt;</th>"));
+    assert!(html.contains("style=\"background: rgb("));
+    // All documents are listed, not just the top 20
+    assert_eq!(html.matches("<td class=\"name\"").count(), 25);
+}
+
+#[test]
+fn test_markdown_json_and_csv_name_keywords() {
+    let report = report();
+
+    let markdown = render(&report, ReportFormat::Markdown);
+    assert!(markdown.contains("| | alpha | beta\\|b<eta> |"));
+
+    let json = render(&report, ReportFormat::Json);
+    assert!(json.contains("\"keywords\": [\"alpha\", \"beta|b<eta>\"]"));
+    assert!(json.contains("\"statistics\": null"));
+    assert_eq!(json.matches("\"filename\"").count(), 25);
+
+    let csv = render(&report, ReportFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "rank,filename,path,score,words,alpha,beta|b<eta>");
+    assert_eq!(lines.len(), 26);
+}
+
+#[test]
+fn test_format_from_name_or_extension() {
+    assert_eq!("md".parse::<ReportFormat>(), Ok(ReportFormat::Markdown));
+    assert_eq!(ReportFormat::from_path(Path::new("out/report.HTML")), Some(ReportFormat::Html));
+    assert_eq!(ReportFormat::from_path(Path::new("report.pdf")), None);
+    assert!("xml".parse::<ReportFormat>().is_err());
+}
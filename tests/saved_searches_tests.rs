@@ -0,0 +1,60 @@
+use pdfscan::saved_searches::{add_to_history, format_saved_searches, parse_saved_searches, SavedSearch, SearchScope};
+use pdfscan::search::{find_matches, SearchMode, SearchOptions};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_saved_searches_round_trip() {
+    let searches = vec![
+        SavedSearch {
+            name: "Contracts".to_string(),
+            query: "termination\tclause".to_string(),
+            scope: SearchScope::Directory,
+            directory: Some(PathBuf::from("/archive/legal")),
+            options: SearchOptions { mode: SearchMode::WholeWord, case_sensitive: true, filter: "2024".to_string() },
+        },
+        SavedSearch {
+            name: "Here".to_string(),
+            query: "a, b".to_string(),
+            scope: SearchScope::CurrentDocument,
+            directory: None,
+            options: SearchOptions::default(),
+        },
+    ];
+
+    let content = format_saved_searches(&searches);
+    let parsed = parse_saved_searches(&format!("{}not a saved search\n", content));
+
+    assert_eq!(parsed.len(), 2);
+    // Tabs would split the line, so they are saved as spaces
+    assert_eq!(parsed[0].query, "termination clause");
+    assert_eq!(parsed[0].options, searches[0].options);
+    assert_eq!(parsed[0].directory, searches[0].directory);
+    assert_eq!(parsed[1], searches[1]);
+}
+
+#[test]
+fn test_history_moves_repeated_queries_to_front() {
+    let mut history = vec!["beta".to_string(), "alpha".to_string()];
+
+    add_to_history(&mut history, "alpha");
+    add_to_history(&mut history, "  ");
+    add_to_history(&mut history, "gamma");
+
+    assert_eq!(history, ["gamma", "alpha", "beta"]);
+}
+
+#[test]
+fn test_whole_word_and_case_options() {
+    let text = "Cat catalog cat. Ärger ärger";
+    let options = |mode, case_sensitive| SearchOptions { mode, case_sensitive, filter: String::new() };
+
+    assert_eq!(find_matches(text, "cat", &options(SearchMode::Substring, false)), vec![0, 4, 12]);
+    assert_eq!(find_matches(text, "cat", &options(SearchMode::WholeWord, false)), vec![0, 12]);
+    assert_eq!(find_matches(text, "cat", &options(SearchMode::WholeWord, true)), vec![12]);
+    // Offsets are in characters of the original text
+    assert_eq!(find_matches(text, "ärger", &options(SearchMode::Substring, false)), vec![17, 23]);
+
+    let filtered = SearchOptions { filter: "Report".to_string(), ..Default::default() };
+    assert!(filtered.accepts(Path::new("/x/annual_report_2024.pdf")));
+    assert!(!filtered.accepts(Path::new("/report/summary.pdf")));
+}
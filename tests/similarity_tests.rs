@@ -0,0 +1,112 @@
+use pdfscan::similarity::{cluster, dedupe, diff_lines, DedupeOptions, DiffLine, Fingerprint, SimilarityMethod};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+
+const OFFICE: &[&str] = &[
+    "archive", "budget", "council", "draft", "estimate", "forecast", "grant", "harbour", "invoice",
+    "journal", "ledger", "minutes", "notice", "order", "permit", "quota", "report", "survey",
+    "tender", "update",
+];
+
+const NATURE: &[&str] = &[
+    "alder", "brook", "cedar", "delta", "fern", "glacier", "heron", "island", "juniper", "kelp",
+    "lichen", "meadow", "nettle", "orchid", "pine", "reed", "sedge", "tundra", "willow", "yarrow",
+];
+
+/// Deterministic pseudo-random text of `words` words
+fn text(seed: u64, words: usize, vocabulary: &[&str]) -> String {
+    let mut state = seed;
+    (0..words)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            vocabulary[(state >> 33) as usize % vocabulary.len()]
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn fingerprint(name: &str, bytes: &[u8], text: &str) -> Fingerprint {
+    Fingerprint::new(PathBuf::from(name), bytes, text, 5)
+}
+
+fn corpus() -> Vec<Fingerprint> {
+    let original = text(1, 400, OFFICE);
+    // A revised draft: one word changed near the end
+    let revised = original.replacen("survey", "census", 1);
+    let unrelated = text(2, 400, NATURE);
+
+    vec![
+        fingerprint("a.pdf", b"original bytes", &original),
+        fingerprint("b.pdf", b"revised bytes", &revised),
+        fingerprint("c.pdf", b"original bytes", &original),
+        fingerprint("d.pdf", b"unrelated bytes", &unrelated),
+        // Scans without text are only matched by their bytes
+        fingerprint("scan1.pdf", b"scan", ""),
+        fingerprint("scan2.pdf", b"scan", ""),
+        fingerprint("scan3.pdf", b"another scan", ""),
+    ]
+}
+
+#[test]
+fn test_minhash_clusters_duplicates_and_revisions() {
+    let report = cluster(&corpus(), &DedupeOptions::default());
+
+    assert_eq!(report.total_documents, 7);
+    assert_eq!(report.without_text, 3);
+    assert_eq!(report.clusters.len(), 2);
+
+    let drafts = &report.clusters[0];
+    let paths: Vec<&str> = drafts.members.iter().map(|m| m.path.to_str().unwrap()).collect();
+    assert_eq!(paths, vec!["a.pdf", "b.pdf", "c.pdf"]);
+    assert!(!drafts.is_exact());
+    assert!(!drafts.members[1].exact && drafts.members[1].similarity >= 0.8);
+    assert!(drafts.members[2].exact);
+
+    assert!(report.clusters[1].is_exact());
+    assert_eq!(report.clusters[1].members.len(), 2);
+    assert!(report.to_text().contains("Duplicate clusters: 2"));
+}
+
+#[test]
+fn test_simhash_clusters_revisions() {
+    let options = DedupeOptions { method: SimilarityMethod::SimHash, threshold: 0.9, ..DedupeOptions::default() };
+    let fingerprints = corpus();
+
+    assert!(fingerprints[0].similarity(&fingerprints[1], SimilarityMethod::SimHash) >= 0.9);
+    assert!(fingerprints[0].similarity(&fingerprints[3], SimilarityMethod::SimHash) < 0.9);
+    assert!(fingerprints[0].similarity(&fingerprints[3], SimilarityMethod::MinHash) < 0.2);
+
+    let report = cluster(&fingerprints, &options);
+    assert_eq!(report.clusters[0].members.len(), 3);
+}
+
+#[test]
+fn test_diff_lines() {
+    let diff = diff_lines("title\nfirst\nsecond\nend", "title\nfirst\nchanged\nend\nappendix");
+
+    assert_eq!(
+        diff,
+        vec![
+            DiffLine::Same("title".to_string()),
+            DiffLine::Same("first".to_string()),
+            DiffLine::Removed("second".to_string()),
+            DiffLine::Added("changed".to_string()),
+            DiffLine::Same("end".to_string()),
+            DiffLine::Added("appendix".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_cancelled_dedupe_stops_with_an_error() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-dedupe-cancel-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.pdf"), b"%PDF-1.4").unwrap();
+    std::fs::write(dir.join("b.pdf"), b"%PDF-1.4").unwrap();
+
+    let input_paths = vec![dir.to_string_lossy().to_string()];
+    let result = dedupe(&input_paths, &DedupeOptions::default(), &AtomicBool::new(true));
+    assert!(result.unwrap_err().to_string().contains("cancelled"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,312 @@
+// Integration tests for keyword statistics
+// These tests verify the structured analysis results without needing real PDFs
+
+use pdfscan::stats::{
+    analyze, analyze_counts, analyze_records, analyze_with_progress, count_windows, AnalysisOptions, CooccurrenceWindow,
+    CorrelationMeasure, Document, RankingMethod,
+};
+use pdfscan::cache::CacheRecord;
+use pdfscan::text::{KeywordMatcher, MatchOptions};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+
+fn counts(pairs: &[(&str, usize)]) -> HashMap<String, usize> {
+    pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+}
+
+fn keywords(words: &[&str]) -> Vec<String> {
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+fn options(measure: CorrelationMeasure, threshold: f64, ranking: RankingMethod) -> AnalysisOptions {
+    AnalysisOptions { measure, threshold, ranking, ..AnalysisOptions::default() }
+}
+
+#[test]
+fn test_correlation_matrix_is_symmetric() {
+    let keywords = keywords(&["rust", "pdf", "search"]);
+    let documents = vec![
+        Document::new(PathBuf::from("a.pdf"), counts(&[("rust", 4), ("pdf", 2), ("search", 0)]), 0),
+        Document::new(PathBuf::from("b.pdf"), counts(&[("rust", 1), ("pdf", 3), ("search", 5)]), 0),
+    ];
+
+    let report = analyze_counts(documents, &keywords, &options(CorrelationMeasure::CoOccurrence, 0.0, RankingMethod::Correlation));
+
+    assert_eq!(report.keywords, keywords);
+    assert_eq!(report.correlations.len(), 3);
+    for i in 0..3 {
+        assert_eq!(report.correlations[i][i], 0.0);
+        for j in 0..3 {
+            assert_eq!(report.correlations[i][j], report.correlations[j][i]);
+        }
+    }
+
+    // rust/pdf co-occur in both documents: (min(4,2) + min(1,3)) / 2
+    assert!((report.correlations[0][1] - 1.5).abs() < 1e-9);
+    // rust/search only in b.pdf: min(1,5) / 2
+    assert!((report.correlations[0][2] - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_documents_are_ranked_with_counts() {
+    let keywords = keywords(&["alpha", "beta"]);
+    let documents = vec![
+        Document::new(PathBuf::from("/docs/none.pdf"), counts(&[("alpha", 3)]), 0),
+        Document::new(PathBuf::from("/docs/both.pdf"), counts(&[("alpha", 2), ("beta", 2)]), 0),
+    ];
+
+    let report = analyze_counts(documents, &keywords, &options(CorrelationMeasure::CoOccurrence, 0.0, RankingMethod::Correlation));
+
+    assert_eq!(report.total_documents(), 2);
+    assert_eq!(report.documents[0].filename, "both.pdf");
+    assert_eq!(report.documents[0].path, PathBuf::from("/docs/both.pdf"));
+    assert_eq!(report.documents[0].count("beta"), 2);
+    assert_eq!(report.documents[1].count("beta"), 0);
+
+    let ranked: Vec<&str> = report.ranked_documents().map(|d| d.filename.as_str()).collect();
+    assert_eq!(ranked, vec!["both.pdf"]);
+}
+
+#[test]
+fn test_threshold_excludes_weak_correlations() {
+    let keywords = keywords(&["alpha", "beta"]);
+    let documents = vec![
+        Document::new(PathBuf::from("a.pdf"), counts(&[("alpha", 1), ("beta", 1)]), 0),
+        Document::new(PathBuf::from("b.pdf"), counts(&[("alpha", 1)]), 0),
+    ];
+
+    // The only correlation is 0.5, below the threshold
+    let report = analyze_counts(documents, &keywords, &options(CorrelationMeasure::CoOccurrence, 0.9, RankingMethod::Correlation));
+
+    assert_eq!(report.threshold, 0.9);
+    assert!(report.ranked_documents().next().is_none());
+}
+
+#[test]
+fn test_text_report_renders_matrix_and_ranking() {
+    let keywords = keywords(&["alpha", "beta"]);
+    let documents = vec![
+        Document::new(PathBuf::from("a.pdf"), counts(&[("alpha", 1), ("beta", 1)]), 0),
+    ];
+
+    let text = analyze_counts(documents, &keywords, &options(CorrelationMeasure::CoOccurrence, 0.1, RankingMethod::Correlation)).to_text();
+
+    assert!(text.contains("Keywords: alpha, beta"));
+    assert!(text.contains("Total documents analyzed: 1"));
+    assert!(text.contains("Keyword Correlation Matrix (cooccurrence):"));
+    assert!(text.contains("0   ---- 1.00 "));
+    assert!(text.contains("1: beta"));
+    assert!(text.contains("1. a.pdf (score: 1.00)"));
+}
+
+#[test]
+fn test_analyze_requires_keywords() {
+    let result = analyze(&["/tmp".to_string()], &[], &AnalysisOptions::default());
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_analyze_records_counts_extracted_pages() {
+    let record = |pages: &[&str]| CacheRecord { pages: keywords(pages), ..Default::default() };
+    let records = vec![
+        (PathBuf::from("a.pdf"), record(&["alpha beta", "alpha"])),
+        (PathBuf::from("b.pdf"), record(&["beta"])),
+    ];
+
+    let options = AnalysisOptions { window: CooccurrenceWindow::Page, ..AnalysisOptions::default() };
+    let report = analyze_records(&records, &keywords(&["alpha", "beta"]), &options).unwrap();
+
+    assert_eq!(report.total_documents(), 2);
+    let a = report.documents.iter().find(|d| d.filename == "a.pdf").unwrap();
+    assert_eq!(a.count("alpha"), 2);
+    assert_eq!(a.count("beta"), 1);
+    assert_eq!(a.word_count, 3);
+    assert!(analyze_records(&records, &[], &options).is_err());
+}
+
+#[test]
+fn test_progress_reports_unreadable_files_and_cancel_stops() {
+    let dir = std::env::temp_dir().join(format!("pdfscan-progress-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("broken.pdf"), [0xff, 0xfe, 0x00]).unwrap();
+    let input = vec![dir.to_string_lossy().to_string()];
+    let keywords = keywords(&["alpha"]);
+
+    let reported = Mutex::new(Vec::new());
+    let report = analyze_with_progress(&input, &keywords, &AnalysisOptions::default(), &|progress| {
+        reported.lock().unwrap().push(progress);
+    }, &AtomicBool::new(false)).unwrap();
+    let reported = reported.into_inner().unwrap();
+    assert_eq!(report.total_documents(), 1);
+    assert_eq!(reported.len(), 1);
+    assert_eq!((reported[0].done, reported[0].total), (1, 1));
+    assert!(reported[0].error.is_some());
+
+    let cancelled = analyze_with_progress(&input, &keywords, &AnalysisOptions::default(), &|_| {
+        panic!("no file should be processed after cancelling");
+    }, &AtomicBool::new(true));
+    assert!(cancelled.is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn presence_documents() -> Vec<Document> {
+    vec![
+        Document::new(PathBuf::from("a.pdf"), counts(&[("alpha", 1), ("beta", 2)]), 0),
+        Document::new(PathBuf::from("b.pdf"), counts(&[("alpha", 3), ("beta", 4)]), 0),
+        Document::new(PathBuf::from("c.pdf"), counts(&[("alpha", 2)]), 0),
+        Document::new(PathBuf::from("d.pdf"), counts(&[]), 0),
+    ]
+}
+
+#[test]
+fn test_presence_measures() {
+    let keywords = keywords(&["alpha", "beta"]);
+
+    // alpha in a, b, c; beta in a, b
+    let jaccard = analyze_counts(presence_documents(), &keywords, &options(CorrelationMeasure::Jaccard, 0.0, RankingMethod::Correlation));
+    assert!((jaccard.correlations[0][1] - 2.0 / 3.0).abs() < 1e-9);
+    let mut together: Vec<&str> = jaccard.cooccurring_documents(0, 1).map(|doc| doc.filename.as_str()).collect();
+    together.sort();
+    assert_eq!(together, vec!["a.pdf", "b.pdf"]);
+
+    let dice = analyze_counts(presence_documents(), &keywords, &options(CorrelationMeasure::Dice, 0.0, RankingMethod::Correlation));
+    assert!((dice.correlations[0][1] - 0.8).abs() < 1e-9);
+
+    // NPMI is bounded and positive for keywords that tend to appear together
+    let npmi = analyze_counts(presence_documents(), &keywords, &options(CorrelationMeasure::Npmi, 0.0, RankingMethod::Correlation));
+    assert!(npmi.correlations[0][1] > 0.0 && npmi.correlations[0][1] <= 1.0);
+    assert!(npmi.statistics.is_none());
+
+    let pmi = analyze_counts(presence_documents(), &keywords, &options(CorrelationMeasure::Pmi, 0.0, RankingMethod::Correlation));
+    let expected_pmi = (0.5f64 / (0.75 * 0.5)).ln();
+    assert!((pmi.statistics.as_ref().unwrap()[0][1] - expected_pmi).abs() < 1e-9);
+    assert_eq!(pmi.correlations[0][1], npmi.correlations[0][1]);
+}
+
+#[test]
+fn test_rank_correlation_measures() {
+    let keywords = keywords(&["alpha", "beta"]);
+
+    let pearson = analyze_counts(presence_documents(), &keywords, &options(CorrelationMeasure::Pearson, 0.0, RankingMethod::Correlation));
+    let value = pearson.correlations[0][1];
+    assert!(value > 0.0 && value <= 1.0);
+
+    // Perfectly monotonic counts have a Spearman correlation of 1
+    let documents = vec![
+        Document::new(PathBuf::from("a.pdf"), counts(&[("alpha", 1), ("beta", 10)]), 0),
+        Document::new(PathBuf::from("b.pdf"), counts(&[("alpha", 2), ("beta", 20)]), 0),
+        Document::new(PathBuf::from("c.pdf"), counts(&[("alpha", 5), ("beta", 21)]), 0),
+    ];
+    let spearman = analyze_counts(documents, &keywords, &options(CorrelationMeasure::Spearman, 0.0, RankingMethod::Correlation));
+    assert!((spearman.correlations[0][1] - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_chi_square_reports_p_values() {
+    let keywords = keywords(&["alpha", "beta"]);
+    let report = analyze_counts(presence_documents(), &keywords, &options(CorrelationMeasure::ChiSquare, 0.0, RankingMethod::Correlation));
+
+    // 2x2 table: n11 = 2, n10 = 1, n01 = 0, n00 = 1
+    let chi2 = report.statistics.as_ref().unwrap()[0][1];
+    assert!((chi2 - 4.0 / 3.0).abs() < 1e-9);
+
+    let p_value = report.p_values.as_ref().unwrap()[0][1];
+    assert!((p_value - 0.2482).abs() < 1e-3);
+    // The value is the phi coefficient sqrt(chi2 / n), not the significance
+    assert!((report.correlations[0][1] - (chi2 / 4.0).sqrt()).abs() < 1e-9);
+    assert!(report.to_text().contains("p-values:"));
+}
+
+#[test]
+fn test_measure_names_round_trip() {
+    for measure in CorrelationMeasure::ALL {
+        assert_eq!(measure.name().parse::<CorrelationMeasure>(), Ok(measure));
+    }
+    assert!("cosine".parse::<CorrelationMeasure>().is_err());
+}
+
+#[test]
+fn test_tfidf_ranks_single_keyword_documents() {
+    let keywords = keywords(&["rust"]);
+    let documents = vec![
+        Document::new(PathBuf::from("short.pdf"), counts(&[("rust", 10)]), 1_000),
+        Document::new(PathBuf::from("long.pdf"), counts(&[("rust", 500)]), 100_000),
+        Document::new(PathBuf::from("none.pdf"), counts(&[]), 1_000),
+    ];
+
+    // Correlation ranking needs keyword pairs, so a single keyword scores nothing
+    let report = analyze_counts(documents.clone(), &keywords, &options(CorrelationMeasure::default(), 0.1, RankingMethod::Correlation));
+    assert!(report.ranked_documents().next().is_none());
+
+    // Length normalization puts the denser document first
+    let report = analyze_counts(documents, &keywords, &options(CorrelationMeasure::default(), 0.1, RankingMethod::TfIdf));
+    let ranked: Vec<&str> = report.ranked_documents().map(|d| d.filename.as_str()).collect();
+    assert_eq!(ranked, vec!["short.pdf", "long.pdf"]);
+}
+
+#[test]
+fn test_bm25_saturates_term_frequency() {
+    let keywords = keywords(&["alpha", "beta"]);
+    let documents = vec![
+        Document::new(PathBuf::from("spam.pdf"), counts(&[("alpha", 500)]), 1_000),
+        Document::new(PathBuf::from("both.pdf"), counts(&[("alpha", 3), ("beta", 3)]), 1_000),
+        Document::new(PathBuf::from("other.pdf"), counts(&[]), 1_000),
+    ];
+
+    let mut report = analyze_counts(documents, &keywords, &options(CorrelationMeasure::default(), 0.1, RankingMethod::Bm25));
+    assert_eq!(report.ranking, RankingMethod::Bm25);
+    assert_eq!(report.documents[0].filename, "both.pdf");
+    assert!(report.documents[1].score > 0.0);
+
+    // Re-ranking reuses the counted keywords
+    report.rerank(RankingMethod::TfIdf);
+    assert_eq!(report.ranking, RankingMethod::TfIdf);
+    assert_eq!(report.documents[0].filename, "spam.pdf");
+    assert!(report.to_text().contains("Ranked Documents by TF-IDF:"));
+}
+
+#[test]
+fn test_sentence_windows_find_contexts() {
+    let keywords = keywords(&["alpha", "beta"]);
+    let pages = vec![
+        "Alpha is here. The alpha and beta pair. Nothing else.\n\nOnly beta now.".to_string(),
+        "alpha beta alpha beta!".to_string(),
+    ];
+    let document = Document::new(PathBuf::from("report.pdf"), counts(&[("alpha", 3), ("beta", 4)]), 20);
+
+    let matcher = KeywordMatcher::new(&keywords, MatchOptions::default());
+    let windows = count_windows(&document, &pages, &matcher, CooccurrenceWindow::Sentence);
+
+    // Five sentences; only "Nothing else." contains no keyword
+    assert_eq!(windows.windows.len(), 4);
+    assert_eq!(windows.empty, 1);
+    assert_eq!(windows.contexts.len(), 2);
+    assert_eq!(windows.contexts[0].page, 1);
+    assert_eq!(windows.contexts[0].strength, 2);
+    assert_eq!(windows.contexts[0].snippet, "alpha beta alpha beta!");
+    assert_eq!(windows.contexts[1].snippet, "The alpha and beta pair.");
+
+    let options = AnalysisOptions {
+        measure: CorrelationMeasure::Jaccard,
+        window: CooccurrenceWindow::Sentence,
+        ..AnalysisOptions::default()
+    };
+    let report = analyze_counts(vec![document.with_windows(windows)], &keywords, &options);
+
+    // Jaccard over sentences: alpha and beta are in 3 sentences each, both in 2
+    assert!((report.correlations[0][1] - 2.0 / 4.0).abs() < 1e-9);
+    assert_eq!(report.contexts.len(), 2);
+    assert!(report.to_text().contains("Strongest Co-occurring Contexts:"));
+}
+
+#[test]
+fn test_window_names_parse() {
+    assert_eq!("sentence".parse::<CooccurrenceWindow>(), Ok(CooccurrenceWindow::Sentence));
+    assert_eq!("words:25".parse::<CooccurrenceWindow>(), Ok(CooccurrenceWindow::Words(25)));
+    assert_eq!(CooccurrenceWindow::Words(25).to_string(), "words:25");
+    assert!("words:0".parse::<CooccurrenceWindow>().is_err());
+    assert!("chapter".parse::<CooccurrenceWindow>().is_err());
+}
@@ -0,0 +1,51 @@
+use pdfscan::stopwords::Language;
+use pdfscan::text::{tokenize, KeywordMatcher, MatchOptions};
+
+fn keywords(words: &[&str]) -> Vec<String> {
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+#[test]
+fn test_tokenize_keeps_case_on_request() {
+    assert_eq!(tokenize("Rust's borrow-checker, again.", false), vec!["rust's", "borrow-checker", "again"]);
+    assert_eq!(tokenize("Rust's borrow-checker", true), vec!["Rust's", "borrow-checker"]);
+}
+
+#[test]
+fn test_counts_whole_words_only() {
+    let matcher = KeywordMatcher::new(&keywords(&["cat", "machine learning"]), MatchOptions::default());
+
+    let counts = matcher.count("Education about cats: a Cat, the CAT and machine\nlearning. Machine-learning is one token.");
+
+    assert_eq!(counts, vec![2, 1]);
+}
+
+#[test]
+fn test_case_sensitive_matching() {
+    let options = MatchOptions { case_sensitive: true, ..MatchOptions::default() };
+    let matcher = KeywordMatcher::new(&keywords(&["PDF"]), options);
+
+    assert_eq!(matcher.count("PDF files, pdf files and PDFs"), vec![1]);
+}
+
+#[test]
+fn test_stemming_matches_inflections() {
+    let text = "One network, two networks.";
+
+    let plain = KeywordMatcher::new(&keywords(&["network"]), MatchOptions::default());
+    assert_eq!(plain.count(text), vec![1]);
+
+    let options = MatchOptions { stemming: Some(Language::English), ..MatchOptions::default() };
+    let stemmed = KeywordMatcher::new(&keywords(&["networks"]), options);
+    assert_eq!(stemmed.count(text), vec![2]);
+}
+
+#[test]
+fn test_synonym_groups_count_as_one_keyword() {
+    let matcher = KeywordMatcher::new(&keywords(&["car|automobile|motor vehicle", "road"]), MatchOptions::default());
+
+    let counts = matcher.count("A car and an automobile share the road with a motor vehicle. Motor boats don't.");
+
+    assert_eq!(counts, vec![3, 1]);
+    assert_eq!(matcher.keywords()[0], "car|automobile|motor vehicle");
+}
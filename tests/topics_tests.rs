@@ -0,0 +1,80 @@
+use pdfscan::topics::{cluster_texts, ClusteringMethod, TopicOptions, MAX_HIERARCHICAL_DOCUMENTS};
+use std::path::PathBuf;
+
+fn corpus() -> Vec<(PathBuf, String)> {
+    let documents = [
+        ("astronomy1.pdf", "Telescopes observe distant galaxies. The telescope measured galaxy redshift and stellar orbits."),
+        ("cooking1.pdf", "Simmer the tomato sauce with garlic. Season the sauce and serve the pasta."),
+        ("astronomy2.pdf", "Stellar orbits around galaxies reveal dark matter. Redshift surveys use a telescope."),
+        ("cooking2.pdf", "Fresh pasta needs flour and eggs. Toss the pasta in garlic sauce with tomato."),
+        ("astronomy3.pdf", "Galaxies cluster in filaments; each telescope survey maps redshift and stellar mass."),
+        ("cooking3.pdf", "Roast garlic, crush tomato, and reduce the sauce before adding pasta."),
+        ("empty.pdf", ""),
+    ];
+
+    documents.iter().map(|(name, text)| (PathBuf::from(name), text.to_string())).collect()
+}
+
+fn names(members: &[pdfscan::topics::TopicMember]) -> Vec<String> {
+    let mut names: Vec<String> = members.iter().map(|m| m.path.to_string_lossy().to_string()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_clustering_separates_subjects() {
+    for method in ClusteringMethod::ALL {
+        let options = TopicOptions { method, clusters: 2, ..Default::default() };
+        let report = cluster_texts(&corpus(), &options).unwrap();
+
+        assert_eq!(report.total_documents, 7, "{}", method);
+        assert_eq!(report.without_text, vec![PathBuf::from("empty.pdf")], "{}", method);
+        assert_eq!(report.topics.len(), 2, "{}", method);
+
+        let mut groups: Vec<Vec<String>> = report.topics.iter().map(|topic| names(&topic.members)).collect();
+        groups.sort();
+        assert_eq!(groups[0], vec!["astronomy1.pdf", "astronomy2.pdf", "astronomy3.pdf"], "{}", method);
+        assert_eq!(groups[1], vec!["cooking1.pdf", "cooking2.pdf", "cooking3.pdf"], "{}", method);
+
+        for topic in &report.topics {
+            let terms: Vec<&str> = topic.terms.iter().map(|(term, _)| term.as_str()).collect();
+            if topic.members[0].path.to_string_lossy().starts_with("astronomy") {
+                assert!(terms.contains(&"telescope") && !terms.contains(&"pasta"), "{}: {:?}", method, terms);
+            } else {
+                assert!(terms.contains(&"pasta") && !terms.contains(&"the"), "{}: {:?}", method, terms);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hierarchical_clustering_refuses_large_collections() {
+    let texts: Vec<(PathBuf, String)> = (0..=MAX_HIERARCHICAL_DOCUMENTS)
+        .map(|i| (PathBuf::from(format!("{}.pdf", i)), format!("solar panels report {}", i % 7)))
+        .collect();
+
+    let options = TopicOptions { method: ClusteringMethod::Hierarchical, ..Default::default() };
+    assert!(cluster_texts(&texts, &options).is_err());
+
+    let options = TopicOptions { method: ClusteringMethod::KMeans, ..Default::default() };
+    assert_eq!(cluster_texts(&texts, &options).unwrap().total_documents, texts.len());
+}
+
+#[test]
+fn test_cluster_count_is_capped_by_documents() {
+    let texts = vec![
+        (PathBuf::from("a.pdf"), "solar panels convert sunlight".to_string()),
+        (PathBuf::from("b.pdf"), "wind turbines convert wind".to_string()),
+    ];
+    let report = cluster_texts(&texts, &TopicOptions { clusters: 5, ..Default::default() }).unwrap();
+
+    assert_eq!(report.topics.len(), 2);
+    assert!(report.to_text().contains("Topics found: 2"));
+}
+
+#[test]
+fn test_method_names_parse() {
+    assert_eq!("k-means".parse::<ClusteringMethod>(), Ok(ClusteringMethod::KMeans));
+    assert_eq!("Agglomerative".parse::<ClusteringMethod>(), Ok(ClusteringMethod::Hierarchical));
+    assert!("dbscan".parse::<ClusteringMethod>().is_err());
+}
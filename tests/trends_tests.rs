@@ -0,0 +1,50 @@
+use pdfscan::stats::Document;
+use pdfscan::trends::{group_documents, parse_pdf_date, GroupBy, Period, TrendOptions};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn document(name: &str, counts: &[(&str, usize)], word_count: usize) -> Document {
+    let counts: HashMap<String, usize> = counts.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    Document::new(PathBuf::from(name), counts, word_count)
+}
+
+#[test]
+fn test_groups_sum_counts_in_key_order() {
+    let keywords = vec!["budget".to_string(), "risk, legal".to_string()];
+    let documents = vec![
+        (Some("2024-02".to_string()), document("b.pdf", &[("budget", 3)], 1000)),
+        (Some("2024-01".to_string()), document("a1.pdf", &[("budget", 1), ("risk, legal", 2)], 500)),
+        (Some("2024-01".to_string()), document("a2.pdf", &[("budget", 1)], 500)),
+        (None, document("undated.pdf", &[("budget", 9)], 100)),
+    ];
+    let options = TrendOptions { group_by: GroupBy::Created, ..Default::default() };
+
+    let report = group_documents(&documents, &keywords, &options);
+
+    assert_eq!(report.groups.len(), 2);
+    assert_eq!(report.groups[0].key, "2024-01");
+    assert_eq!(report.groups[0].documents, 2);
+    assert_eq!(report.groups[0].counts, vec![2, 2]);
+    assert!((report.groups[0].frequency(0) - 2.0).abs() < 1e-9);
+    assert_eq!(report.groups[1].counts, vec![3, 0]);
+    assert_eq!(report.undated, vec![PathBuf::from("undated.pdf")]);
+    assert_eq!(report.grouping(), "created (month)");
+
+    let csv = report.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "group,documents,words,budget,budget per 1000 words,\"risk, legal\",\"risk, legal per 1000 words\"");
+    assert_eq!(lines[1], "2024-01,2,1000,2,2.0000,2,2.0000");
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn test_pdf_dates_map_to_period_keys() {
+    assert_eq!(parse_pdf_date("D:20240315093000+01'00'"), Some((2024, 3, 15)));
+    assert_eq!(parse_pdf_date("D:2023"), Some((2023, 1, 1)));
+    assert_eq!(parse_pdf_date("yesterday"), None);
+
+    assert_eq!(Period::Day.key(2024, 3, 5), "2024-03-05");
+    assert_eq!(Period::Month.key(2024, 3, 5), "2024-03");
+    assert_eq!(Period::Year.key(2024, 3, 5), "2024");
+    assert_eq!("mtime".parse::<GroupBy>(), Ok(GroupBy::Modified));
+}
@@ -0,0 +1,52 @@
+use pdfscan::config::{self, Config};
+use pdfscan::watch::{is_watched_pdf, Change, ChangeQueue, WatchEvent, DEBOUNCE};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_changes_are_reported_once_settled() {
+    let existing = std::env::temp_dir().join("pdfscan_watch_test.pdf");
+    std::fs::write(&existing, b"%PDF-1.4").unwrap();
+    let missing = PathBuf::from("/nonexistent/pdfscan/removed.pdf");
+
+    let start = Instant::now();
+    let mut queue = ChangeQueue::default();
+    queue.touch(existing.clone(), start);
+    queue.touch(missing.clone(), start);
+
+    // Another event while the file is still being written postpones it
+    let later = start + DEBOUNCE / 2;
+    queue.touch(existing.clone(), later);
+    assert!(queue.ready(start + DEBOUNCE / 4).is_empty());
+    assert_eq!(queue.ready(start + DEBOUNCE), vec![Change::Removed(missing)]);
+    assert!(queue.ready(later + DEBOUNCE - Duration::from_millis(1)).is_empty());
+    assert_eq!(queue.ready(later + DEBOUNCE), vec![Change::Updated(existing.clone())]);
+    assert!(queue.is_empty());
+
+    std::fs::remove_file(existing).unwrap();
+}
+
+#[test]
+fn test_only_pdfs_outside_the_cache_are_watched() {
+    // The default cache directory name, not the one in the developer's config.toml
+    config::init(Config::default());
+    assert!(is_watched_pdf(Path::new("/inbox/Report.PDF")));
+    assert!(!is_watched_pdf(Path::new("/inbox/report.txt")));
+    assert!(!is_watched_pdf(Path::new("/inbox/.pdfscan/report.pdf")));
+}
+
+#[test]
+fn test_match_event_as_json() {
+    let event = WatchEvent::Match {
+        path: PathBuf::from("/inbox/a \"b\".pdf"),
+        query: "invoice".to_string(),
+        saved: None,
+        matches: 2,
+    };
+
+    assert_eq!(
+        event.to_json("2024-01-01T00:00:00+00:00"),
+        "{\"event\": \"match\", \"time\": \"2024-01-01T00:00:00+00:00\", \"path\": \"/inbox/a \\\"b\\\".pdf\", \
+         \"query\": \"invoice\", \"saved\": null, \"matches\": 2}"
+    );
+}